@@ -0,0 +1,115 @@
+// Build time, query latency and memory of the local title index. Uses the real dataset when
+// ANIME_OFFLINE_DATABASE points at it, a generated one about the same size otherwise
+//   cargo bench --bench title_index
+
+use annie_mai::{models::fetcher::FetchOptions, utils::title_index::TitleIndex};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env, fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// Bytes currently allocated, to see what the index keeps around
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// The real dataset has a bit under 40k entries
+const GENERATED_ENTRIES: u32 = 40_000;
+const FORMATS: [&str; 3] = ["TV", "MOVIE", "OVA"];
+const WORDS: [&str; 16] = [
+    "shingeki", "kyojin", "kimi", "na", "wa", "sword", "art", "online", "hunter", "bebop", "mahou",
+    "shoujo", "tensei", "slime", "datta", "ken",
+];
+
+fn generated_dataset() -> String {
+    let word = |seed: u32| WORDS[(seed % WORDS.len() as u32) as usize];
+    let data: Vec<Value> = (1..=GENERATED_ENTRIES)
+        .map(|id| {
+            let title = format!(
+                "{} {} {} {}",
+                word(id),
+                word(id / 7),
+                word(id / 53),
+                id % 97
+            );
+            let format = FORMATS[(id % FORMATS.len() as u32) as usize];
+            json!({
+                "sources": [format!("https://anilist.co/anime/{}", id)],
+                "title": title,
+                "type": format,
+                "animeSeason": { "year": 1980 + (id % 45) },
+                "synonyms": [format!("{} {}", word(id / 3), word(id / 11)), title.to_uppercase()],
+            })
+        })
+        .collect();
+
+    json!({ "data": data }).to_string()
+}
+
+fn dataset() -> String {
+    env::var("ANIME_OFFLINE_DATABASE")
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_else(generated_dataset)
+}
+
+fn title_index(c: &mut Criterion) {
+    let json = dataset();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let index = TitleIndex::from_json(&json).expect("Valid dataset");
+    let retained = ALLOCATED.load(Ordering::Relaxed).saturating_sub(before);
+    println!(
+        "Title index: {} entries in {:.1} MiB",
+        index.len(),
+        retained as f64 / (1024.0 * 1024.0)
+    );
+
+    let options = FetchOptions::default();
+    let from_2001 = FetchOptions {
+        year: Some(2001),
+        ..Default::default()
+    };
+
+    c.bench_function("build", |b| {
+        b.iter(|| TitleIndex::from_json(black_box(&json)))
+    });
+    c.bench_function("search", |b| {
+        b.iter(|| index.search(black_box("shingeki no kyojin"), &options, 5))
+    });
+    c.bench_function("search typo", |b| {
+        b.iter(|| index.search(black_box("sord art onlin"), &options, 5))
+    });
+    c.bench_function("search filtered", |b| {
+        b.iter(|| index.search(black_box("hunter bebop"), &from_2001, 5))
+    });
+    c.bench_function("complete", |b| {
+        b.iter(|| index.complete(black_box("kimi na"), 10))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // Building the whole index is slow enough that the default 100 samples take minutes
+    config = Criterion::default().sample_size(10);
+    targets = title_index
+}
+criterion_main!(benches);
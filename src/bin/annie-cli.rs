@@ -0,0 +1,212 @@
+// Runs the same lookup as the bot from the terminal, without connecting to Discord
+//   annie-cli <anime|manga> <query...> [--format x] [--json]
+//   annie-cli complete <prefix...> [--json]
+use annie_mai::{
+    commands::{
+        anime::command::build_message_from_anime, manga::command::build_message_from_manga,
+    },
+    models::{
+        anilist_anime::Anime, anilist_manga::Manga, fetcher::FetchOptions,
+        media_type::MediaType as Type, transformers::Transformers,
+        user_preferences::DisplayPreferences,
+    },
+    utils::{
+        response_fetcher::{traced_lookup, LookupTrace},
+        title_index::title_index,
+        EMPTY_STR,
+    },
+};
+use dotenv::dotenv;
+use serde_json::{json, Map, Value};
+use serenity::{builder::CreateEmbed, json::hashmap_to_json_map};
+use std::{env, process};
+
+const USAGE: &str = "Usage: annie-cli <anime|manga> <query...> [--format <format>] [--json]
+       annie-cli complete <prefix...> [--json]";
+const JSON_FLAG: &str = "--json";
+const COMPLETIONS: usize = 10;
+
+struct Lookup {
+    // None for fallback entries without an AniList id
+    id: Option<u32>,
+    title: String,
+    // The path that found the match and the candidates it ranked, nothing else is fetched
+    trace: LookupTrace,
+    embed: Map<String, Value>,
+}
+
+fn run<T>(
+    media_type: Type,
+    query: &str,
+    build: impl FnOnce(T, &mut CreateEmbed),
+) -> Result<Lookup, String>
+where
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+{
+    // Nothing here ends up in a channel, so adult entries are fair game
+    let options = FetchOptions {
+        allow_adult: true,
+        ..Default::default()
+    };
+
+    let (media, trace) = traced_lookup::<T>(media_type, query, options)
+        .map_err(|why| why.message(media_type).to_string())?;
+
+    let id = media.get_id();
+    let title = media.transform_romaji_title();
+    let mut embed = CreateEmbed::default();
+    build(media, &mut embed);
+
+    Ok(Lookup {
+        id,
+        title,
+        trace,
+        embed: hashmap_to_json_map(embed.0),
+    })
+}
+
+fn id_text(id: Option<u32>) -> String {
+    id.map_or_else(|| EMPTY_STR.to_string(), |id| id.to_string())
+}
+
+fn text(value: Option<&Value>) -> &str {
+    value.and_then(Value::as_str).unwrap_or_default()
+}
+
+fn print_text(lookup: &Lookup) {
+    println!("Match: {} ({})", lookup.title, id_text(lookup.id));
+    println!(
+        "Found through {:?} on {}",
+        lookup.trace.path, lookup.trace.provider
+    );
+
+    if !lookup.trace.candidates.is_empty() {
+        println!("\nCandidates, best first (score / title score, matched title):");
+        for candidate in &lookup.trace.candidates {
+            println!(
+                "  {:>7}  {:.3} / {:.3}  {}  ({})",
+                id_text(candidate.id),
+                candidate.score,
+                candidate.title_score,
+                candidate.title,
+                candidate.matched
+            );
+        }
+    }
+
+    if !lookup.trace.close_calls.is_empty() {
+        println!("\nClose calls in the local index, left to the search to pick from:");
+        for close_call in &lookup.trace.close_calls {
+            println!("  {:>7}  {}", id_text(close_call.id), close_call.title);
+        }
+    }
+
+    let embed = &lookup.embed;
+    println!("\n{}", text(embed.get("title")));
+    println!("{}", text(embed.get("url")));
+    println!("\n{}", text(embed.get("description")));
+
+    let fields = embed.get("fields").and_then(Value::as_array);
+    for field in fields.into_iter().flatten() {
+        println!(
+            "\n[{}]\n{}",
+            text(field.get("name")),
+            text(field.get("value"))
+        );
+    }
+
+    let footer = embed.get("footer").and_then(|footer| footer.get("text"));
+    if footer.is_some() {
+        println!("\n-- {}", text(footer));
+    }
+}
+
+// What autocomplete would offer for a half typed title, straight from the local index
+fn print_completions(prefix: &str, as_json: bool) {
+    let index = title_index();
+    let completions: Vec<Value> = index
+        .complete(prefix, COMPLETIONS)
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "id": entry.candidate.id,
+                "title": entry.candidate.title.romaji,
+                "format": entry.candidate.format,
+                "year": entry.year,
+            })
+        })
+        .collect();
+
+    if as_json {
+        println!("{:#}", Value::Array(completions));
+        return;
+    }
+    if index.is_empty() {
+        eprintln!("No title dataset, run !refresh-ids or set ANIME_OFFLINE_DATABASE");
+    }
+    for completion in &completions {
+        println!(
+            "  {:>7}  {}",
+            completion["id"],
+            text(completion.get("title"))
+        );
+    }
+}
+
+fn main() {
+    dotenv().ok();
+    // Logs go to stderr so --json output stays parseable
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let as_json = args.iter().any(|arg| arg == JSON_FLAG);
+    args.retain(|arg| arg != JSON_FLAG);
+
+    if args.len() < 2 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    let query = args[1..].join(" ");
+    if args[0].eq_ignore_ascii_case("complete") {
+        print_completions(&query, as_json);
+        return;
+    }
+    let preferences = DisplayPreferences::default();
+
+    let result = match args[0].to_lowercase().as_str() {
+        "anime" => run::<Anime>(Type::Anime, &query, |anime, embed| {
+            build_message_from_anime(anime, &preferences, embed);
+        }),
+        "manga" => run::<Manga>(Type::Manga, &query, |manga, embed| {
+            build_message_from_manga(manga, None, &preferences, embed);
+        }),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let lookup = match result {
+        Ok(lookup) => lookup,
+        Err(why) => {
+            eprintln!("{}", why);
+            process::exit(1);
+        }
+    };
+
+    match as_json {
+        true => println!(
+            "{:#}",
+            json!({
+                "match": { "id": lookup.id, "title": lookup.title },
+                "path": lookup.trace.path,
+                "provider": lookup.trace.provider,
+                "candidates": lookup.trace.candidates,
+                "embed": lookup.embed,
+            })
+        ),
+        false => print_text(&lookup),
+    }
+}
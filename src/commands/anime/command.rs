@@ -1,6 +1,17 @@
 use crate::{
-    models::{anilist_anime::Anime, media_type::MediaType as Type, transformers::Transformers},
-    utils::{message::NOT_FOUND_ANIME, response_fetcher::fetcher},
+    commands::{config::settings::allow_adult_content, prefs::settings::display_preferences},
+    models::{
+        anilist_anime::Anime,
+        fetcher::{FetchError, FetchOptions},
+        guild_settings::Verbosity,
+        media_type::MediaType as Type,
+        transformers::Transformers,
+        user_preferences::DisplayPreferences,
+    },
+    utils::{
+        components::link_buttons, embed_budget::EmbedBudget, message::did_you_mean,
+        response_fetcher::traced_fetcher,
+    },
 };
 use serenity::{
     builder::CreateEmbed,
@@ -12,20 +23,48 @@ use tokio::task;
 use tracing::error;
 
 #[command]
+#[bucket = "lookup"]
 async fn anime(ctx: &Context, msg: &Message) -> CommandResult {
     let args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
-    let response = task::spawn_blocking(|| fetcher(Type::Anime, args)).await?;
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let options = FetchOptions {
+        allow_adult,
+        ..Default::default()
+    };
+    let response = task::spawn_blocking(move || {
+        let (mut anime, trace): (Anime, _) = traced_fetcher(Type::Anime, args, options)?;
+        if !allow_adult {
+            anime.hide_adult_relations();
+        }
+        // Close calls AniList picked between, offered by id in case it picked wrong
+        let close_calls: Vec<(String, u32)> = trace
+            .did_you_mean(anime.get_id())
+            .into_iter()
+            .filter_map(|close_call| Some((close_call.title.to_string(), close_call.id?)))
+            .collect();
+        Ok::<(Anime, Vec<(String, u32)>), FetchError>((anime, close_calls))
+    })
+    .await?;
+    let preferences = display_preferences(ctx, msg).await;
 
     let msg = match response {
-        None => {
+        Err(why) => {
             msg.channel_id
-                .send_message(&ctx.http, |m| m.content(NOT_FOUND_ANIME))
+                .send_message(&ctx.http, |m| m.content(why.message(Type::Anime)))
                 .await
         }
-        Some(anime) => {
+        Ok((anime, close_calls)) => {
+            let links = anime.transform_links(&preferences.links);
             msg.channel_id
                 .send_message(&ctx.http, |m| {
-                    m.embed(|e| build_message_from_anime(anime, e))
+                    if !close_calls.is_empty() {
+                        m.content(did_you_mean(&close_calls));
+                    }
+                    m.embed(|e| build_message_from_anime(anime, &preferences, e));
+                    if !links.is_empty() {
+                        m.components(|c| link_buttons(c, &links));
+                    }
+                    m
                 })
                 .await
         }
@@ -42,34 +81,89 @@ async fn anime(ctx: &Context, msg: &Message) -> CommandResult {
 // TODO: Maybe use https://docs.rs/serenity/latest/serenity/model/channel/struct.Message.html
 //                 https://docs.rs/serenity/latest/serenity/model/channel/struct.Embed.html
 // and send proper embeds
-fn build_message_from_anime(anime: Anime, embed: &mut CreateEmbed) -> &mut CreateEmbed {
+pub fn build_message_from_anime<'a>(
+    anime: Anime,
+    preferences: &DisplayPreferences,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    if preferences.verbosity == Verbosity::Compact {
+        return build_compact_message_from_anime(anime, preferences, embed);
+    }
+
+    // Fields go through the budget first, the description gets whatever is left
+    let mut budget = EmbedBudget::default();
     embed
         .colour(anime.transform_color())
-        .title(anime.transform_romaji_title())
-        .description(anime.transform_description_and_mal_link())
-        .fields(vec![
+        .title(budget.title(&anime.transform_title(preferences.title_language)))
+        .fields(budget.fields(vec![
             ("Type", "Anime", true),                     // Field 0
             ("Status", &anime.transform_status(), true), // Field 1
             ("Season", &anime.transform_season(), true), // Field 2
-        ])
-        .fields(vec![
+        ]))
+        .fields(budget.fields(vec![
             ("Format", &anime.transform_format(), true), // Field 3
             ("Episodes", &anime.transform_episodes(), true), // Field 4
             ("Duration", &anime.transform_duration(), true), // Field 5
-        ])
-        .fields(vec![
-            ("Source", &anime.transform_source(), true), // Field 6
-            ("Average Score", &anime.transform_score(), true), // Field 7
+        ]))
+        .fields(budget.fields(vec![
+            (
+                "Source",
+                &anime.transform_source_material(&preferences.prefix),
+                true,
+            ), // Field 6
+            (
+                "Average Score",
+                &anime.transform_score(preferences.score_format),
+                true,
+            ), // Field 7
             // ("\u{200b}", &"\u{200b}".to_string(), true), // Would add a blank field
-            ("Top Tag", &anime.transform_tags(), true), // Field 8
+            (
+                "Top Tag",
+                &anime.transform_tags(preferences.show_spoilers),
+                true,
+            ), // Field 8
+        ]))
+        .fields(budget.fields(vec![
+            ("Genres", &anime.transform_genres(), false), // Field 9
+            ("Studios", &anime.transform_studios(), false), // Field 10
+        ]))
+        .fields(budget.fields(vec![
+            ("Aired", &anime.transform_date(), true),      // Field 11
+            ("Trailer", &anime.transform_trailer(), true), // Field 12
+        ]))
+        .footer(|f| {
+            f.text(budget.footer(&anime.transform_title(preferences.title_language.secondary())))
+        })
+        .description(
+            anime.transform_description_and_mal_link(preferences.show_spoilers, &mut budget),
+        )
+        .url(anime.transform_anilist())
+        .thumbnail(anime.transform_thumbnail())
+}
+
+fn build_compact_message_from_anime<'a>(
+    anime: Anime,
+    preferences: &DisplayPreferences,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    embed
+        .colour(anime.transform_color())
+        .title(anime.transform_title(preferences.title_language))
+        .fields(vec![
+            ("Status", &anime.transform_status(), true),
+            ("Season", &anime.transform_season(), true),
+            (
+                "Average Score",
+                &anime.transform_score(preferences.score_format),
+                true,
+            ),
         ])
-        .field("Genres", &anime.transform_genres(), false) // Field 9
-        .field("Studios", &anime.transform_studios(), false) // Field 10
         .fields(vec![
-            ("Streaming", &anime.transform_links(), true), // Field 11
-            ("Trailer", &anime.transform_trailer(), true), // Field 12
+            ("Format", &anime.transform_format(), true),
+            ("Episodes", &anime.transform_episodes(), true),
+            ("Studios", &anime.transform_studios(), true),
         ])
-        .footer(|f| f.text(anime.transform_english_title()))
-        .url(&anime.transform_anilist())
+        .footer(|f| f.text(anime.transform_title(preferences.title_language.secondary())))
+        .url(anime.transform_anilist())
         .thumbnail(anime.transform_thumbnail())
 }
@@ -1,117 +1,18 @@
-pub const FETCH_ANIME_BY_ID: &str = "
-query ($id: Int) {
-  Media (id: $id, type: ANIME) {
-    type
-    id
-    idMal
-    title {
-      romaji
-      english
-      native
-    }
-    synonyms
-    season
-    seasonYear
-    format
-    status
-    episodes
-    duration
-    genres
-    source
-    coverImage {
-      extraLarge
-      large
-      medium
-      color
-    }
-    averageScore
-    studios {
-      edges {
-        id
-        isMain
-      }
-      nodes {
-        id
-        name
-      }
-    }
-    siteUrl
-    externalLinks {
-      url
-      type
-    }
-    trailer {
-      id
-      site
-    }
-    description
-    tags {
-      name
-    }
-  }
-}
-";
+use crate::utils::graphql::{Query, ANIME_DETAILS, MEDIA_CORE, RELATIONS};
+use std::sync::LazyLock;
 
-pub const FETCH_ANIME: &str = "
-query ($page: Int, $perPage: Int, $search: String) {
-  Page(page: $page, perPage: $perPage) {
-    pageInfo {
-      total
-      currentPage
-      lastPage
-      hasNextPage
-      perPage
-    }
-    media(search: $search) {
-      type
-      id
-      idMal
-      title {
-        romaji
-        english
-        native
-      }
-      synonyms
-      season
-      seasonYear
-      format
-      status
-      episodes
-      duration
-      genres
-      source
-      coverImage {
-        extraLarge
-        large
-        medium
-        color
-      }
-      averageScore
-      studios {
-        edges {
-          id
-          isMain
-        }
-        nodes {
-          id
-          name
-        }
-      }
-      siteUrl
-      externalLinks {
-        url
-        type
-      }
-      trailer {
-        id
-        site
-      }
-      description
-      tags {
-        name
-      }
-    }
-  }
-}
+pub static FETCH_ANIME_BY_ID: LazyLock<String> = LazyLock::new(|| {
+    Query::media_by_id("AnimeById", "Media(id: $id, idMal: $idMal, type: ANIME)")
+        .fragment(&MEDIA_CORE)
+        .fragment(&ANIME_DETAILS)
+        .fragment(&RELATIONS)
+        .build()
+});
 
-";
+pub static FETCH_ANIME: LazyLock<String> = LazyLock::new(|| {
+    Query::media_search("AnimeSearch")
+        .fragment(&MEDIA_CORE)
+        .fragment(&ANIME_DETAILS)
+        .fragment(&RELATIONS)
+        .build()
+});
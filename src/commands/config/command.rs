@@ -0,0 +1,295 @@
+use super::settings::{guild_settings, update_guild_settings};
+use crate::{
+    models::guild_settings::{Cooldown, GuildSettings, ScoreFormat, TitleLanguage, Verbosity},
+    utils::{
+        formatter::code,
+        message::{CONFIG_USAGE, COOLDOWN_USAGE},
+    },
+    GENERAL_GROUP,
+};
+use chrono_tz::Tz;
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::channel::Message,
+};
+use tracing::error;
+
+// These can never be disabled, otherwise there is no way back
+const PROTECTED_COMMANDS: [&str; 2] = ["config", "help"];
+const MAX_PREFIX_LENGTH: usize = 5;
+const CLEAR_LIST: &str = "none";
+
+enum ConfigChange {
+    Prefix(String),
+    TitleLanguage(TitleLanguage),
+    AllowNsfw(bool),
+    Verbosity(Verbosity),
+    ScoreFormat(ScoreFormat),
+    ShowSpoilers(bool),
+    Timezone(String),
+    PreferredSites(Vec<String>),
+    BlockedSites(Vec<String>),
+    LinkLanguages(Vec<String>),
+    Enable(String),
+    Disable(String),
+    // None goes back to the default buckets
+    Cooldown(String, Option<Cooldown>),
+    Reset,
+}
+
+impl ConfigChange {
+    fn apply(self, settings: &mut GuildSettings) {
+        match self {
+            ConfigChange::Prefix(prefix) => settings.prefix = prefix,
+            ConfigChange::TitleLanguage(language) => settings.title_language = language,
+            ConfigChange::AllowNsfw(allow) => settings.allow_nsfw = allow,
+            ConfigChange::Verbosity(verbosity) => settings.verbosity = verbosity,
+            ConfigChange::ScoreFormat(format) => settings.score_format = format,
+            ConfigChange::ShowSpoilers(show) => settings.show_spoilers = show,
+            ConfigChange::Timezone(timezone) => settings.timezone = timezone,
+            ConfigChange::PreferredSites(sites) => settings.links.preferred_sites = sites,
+            ConfigChange::BlockedSites(sites) => settings.links.blocked_sites = sites,
+            ConfigChange::LinkLanguages(languages) => settings.links.languages = languages,
+            ConfigChange::Enable(command) => {
+                settings.disabled_commands.remove(&command);
+            }
+            ConfigChange::Disable(command) => {
+                settings.disabled_commands.insert(command);
+            }
+            ConfigChange::Cooldown(command, Some(cooldown)) => {
+                settings.cooldowns.insert(command, cooldown);
+            }
+            ConfigChange::Cooldown(command, None) => {
+                settings.cooldowns.remove(&command);
+            }
+            ConfigChange::Reset => *settings = GuildSettings::default(),
+        }
+    }
+}
+
+pub fn parse_toggle(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "on" | "true" | "yes" | "allow" => Some(true),
+        "off" | "false" | "no" | "deny" => Some(false),
+        _ => None,
+    }
+}
+
+// Comma separated, "none" clears the list
+fn parse_list(value: &str) -> Vec<String> {
+    match value.eq_ignore_ascii_case(CLEAR_LIST) {
+        true => vec![],
+        false => value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+    }
+}
+
+fn describe_list(list: &[String], empty: &str) -> String {
+    match list.is_empty() {
+        true => empty.to_string(),
+        false => list.join(", "),
+    }
+}
+
+fn known_command(name: &str) -> Option<String> {
+    GENERAL_GROUP
+        .options
+        .commands
+        .iter()
+        .flat_map(|command| command.options.names.iter())
+        .find(|command_name| command_name.eq_ignore_ascii_case(name))
+        .map(|command_name| command_name.to_string())
+}
+
+// "<command> <uses>/<seconds> [user|channel|guild]" or "<command> none"
+fn parse_cooldown(value: &str) -> Result<ConfigChange, String> {
+    let mut words = value.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let Some(command) = known_command(name) else {
+        return Err(format!("Unknown command {}", code(name.to_string())));
+    };
+
+    match (words.next(), words.next(), words.next()) {
+        (Some(limit), None, None) if limit.eq_ignore_ascii_case(CLEAR_LIST) => {
+            Ok(ConfigChange::Cooldown(command, None))
+        }
+        (Some(limit), scope, None) => Cooldown::from_args(limit, scope)
+            .map(|cooldown| ConfigChange::Cooldown(command, Some(cooldown)))
+            .ok_or_else(|| COOLDOWN_USAGE.to_string()),
+        _ => Err(COOLDOWN_USAGE.to_string()),
+    }
+}
+
+fn parse_change(key: &str, value: Option<&str>) -> Result<ConfigChange, String> {
+    let value = value.map(|value| value.trim()).unwrap_or_default();
+
+    match key {
+        "reset" => Ok(ConfigChange::Reset),
+        "prefix" => match value {
+            _ if value.is_empty() || value.chars().any(char::is_whitespace) => {
+                Err("The prefix can't be empty or contain spaces".to_string())
+            }
+            _ if value.chars().count() > MAX_PREFIX_LENGTH => Err(format!(
+                "The prefix can be at most {} characters long",
+                MAX_PREFIX_LENGTH
+            )),
+            _ => Ok(ConfigChange::Prefix(value.to_string())),
+        },
+        "title" => TitleLanguage::from_arg(value)
+            .map(ConfigChange::TitleLanguage)
+            .ok_or_else(|| "Title language must be romaji, english or native".to_string()),
+        "nsfw" => parse_toggle(value)
+            .map(ConfigChange::AllowNsfw)
+            .ok_or_else(|| "NSFW must be on or off".to_string()),
+        "verbosity" => Verbosity::from_arg(value)
+            .map(ConfigChange::Verbosity)
+            .ok_or_else(|| "Verbosity must be full or compact".to_string()),
+        "score" => ScoreFormat::from_arg(value)
+            .map(ConfigChange::ScoreFormat)
+            .ok_or_else(|| {
+                "Score format must be point_100, point_10_decimal, point_10, point_5, point_3 or stars".to_string()
+            }),
+        "spoilers" => parse_toggle(value)
+            .map(ConfigChange::ShowSpoilers)
+            .ok_or_else(|| "Spoilers must be on or off".to_string()),
+        "timezone" => value
+            .parse::<Tz>()
+            .map(|timezone| ConfigChange::Timezone(timezone.name().to_string()))
+            .map_err(|_| {
+                format!(
+                    "Unknown timezone {}, try something like Asia/Tokyo",
+                    code(value.to_string())
+                )
+            }),
+        "prefer" => Ok(ConfigChange::PreferredSites(parse_list(value))),
+        "block" => Ok(ConfigChange::BlockedSites(parse_list(value))),
+        "languages" => Ok(ConfigChange::LinkLanguages(parse_list(value))),
+        "enable" | "disable" => match known_command(value) {
+            None => Err(format!("Unknown command {}", code(value.to_string()))),
+            Some(command) if PROTECTED_COMMANDS.contains(&command.as_str()) => {
+                Err(format!("{} can't be disabled", code(command)))
+            }
+            Some(command) if key == "enable" => Ok(ConfigChange::Enable(command)),
+            Some(command) => Ok(ConfigChange::Disable(command)),
+        },
+        "cooldown" => parse_cooldown(value),
+        _ => Err(CONFIG_USAGE.to_string()),
+    }
+}
+
+fn build_message_from_settings(
+    settings: GuildSettings,
+    embed: &mut CreateEmbed,
+) -> &mut CreateEmbed {
+    let mut disabled_commands = settings
+        .disabled_commands
+        .iter()
+        .map(|command| code(command.to_string()))
+        .collect::<Vec<String>>();
+    disabled_commands.sort();
+    let disabled_commands = match disabled_commands.is_empty() {
+        true => "None".to_string(),
+        false => disabled_commands.join(" "),
+    };
+    let mut cooldowns = settings
+        .cooldowns
+        .iter()
+        .map(|(command, cooldown)| format!("{} {}", code(command.to_string()), cooldown.describe()))
+        .collect::<Vec<String>>();
+    cooldowns.sort();
+    let cooldowns = match cooldowns.is_empty() {
+        true => "Defaults".to_string(),
+        false => cooldowns.join("\n"),
+    };
+
+    embed
+        .colour(0x00ff00)
+        .title("Server Settings")
+        .fields(vec![
+            ("Prefix", code(settings.prefix.to_string()), true),
+            (
+                "Title Language",
+                settings.title_language.name().to_string(),
+                true,
+            ),
+            (
+                "NSFW",
+                (if settings.allow_nsfw { "on" } else { "off" }).to_string(),
+                true,
+            ),
+        ])
+        .fields(vec![
+            ("Verbosity", settings.verbosity.name().to_string(), true),
+            (
+                "Score Format",
+                settings.score_format.name().to_string(),
+                true,
+            ),
+            (
+                "Spoilers",
+                (if settings.show_spoilers { "on" } else { "off" }).to_string(),
+                true,
+            ),
+        ])
+        .field("Timezone", settings.tz().name().to_string(), true)
+        .field(
+            "Links",
+            format!(
+                "Preferred: {}\nBlocked: {}\nLanguages: {}",
+                describe_list(&settings.links.preferred_sites, "None"),
+                describe_list(&settings.links.blocked_sites, "None"),
+                describe_list(&settings.links.languages, "Any"),
+            ),
+            false,
+        )
+        .field("Disabled Commands", disabled_commands, false)
+        .field("Cooldowns", cooldowns, false)
+        .footer(|f| f.text(CONFIG_USAGE))
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn config(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
+    // Skips over the first arg because this is the command name
+    args.advance();
+
+    let guild_id = msg.guild_id.unwrap();
+    let key = args.single::<String>().ok().map(|key| key.to_lowercase());
+
+    let msg = match key {
+        None => {
+            let settings = guild_settings(ctx, Some(guild_id)).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| build_message_from_settings(settings, e))
+                })
+                .await
+        }
+        Some(key) => match parse_change(&key, args.remains()) {
+            Err(why) => msg.channel_id.say(&ctx.http, why).await,
+            Ok(change) => {
+                let settings =
+                    update_guild_settings(ctx, guild_id, |settings| change.apply(settings)).await;
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.content("Settings updated")
+                            .embed(|e| build_message_from_settings(settings, e))
+                    })
+                    .await
+            }
+        },
+    };
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
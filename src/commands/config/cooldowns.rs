@@ -0,0 +1,130 @@
+use super::settings::guild_settings;
+use crate::models::guild_settings::{Cooldown, CooldownScope};
+use serenity::{
+    client::Context,
+    model::channel::Message,
+    prelude::{Mutex, TypeMapKey},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// Guild ID, command name and the user, channel or guild sharing the uses
+type CooldownKey = (u64, String, u64);
+
+#[derive(Debug, Default)]
+struct Uses {
+    times: VecDeque<Instant>,
+    span: Duration,
+    // Only the first blocked try gets told to wait, like the default buckets
+    notified: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Limited {
+    pub wait: Duration,
+    pub is_first_try: bool,
+}
+
+// Uses of the cooldowns servers set with !config. Only kept in memory, a restart forgets them
+#[derive(Debug, Default)]
+pub struct CooldownTracker {
+    uses: HashMap<CooldownKey, Uses>,
+}
+
+impl CooldownTracker {
+    // Counts a use, or says how long until the next one when the cooldown is used up
+    pub fn take(
+        &mut self,
+        guild_id: u64,
+        command_name: &str,
+        holder: u64,
+        cooldown: &Cooldown,
+        now: Instant,
+    ) -> Result<(), Limited> {
+        // Cooldowns that ran out don't need to be kept around
+        self.uses.retain(|_, uses| {
+            uses.times
+                .back()
+                .is_some_and(|last| now.duration_since(*last) < uses.span)
+        });
+
+        let span = Duration::from_secs(cooldown.seconds);
+        let uses = self
+            .uses
+            .entry((guild_id, command_name.to_string(), holder))
+            .or_default();
+        // The cooldown may have been changed since
+        uses.span = span;
+        while uses
+            .times
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= span)
+        {
+            uses.times.pop_front();
+        }
+
+        if uses.times.len() < cooldown.uses as usize {
+            uses.times.push_back(now);
+            uses.notified = false;
+            return Ok(());
+        }
+
+        // Lowering the uses can leave more than allowed, all of those have to run out
+        let oldest = uses.times[uses.times.len() - cooldown.uses as usize];
+        let wait = span.saturating_sub(now.duration_since(oldest));
+        let is_first_try = !uses.notified;
+        uses.notified = true;
+        Err(Limited { wait, is_first_try })
+    }
+}
+
+pub struct Cooldowns;
+
+impl TypeMapKey for Cooldowns {
+    type Value = Arc<Mutex<CooldownTracker>>;
+}
+
+impl Cooldowns {
+    pub fn open() -> Arc<Mutex<CooldownTracker>> {
+        Arc::new(Mutex::new(CooldownTracker::default()))
+    }
+}
+
+fn holder(msg: &Message, scope: CooldownScope) -> u64 {
+    match scope {
+        CooldownScope::User => msg.author.id.0,
+        CooldownScope::Channel => msg.channel_id.0,
+        CooldownScope::Guild => msg.guild_id.map(|guild_id| guild_id.0).unwrap_or_default(),
+    }
+}
+
+// The guild's own cooldown for the command, DMs and commands without one are never limited
+pub async fn take_cooldown(
+    ctx: &Context,
+    msg: &Message,
+    command_name: &str,
+) -> Result<(), Limited> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+    let settings = guild_settings(ctx, Some(guild_id)).await;
+    let Some(cooldown) = settings.cooldown(command_name) else {
+        return Ok(());
+    };
+
+    let tracker = {
+        let data = ctx.data.read().await;
+        data.get::<Cooldowns>().unwrap().clone()
+    };
+    let mut tracker = tracker.lock().await;
+    tracker.take(
+        guild_id.0,
+        &command_name.to_lowercase(),
+        holder(msg, cooldown.scope),
+        cooldown,
+        Instant::now(),
+    )
+}
@@ -0,0 +1,67 @@
+use crate::{models::guild_settings::GuildSettings, utils::store::JsonStore};
+use serenity::{
+    client::Context,
+    model::{channel::Channel, channel::Message, id::GuildId},
+    prelude::{RwLock, TypeMapKey},
+};
+use std::{collections::HashMap, sync::Arc};
+
+const GUILD_SETTINGS_STORE: &str = "guild_settings";
+
+// Guild ID -> Settings
+pub type Settings = HashMap<u64, GuildSettings>;
+
+pub struct GuildConfig;
+
+impl TypeMapKey for GuildConfig {
+    type Value = Arc<RwLock<JsonStore<Settings>>>;
+}
+
+impl GuildConfig {
+    pub fn open() -> Arc<RwLock<JsonStore<Settings>>> {
+        Arc::new(RwLock::new(JsonStore::open(GUILD_SETTINGS_STORE)))
+    }
+}
+
+async fn store(ctx: &Context) -> Arc<RwLock<JsonStore<Settings>>> {
+    let data = ctx.data.read().await;
+    data.get::<GuildConfig>().unwrap().clone()
+}
+
+// DMs and unconfigured guilds get the defaults
+pub async fn guild_settings(ctx: &Context, guild_id: Option<GuildId>) -> GuildSettings {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id.0,
+        None => return GuildSettings::default(),
+    };
+
+    let store = store(ctx).await;
+    let store = store.read().await;
+    store.data.get(&guild_id).cloned().unwrap_or_default()
+}
+
+pub async fn update_guild_settings<F>(ctx: &Context, guild_id: GuildId, update: F) -> GuildSettings
+where
+    F: FnOnce(&mut GuildSettings),
+{
+    let store = store(ctx).await;
+    let mut store = store.write().await;
+    let settings = {
+        let settings = store.data.entry(guild_id.0).or_default();
+        update(settings);
+        settings.clone()
+    };
+    store.save();
+
+    settings
+}
+
+// Adult entries need both an NSFW channel and a guild that allows them, DMs never get them
+pub async fn allow_adult_content(ctx: &Context, msg: &Message) -> bool {
+    let is_nsfw_channel = match msg.channel(ctx).await {
+        Ok(Channel::Guild(channel)) => channel.is_nsfw(),
+        _ => false,
+    };
+
+    is_nsfw_channel && guild_settings(ctx, msg.guild_id).await.allow_nsfw
+}
@@ -1,3 +1,4 @@
+use crate::commands::config::settings::guild_settings;
 use serenity::{
     client::Context,
     framework::standard::{macros::command, CommandResult},
@@ -6,8 +7,85 @@ use serenity::{
 
 use tracing::error;
 
+// (Command name, Arguments, Description)
+const COMMANDS: [(&str, &str, &str); 14] = [
+    (
+        "anime",
+        "<id/link/search term> [--format tv/movie/ova/...] [--year 2019]",
+        "Search for an anime",
+    ),
+    (
+        "manga",
+        "<id/link/search term> [--format manga/novel/oneshot] [--year 2019]",
+        "Search for a manga",
+    ),
+    ("ln", "<anilist id/search term>", "Search for a light novel"),
+    (
+        "chapters",
+        "<anilist id/search term> [language]",
+        "Latest chapters on MangaDex",
+    ),
+    (
+        "follow-manga",
+        "<anilist id/search term> [language]",
+        "Post new chapters in this channel (admins only)",
+    ),
+    (
+        "unfollow-manga",
+        "<title/anilist id>",
+        "Stop posting new chapters (admins only)",
+    ),
+    (
+        "songs",
+        "<anilist id/search term>",
+        "Lookup the anime's songs",
+    ),
+    (
+        "watchorder",
+        "<anilist id/search term> [--no-specials] [--no-recaps]",
+        "What to watch and in which order",
+    ),
+    (
+        "quiz",
+        "[easy/hard] [rounds]",
+        "Start a guess-the-anime quiz",
+    ),
+    ("leaderboard", "", "Show the quiz leaderboard"),
+    (
+        "config",
+        "[setting] [value]",
+        "Change server settings (admins only)",
+    ),
+    (
+        "prefs",
+        "[setting] [value]",
+        "Change your own display preferences",
+    ),
+    (
+        "refresh-ids",
+        "",
+        "Download the latest MAL/Kitsu/AniDB id mappings (owner only)",
+    ),
+    ("help", "", "Show this message"),
+];
+
 #[command]
 async fn help(ctx: &Context, msg: &Message) -> CommandResult {
+    let settings = guild_settings(ctx, msg.guild_id).await;
+    let fields = COMMANDS
+        .iter()
+        .filter(|(name, _, _)| settings.is_enabled(name))
+        .map(|(name, arguments, description)| {
+            (
+                format!("{}{} {}", settings.prefix, name, arguments)
+                    .trim()
+                    .to_string(),
+                description.to_string(),
+                false,
+            )
+        })
+        .collect::<Vec<(String, String, bool)>>();
+
     let msg = msg
         .channel_id
         .send_message(&ctx.http, |m| {
@@ -15,22 +93,7 @@ async fn help(ctx: &Context, msg: &Message) -> CommandResult {
                 e.colour(0x00ff00)
                     .title("Hello there!")
                     .description("Use these commands to interact with Anilist!")
-                    .field(
-                        "!anime <anilist id/search term>",
-                        "Search for an anime",
-                        false,
-                    )
-                    .field(
-                        "!manga <anilist id/search term>",
-                        "Search for a manga",
-                        false,
-                    )
-                    .field(
-                        "!songs <anilist id/search term>",
-                        "Lookup the anime's songs",
-                        false,
-                    )
-                    .field("!help", "Show this message", false)
+                    .fields(fields)
                     .footer(|f| f.text("Annie Mai"))
                     .timestamp(chrono::Utc::now())
                     .thumbnail("attachment://mai.jpg")
@@ -0,0 +1,29 @@
+use crate::utils::id_mapper::refresh;
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, CommandResult},
+    model::channel::Message,
+};
+use tokio::task;
+use tracing::error;
+
+#[command("refresh-ids")]
+#[owners_only]
+async fn refresh_ids(ctx: &Context, msg: &Message) -> CommandResult {
+    let typing = msg.channel_id.start_typing(&ctx.http);
+    let response = task::spawn_blocking(refresh).await?;
+    if let Ok(typing) = typing {
+        let _ = typing.stop();
+    }
+
+    let content = match response {
+        Ok(entries) => format!("Loaded {} id mappings", entries),
+        Err(why) => why,
+    };
+
+    if let Err(why) = msg.channel_id.say(&ctx.http, content).await {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
@@ -1,6 +1,22 @@
 use crate::{
-    models::{anilist_manga::Manga, media_type::MediaType as Type, transformers::Transformers},
-    utils::{message::NOT_FOUND_MANGA, response_fetcher::fetcher},
+    commands::{config::settings::allow_adult_content, prefs::settings::display_preferences},
+    models::{
+        anilist_manga::Manga,
+        fetcher::{FetchError, FetchOptions},
+        guild_settings::Verbosity,
+        media_format::MediaFormat,
+        media_type::MediaType as Type,
+        transformers::Transformers,
+        user_preferences::DisplayPreferences,
+    },
+    utils::{
+        components::link_buttons,
+        embed_budget::EmbedBudget,
+        formatter::linker,
+        mangadex::{self, find_manga},
+        response_fetcher::fetcher,
+        EMPTY_STR,
+    },
 };
 use serenity::{
     builder::CreateEmbed,
@@ -12,20 +28,56 @@ use tokio::task;
 use tracing::error;
 
 #[command]
+#[bucket = "lookup"]
 async fn manga(ctx: &Context, msg: &Message) -> CommandResult {
+    send_manga(ctx, msg, None).await
+}
+
+#[command]
+#[bucket = "lookup"]
+async fn ln(ctx: &Context, msg: &Message) -> CommandResult {
+    send_manga(ctx, msg, Some(vec![MediaFormat::Novel])).await
+}
+
+// `format_in` pins the lookup to those formats, otherwise `--format` decides
+async fn send_manga(
+    ctx: &Context,
+    msg: &Message,
+    format_in: Option<Vec<MediaFormat>>,
+) -> CommandResult {
     let args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
-    let response = task::spawn_blocking(|| fetcher(Type::Manga, args)).await?;
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let options = FetchOptions {
+        allow_adult,
+        format_in,
+        ..Default::default()
+    };
+    let response = task::spawn_blocking(move || {
+        let mut manga: Manga = fetcher(Type::Manga, args, options)?;
+        if !allow_adult {
+            manga.hide_adult_relations();
+        }
+        let read_link = find_manga(mangadex::client().as_ref(), &manga).map(|entry| entry.url());
+        Ok::<(Manga, Option<String>), FetchError>((manga, read_link))
+    })
+    .await?;
+    let preferences = display_preferences(ctx, msg).await;
 
     let msg = match response {
-        None => {
+        Err(why) => {
             msg.channel_id
-                .send_message(&ctx.http, |m| m.content(NOT_FOUND_MANGA))
+                .send_message(&ctx.http, |m| m.content(why.message(Type::Manga)))
                 .await
         }
-        Some(manga) => {
+        Ok((manga, read_link)) => {
+            let links = manga.transform_links(&preferences.links);
             msg.channel_id
                 .send_message(&ctx.http, |m| {
-                    m.embed(|e| build_message_from_manga(manga, e))
+                    m.embed(|e| build_message_from_manga(manga, read_link, &preferences, e));
+                    if !links.is_empty() {
+                        m.components(|c| link_buttons(c, &links));
+                    }
+                    m
                 })
                 .await
         }
@@ -42,31 +94,92 @@ async fn manga(ctx: &Context, msg: &Message) -> CommandResult {
 // TODO: Maybe use https://docs.rs/serenity/latest/serenity/model/channel/struct.Message.html
 //                 https://docs.rs/serenity/latest/serenity/model/channel/struct.Embed.html
 // and send proper embeds
-fn build_message_from_manga(manga: Manga, embed: &mut CreateEmbed) -> &mut CreateEmbed {
+pub fn build_message_from_manga<'a>(
+    manga: Manga,
+    read_link: Option<String>,
+    preferences: &DisplayPreferences,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    if preferences.verbosity == Verbosity::Compact {
+        return build_compact_message_from_manga(manga, preferences, embed);
+    }
+
+    let read_link = read_link
+        .map(|url| linker("MangaDex".to_string(), url))
+        .unwrap_or_else(|| EMPTY_STR.to_string());
+
+    // Fields go through the budget first, the description gets whatever is left
+    let mut budget = EmbedBudget::default();
     embed
         .colour(manga.transform_color())
-        .title(manga.transform_romaji_title())
-        .description(manga.transform_description_and_mal_link())
-        .fields(vec![
+        .title(budget.title(&manga.transform_title(preferences.title_language)))
+        .fields(budget.fields(vec![
             ("Type", "Manga", true),                          // Field 0
             ("Status", &manga.transform_status(), true),      // Field 1
             ("Serialization", &manga.transform_date(), true), // Field 2
-        ])
-        .fields(vec![
+        ]))
+        .fields(budget.fields(vec![
             ("Format", &manga.transform_format(), true), // Field 3
             ("Chapters", &manga.transform_chapters(), true), // Field 4
             ("Volumes", &manga.transform_volumes(), true), // Field 5
-        ])
-        .fields(vec![
+        ]))
+        .fields(budget.fields(vec![
             ("Source", &manga.transform_source(), true), // Field 6
-            ("Average Score", &manga.transform_score(), true), // Field 7
+            (
+                "Average Score",
+                &manga.transform_score(preferences.score_format),
+                true,
+            ), // Field 7
             // ("\u{200b}", &"\u{200b}".to_string(), true), // Would add a blank field
-            ("Top Tag", &manga.transform_tags(), true), // Field 8
+            (
+                "Top Tag",
+                &manga.transform_tags(preferences.show_spoilers),
+                true,
+            ), // Field 8
+        ]))
+        .fields(budget.fields(vec![
+            ("Genres", &manga.transform_genres(), false), // Field 9
+            ("Staff", &manga.transform_staff(), false),   // Field 10
+            (
+                "Adaptations",
+                &manga.transform_adaptations(&preferences.prefix),
+                false,
+            ), // Field 11
+            ("Read", &read_link, false),                  // Field 12
+        ]))
+        .footer(|f| {
+            f.text(budget.footer(&manga.transform_title(preferences.title_language.secondary())))
+        })
+        .description(
+            manga.transform_description_and_mal_link(preferences.show_spoilers, &mut budget),
+        )
+        .url(manga.transform_anilist())
+        .thumbnail(manga.transform_thumbnail())
+}
+
+fn build_compact_message_from_manga<'a>(
+    manga: Manga,
+    preferences: &DisplayPreferences,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    embed
+        .colour(manga.transform_color())
+        .title(manga.transform_title(preferences.title_language))
+        .fields(vec![
+            ("Status", &manga.transform_status(), true),
+            ("Serialization", &manga.transform_date(), true),
+            (
+                "Average Score",
+                &manga.transform_score(preferences.score_format),
+                true,
+            ),
+        ])
+        .fields(vec![
+            ("Format", &manga.transform_format(), true),
+            ("Chapters", &manga.transform_chapters(), true),
+            ("Staff", &manga.transform_staff(), true),
         ])
-        .field("Genres", &manga.transform_genres(), false) // Field 9
-        .field("Staff", &manga.transform_staff(), false) // Field 10
-        // TODO:Add reader link -> mangaDex? -> HOW TF Do I get this
-        .footer(|f| f.text(manga.transform_english_title()))
-        .url(&manga.transform_anilist())
+        .footer(|f| f.text(manga.transform_title(preferences.title_language.secondary())))
+        .url(manga.transform_anilist())
         .thumbnail(manga.transform_thumbnail())
 }
@@ -0,0 +1,109 @@
+use super::fetcher::fetcher as ChaptersFetcher;
+use crate::{
+    commands::{
+        config::settings::{allow_adult_content, guild_settings},
+        prefs::settings::display_preferences,
+    },
+    models::{mangadex_response::Chapter, transformers::Transformers},
+    utils::{
+        formatter::{code, linker},
+        mangadex::DEFAULT_LANGUAGE,
+        message::NO_CHAPTERS,
+    },
+};
+use chrono_tz::Tz;
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::channel::Message,
+};
+use tokio::task;
+use tracing::error;
+
+// Languages MangaDex has a decent amount of scanlations in, anything else is part of the title
+const LANGUAGES: [&str; 18] = [
+    "en", "es", "es-la", "pt", "pt-br", "fr", "de", "it", "ru", "id", "vi", "th", "pl", "tr", "ar",
+    "zh", "zh-hk", "ko",
+];
+
+// Takes the language off the end, "!chapters es" is still a search for "es"
+pub fn split_language(content: &str) -> (String, String) {
+    let mut words = content.split(' ').collect::<Vec<&str>>();
+    let language = match words.last().map(|word| word.to_lowercase()) {
+        Some(last) if words.len() > 2 && LANGUAGES.contains(&last.as_str()) => {
+            words.pop();
+            last
+        }
+        _ => DEFAULT_LANGUAGE.to_string(),
+    };
+
+    (words.join(" "), language)
+}
+
+pub fn format_chapter(chapter: &Chapter, tz: Tz) -> String {
+    let details = [
+        chapter.group().map(code),
+        Some(chapter.attributes.translated_language.to_uppercase()),
+        chapter.published_at().map(|published| {
+            published
+                .with_timezone(&tz)
+                .format("%Y-%m-%d %H:%M %Z")
+                .to_string()
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<String>>()
+    .join(" · ");
+
+    format!("{}\n{}", linker(chapter.label(), chapter.url()), details)
+}
+
+#[command]
+#[bucket = "lookup"]
+async fn chapters(ctx: &Context, msg: &Message) -> CommandResult {
+    let (content, language) = split_language(&msg.content);
+    let args = Args::new(&content, &[Delimiter::Single(' ')]);
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let response =
+        task::spawn_blocking(move || ChaptersFetcher(args, language, allow_adult)).await?;
+    let tz = guild_settings(ctx, msg.guild_id).await.tz();
+    let preferences = display_preferences(ctx, msg).await;
+
+    let msg = match response {
+        Err(why) => msg.channel_id.say(&ctx.http, why.message()).await,
+        Ok(result) => {
+            let description = match result.chapters.is_empty() {
+                true => NO_CHAPTERS.to_string(),
+                false => result
+                    .chapters
+                    .iter()
+                    .map(|chapter| format_chapter(chapter, tz))
+                    .collect::<Vec<String>>()
+                    .join("\n\n"),
+            };
+
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(result.manga.transform_color())
+                            .title(format!(
+                                "Latest Chapters: {}",
+                                result.manga.transform_title(preferences.title_language)
+                            ))
+                            .url(result.mangadex.url())
+                            .description(description)
+                            .thumbnail(result.manga.transform_thumbnail())
+                            .footer(|f| f.text("Chapters from MangaDex"))
+                    })
+                })
+                .await
+        }
+    };
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
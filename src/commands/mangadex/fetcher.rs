@@ -0,0 +1,66 @@
+use crate::{
+    models::{
+        anilist_manga::Manga,
+        fetcher::{FetchError, FetchOptions},
+        mangadex_response::{Chapter, MangaDexManga},
+        media_type::MediaType as Type,
+    },
+    utils::{
+        mangadex::{client, find_manga},
+        message::{MANGADEX_UNAVAILABLE, NOT_ON_MANGADEX},
+        response_fetcher::fetcher as manga_fetcher,
+    },
+};
+use serenity::framework::standard::Args;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaptersError {
+    Lookup(FetchError),
+    NotOnMangaDex,
+    Unavailable,
+}
+
+impl ChaptersError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ChaptersError::Lookup(why) => why.message(Type::Manga),
+            ChaptersError::NotOnMangaDex => NOT_ON_MANGADEX,
+            ChaptersError::Unavailable => MANGADEX_UNAVAILABLE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MangaChapters {
+    pub manga: Manga,
+    pub mangadex: MangaDexManga,
+    pub chapters: Vec<Chapter>,
+}
+
+pub fn fetcher(
+    args: Args,
+    language: String,
+    allow_adult: bool,
+) -> Result<MangaChapters, ChaptersError> {
+    let manga: Manga = manga_fetcher(
+        Type::Manga,
+        args,
+        FetchOptions {
+            allow_adult,
+            ..Default::default()
+        },
+    )
+    .map_err(ChaptersError::Lookup)?;
+
+    let mangadex = client();
+    let entry = find_manga(mangadex.as_ref(), &manga).ok_or(ChaptersError::NotOnMangaDex)?;
+    let chapters = mangadex
+        .feed(&entry.id, &language)
+        .ok_or(ChaptersError::Unavailable)?;
+
+    Ok(MangaChapters {
+        manga,
+        mangadex: entry,
+        chapters,
+    })
+}
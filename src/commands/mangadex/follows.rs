@@ -0,0 +1,309 @@
+use super::{
+    command::{format_chapter, split_language},
+    fetcher::{fetcher as ChaptersFetcher, ChaptersError},
+};
+use crate::{
+    commands::config::settings::{allow_adult_content, guild_settings},
+    models::{
+        fetcher::FetchError,
+        mangadex_response::{title_url, Chapter},
+        transformers::Transformers,
+    },
+    utils::{
+        formatter::{bold, code},
+        mangadex::client,
+        message::{NOT_FOLLOWING, NO_FOLLOWS},
+        store::JsonStore,
+    },
+};
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId},
+    },
+    prelude::{RwLock, TypeMapKey},
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::task;
+use tracing::{error, info};
+
+const FOLLOWS_STORE: &str = "manga_follows";
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MangaFollow {
+    pub anilist_id: u32,
+    pub mangadex_id: String,
+    pub title: String,
+    pub language: String,
+    pub guild_id: u64,
+    // publishAt of the newest chapter already announced
+    pub last_published: Option<String>,
+}
+
+// Channel ID -> Followed manga
+pub type Follows = HashMap<u64, Vec<MangaFollow>>;
+
+pub struct MangaFollows;
+
+impl TypeMapKey for MangaFollows {
+    type Value = Arc<RwLock<JsonStore<Follows>>>;
+}
+
+impl MangaFollows {
+    pub fn open() -> Arc<RwLock<JsonStore<Follows>>> {
+        Arc::new(RwLock::new(JsonStore::open(FOLLOWS_STORE)))
+    }
+}
+
+async fn store(ctx: &Context) -> Arc<RwLock<JsonStore<Follows>>> {
+    let data = ctx.data.read().await;
+    data.get::<MangaFollows>().unwrap().clone()
+}
+
+async fn update_follows<F, R>(ctx: &Context, channel_id: u64, update: F) -> R
+where
+    F: FnOnce(&mut Vec<MangaFollow>) -> R,
+{
+    let store = store(ctx).await;
+    let mut store = store.write().await;
+    let result = update(store.data.entry(channel_id).or_default());
+    store.data.retain(|_, follows| !follows.is_empty());
+    store.save();
+
+    result
+}
+
+fn is_same(a: &MangaFollow, b: &MangaFollow) -> bool {
+    a.anilist_id == b.anilist_id && a.language == b.language
+}
+
+fn format_follows(follows: &[MangaFollow]) -> String {
+    follows
+        .iter()
+        .map(|follow| {
+            format!(
+                "{} ({}) {}",
+                bold(follow.title.to_string()),
+                follow.language.to_uppercase(),
+                code(follow.anilist_id.to_string())
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[command("follow-manga")]
+#[only_in(guilds)]
+#[bucket = "follows"]
+#[required_permissions(MANAGE_GUILD)]
+async fn follow_manga(ctx: &Context, msg: &Message) -> CommandResult {
+    let (content, language) = split_language(&msg.content);
+    let mut args = Args::new(&content, &[Delimiter::Single(' ')]);
+    args.advance();
+
+    // No manga lists what this channel already follows
+    if args.is_empty() {
+        let follows = {
+            let store = store(ctx).await;
+            let store = store.read().await;
+            store
+                .data
+                .get(&msg.channel_id.0)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let reply = match follows.is_empty() {
+            true => NO_FOLLOWS.to_string(),
+            false => format!("Following:\n{}", format_follows(&follows)),
+        };
+        if let Err(why) = msg.channel_id.say(&ctx.http, reply).await {
+            error!("Error sending message: {:?}", why);
+        }
+        return Ok(());
+    }
+
+    args.rewind();
+    let followed_language = language.to_string();
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let response =
+        task::spawn_blocking(move || ChaptersFetcher(args, language, allow_adult)).await?;
+    // Follows are kept by AniList id, a fallback's entry without one can't be followed
+    let response = response.and_then(|result| match result.manga.get_id() {
+        Some(anilist_id) => Ok((anilist_id, result)),
+        None => Err(ChaptersError::Lookup(FetchError::Unmapped)),
+    });
+
+    let reply = match response {
+        Err(why) => why.message().to_string(),
+        Ok((anilist_id, result)) => {
+            let follow = MangaFollow {
+                anilist_id,
+                mangadex_id: result.mangadex.id.to_string(),
+                title: result.manga.transform_romaji_title(),
+                language: followed_language,
+                guild_id: msg.guild_id.unwrap().0,
+                // Only chapters after this one get announced
+                last_published: result
+                    .chapters
+                    .first()
+                    .map(|chapter| chapter.attributes.publish_at.to_string()),
+            };
+            let reply = format!(
+                "New {} chapters of {} will be posted here",
+                follow.language.to_uppercase(),
+                bold(follow.title.to_string())
+            );
+            update_follows(ctx, msg.channel_id.0, |follows| {
+                follows.retain(|existing| !is_same(existing, &follow));
+                follows.push(follow);
+            })
+            .await;
+            reply
+        }
+    };
+
+    if let Err(why) = msg.channel_id.say(&ctx.http, reply).await {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
+
+#[command("unfollow-manga")]
+#[only_in(guilds)]
+#[bucket = "follows"]
+#[required_permissions(MANAGE_GUILD)]
+async fn unfollow_manga(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
+    args.advance();
+    // Matches the AniList id or part of the title of something already followed
+    let target = args.remains().unwrap_or_default().to_lowercase();
+
+    let removed = match target.is_empty() {
+        true => 0,
+        false => {
+            update_follows(ctx, msg.channel_id.0, |follows| {
+                let before = follows.len();
+                follows.retain(|follow| {
+                    follow.anilist_id.to_string() != target
+                        && !follow.title.to_lowercase().contains(&target)
+                });
+                before - follows.len()
+            })
+            .await
+        }
+    };
+
+    let reply = match removed {
+        0 => NOT_FOLLOWING.to_string(),
+        removed => format!("Stopped following {} manga", removed),
+    };
+    if let Err(why) = msg.channel_id.say(&ctx.http, reply).await {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
+
+// Chapters published after the last one announced, oldest first
+fn new_chapters(chapters: Vec<Chapter>, follow: &MangaFollow) -> Vec<Chapter> {
+    let last_published = follow
+        .last_published
+        .as_ref()
+        .and_then(|published| chrono::DateTime::parse_from_rfc3339(published).ok());
+
+    let mut chapters: Vec<Chapter> = chapters
+        .into_iter()
+        .filter(|chapter| match (chapter.published_at(), last_published) {
+            (Some(published), Some(last)) => published > last,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+        .collect();
+    chapters.reverse();
+    chapters
+}
+
+async fn announce(ctx: &Context, channel_id: u64, follow: MangaFollow) {
+    let (mangadex_id, language) = (follow.mangadex_id.to_string(), follow.language.to_string());
+    let feed = task::spawn_blocking(move || client().feed(&mangadex_id, &language))
+        .await
+        .ok()
+        .flatten();
+    let chapters = match feed {
+        Some(chapters) => new_chapters(chapters, &follow),
+        None => return,
+    };
+    let newest = match chapters.last() {
+        Some(chapter) => chapter.attributes.publish_at.to_string(),
+        None => return,
+    };
+
+    info!(
+        "{} new chapters of {} for channel {}",
+        chapters.len(),
+        follow.anilist_id,
+        channel_id
+    );
+    let tz = guild_settings(ctx, Some(GuildId(follow.guild_id)))
+        .await
+        .tz();
+    let description = chapters
+        .iter()
+        .map(|chapter| format_chapter(chapter, tz))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    let msg = ChannelId(channel_id)
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0x00ff00)
+                    .title(format!("New Chapters: {}", follow.title))
+                    .url(title_url(&follow.mangadex_id))
+                    .description(description)
+            })
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+        return;
+    }
+
+    update_follows(ctx, channel_id, |follows| {
+        if let Some(existing) = follows
+            .iter_mut()
+            .find(|existing| is_same(existing, &follow))
+        {
+            existing.last_published = Some(newest);
+        }
+    })
+    .await;
+}
+
+// Checks every followed manga for new chapters, runs for as long as the bot does
+pub async fn poll_follows(ctx: Context) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let follows: Vec<(u64, MangaFollow)> = {
+            let store = store(&ctx).await;
+            let store = store.read().await;
+            store
+                .data
+                .iter()
+                .flat_map(|(channel_id, follows)| {
+                    follows.iter().map(|follow| (*channel_id, follow.clone()))
+                })
+                .collect()
+        };
+
+        for (channel_id, follow) in follows {
+            announce(&ctx, channel_id, follow).await;
+        }
+    }
+}
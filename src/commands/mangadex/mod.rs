@@ -0,0 +1,3 @@
+pub mod command;
+mod fetcher;
+pub mod follows;
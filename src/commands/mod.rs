@@ -1,5 +1,11 @@
 pub mod anime;
+pub mod config;
 pub mod help;
+pub mod ids;
 pub mod manga;
+pub mod mangadex;
 pub mod ping;
+pub mod prefs;
+pub mod quiz;
 pub mod songs;
+pub mod watchorder;
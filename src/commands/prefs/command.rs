@@ -0,0 +1,142 @@
+use super::settings::{display_preferences, update_user_preferences, user_preferences};
+use crate::{
+    commands::config::command::parse_toggle,
+    models::{
+        guild_settings::{ScoreFormat, TitleLanguage},
+        user_preferences::{DisplayPreferences, UserPreferences},
+    },
+    utils::message::PREFS_USAGE,
+};
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::channel::Message,
+};
+use tracing::error;
+
+// Resets a single preference back to the server setting
+const UNSET: &str = "default";
+
+enum PreferenceChange {
+    TitleLanguage(Option<TitleLanguage>),
+    ScoreFormat(Option<ScoreFormat>),
+    ShowSpoilers(Option<bool>),
+    Reset,
+}
+
+impl PreferenceChange {
+    fn apply(self, preferences: &mut UserPreferences) {
+        match self {
+            PreferenceChange::TitleLanguage(language) => preferences.title_language = language,
+            PreferenceChange::ScoreFormat(format) => preferences.score_format = format,
+            PreferenceChange::ShowSpoilers(show) => preferences.show_spoilers = show,
+            PreferenceChange::Reset => *preferences = UserPreferences::default(),
+        }
+    }
+}
+
+// Some(None) clears the preference, None means the value could not be parsed
+fn parse_value<T>(value: &str, parser: impl Fn(&str) -> Option<T>) -> Option<Option<T>> {
+    match value.eq_ignore_ascii_case(UNSET) {
+        true => Some(None),
+        false => parser(value).map(Some),
+    }
+}
+
+fn parse_change(key: &str, value: Option<&str>) -> Result<PreferenceChange, String> {
+    let value = value.map(|value| value.trim()).unwrap_or_default();
+
+    match key {
+        "reset" => Ok(PreferenceChange::Reset),
+        "title" => parse_value(value, TitleLanguage::from_arg)
+            .map(PreferenceChange::TitleLanguage)
+            .ok_or_else(|| "Title language must be romaji, english, native or default".to_string()),
+        "score" => parse_value(value, ScoreFormat::from_arg)
+            .map(PreferenceChange::ScoreFormat)
+            .ok_or_else(|| {
+                "Score format must be point_100, point_10_decimal, point_10, point_5, point_3, stars or default"
+                    .to_string()
+            }),
+        "spoilers" => parse_value(value, parse_toggle)
+            .map(PreferenceChange::ShowSpoilers)
+            .ok_or_else(|| "Spoilers must be on, off or default".to_string()),
+        _ => Err(PREFS_USAGE.to_string()),
+    }
+}
+
+fn describe<T>(value: Option<T>, resolved: &str) -> String {
+    match value {
+        Some(_) => resolved.to_string(),
+        None => format!("{} (server default)", resolved),
+    }
+}
+
+fn build_message_from_preferences<'a>(
+    preferences: &UserPreferences,
+    resolved: &DisplayPreferences,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    embed
+        .colour(0x00ff00)
+        .title("Your Preferences")
+        .fields(vec![
+            (
+                "Title Language",
+                describe(preferences.title_language, resolved.title_language.name()),
+                true,
+            ),
+            (
+                "Score Format",
+                describe(preferences.score_format, resolved.score_format.name()),
+                true,
+            ),
+            (
+                "Spoilers",
+                describe(
+                    preferences.show_spoilers,
+                    if resolved.show_spoilers { "on" } else { "off" },
+                ),
+                true,
+            ),
+        ])
+        .footer(|f| f.text(PREFS_USAGE))
+}
+
+#[command]
+async fn prefs(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
+    // Skips over the first arg because this is the command name
+    args.advance();
+
+    let key = args.single::<String>().ok().map(|key| key.to_lowercase());
+
+    let reply = match key {
+        None => Ok(user_preferences(ctx, msg.author.id).await),
+        Some(key) => match parse_change(&key, args.remains()) {
+            Err(why) => Err(why),
+            Ok(change) => Ok(update_user_preferences(ctx, msg.author.id, |preferences| {
+                change.apply(preferences)
+            })
+            .await),
+        },
+    };
+
+    let msg = match reply {
+        Err(why) => msg.channel_id.say(&ctx.http, why).await,
+        Ok(preferences) => {
+            let resolved = display_preferences(ctx, msg).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| build_message_from_preferences(&preferences, &resolved, e))
+                })
+                .await
+        }
+    };
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
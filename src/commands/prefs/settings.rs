@@ -0,0 +1,67 @@
+use crate::{
+    commands::config::settings::guild_settings,
+    models::user_preferences::{DisplayPreferences, UserPreferences},
+    utils::store::JsonStore,
+};
+use serenity::{
+    client::Context,
+    model::{channel::Message, id::UserId},
+    prelude::{RwLock, TypeMapKey},
+};
+use std::{collections::HashMap, sync::Arc};
+
+const USER_PREFERENCES_STORE: &str = "user_preferences";
+
+// User ID -> Preferences
+pub type Preferences = HashMap<u64, UserPreferences>;
+
+pub struct UserConfig;
+
+impl TypeMapKey for UserConfig {
+    type Value = Arc<RwLock<JsonStore<Preferences>>>;
+}
+
+impl UserConfig {
+    pub fn open() -> Arc<RwLock<JsonStore<Preferences>>> {
+        Arc::new(RwLock::new(JsonStore::open(USER_PREFERENCES_STORE)))
+    }
+}
+
+async fn store(ctx: &Context) -> Arc<RwLock<JsonStore<Preferences>>> {
+    let data = ctx.data.read().await;
+    data.get::<UserConfig>().unwrap().clone()
+}
+
+pub async fn user_preferences(ctx: &Context, user_id: UserId) -> UserPreferences {
+    let store = store(ctx).await;
+    let store = store.read().await;
+    store.data.get(&user_id.0).cloned().unwrap_or_default()
+}
+
+pub async fn update_user_preferences<F>(
+    ctx: &Context,
+    user_id: UserId,
+    update: F,
+) -> UserPreferences
+where
+    F: FnOnce(&mut UserPreferences),
+{
+    let store = store(ctx).await;
+    let mut store = store.write().await;
+    let preferences = {
+        let preferences = store.data.entry(user_id.0).or_default();
+        update(preferences);
+        preferences.clone()
+    };
+    store.save();
+
+    preferences
+}
+
+// Preferences for an embed triggered by this message
+pub async fn display_preferences(ctx: &Context, msg: &Message) -> DisplayPreferences {
+    let guild = guild_settings(ctx, msg.guild_id).await;
+    let user = user_preferences(ctx, msg.author.id).await;
+
+    DisplayPreferences::resolve(&user, &guild)
+}
@@ -0,0 +1,135 @@
+use super::game::{Clue, Difficulty};
+use crate::models::anilist_quiz::QuizMedia;
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat};
+use rand::{seq::SliceRandom, Rng};
+use reqwest::blocking::Client;
+use std::io::Cursor;
+use tracing::error;
+
+pub const CLUE_FILE_NAME: &str = "clue.png";
+
+// Covers get shrunk before any processing, blurring a full size cover takes ages
+const WORKING_WIDTH: u32 = 360;
+
+pub struct RenderedClue {
+    pub clue: Clue,
+    pub text: Option<String>,
+    pub image: Option<Vec<u8>>,
+}
+
+fn available_clues(media: &QuizMedia, difficulty: Difficulty) -> Vec<Clue> {
+    let mut clues: Vec<Clue> = Vec::new();
+
+    if media.cover_url().is_some() {
+        clues.push(Clue::BlurredCover);
+        if difficulty == Difficulty::Hard {
+            clues.push(Clue::Cover);
+        }
+    }
+    if media.redacted_description().is_some() {
+        clues.push(Clue::Description);
+    }
+    if media.character_image_url().is_some() {
+        clues.push(Clue::Character);
+    }
+
+    clues
+}
+
+pub fn render(media: &QuizMedia, difficulty: Difficulty) -> Option<RenderedClue> {
+    let mut rng = rand::thread_rng();
+    let mut clues = available_clues(media, difficulty);
+    clues.shuffle(&mut rng);
+
+    // Fall through to the next clue if an image fails to download or decode
+    for clue in clues {
+        let rendered = match clue {
+            Clue::Description => media.redacted_description().map(|text| RenderedClue {
+                clue,
+                text: Some(text),
+                image: None,
+            }),
+            _ => {
+                let image = match clue {
+                    Clue::Cover => media
+                        .cover_url()
+                        .and_then(|url| download(&url))
+                        .map(|image| crop(image, difficulty, &mut rng)),
+                    Clue::BlurredCover => media
+                        .cover_url()
+                        .and_then(|url| download(&url))
+                        .map(|image| blur(image, difficulty)),
+                    _ => media.character_image_url().and_then(|url| download(&url)),
+                };
+                image
+                    .and_then(|image| encode(&image))
+                    .map(|bytes| RenderedClue {
+                        clue,
+                        text: None,
+                        image: Some(bytes),
+                    })
+            }
+        };
+
+        if rendered.is_some() {
+            return rendered;
+        }
+    }
+
+    None
+}
+
+fn download(url: &str) -> Option<DynamicImage> {
+    let bytes = Client::new()
+        .get(url)
+        .send()
+        .and_then(|response| response.bytes());
+
+    match bytes {
+        Ok(bytes) => match image::load_from_memory(&bytes) {
+            Ok(image) => Some(image.resize(WORKING_WIDTH, u32::MAX, FilterType::Triangle)),
+            Err(why) => {
+                error!("Could not decode quiz image {:#?}: {:?}", url, why);
+                None
+            }
+        },
+        Err(why) => {
+            error!("Could not download quiz image {:#?}: {:?}", url, why);
+            None
+        }
+    }
+}
+
+// Keeps a random window of the cover, smaller on hard
+fn crop(image: DynamicImage, difficulty: Difficulty, rng: &mut impl Rng) -> DynamicImage {
+    let divisor = match difficulty {
+        Difficulty::Easy => 2,
+        Difficulty::Hard => 3,
+    };
+    let width = (image.width() / divisor).max(1);
+    let height = (image.height() / divisor).max(1);
+    let x = rng.gen_range(0..=image.width() - width);
+    let y = rng.gen_range(0..=image.height() - height);
+
+    image.crop_imm(x, y, width, height)
+}
+
+fn blur(image: DynamicImage, difficulty: Difficulty) -> DynamicImage {
+    let sigma = match difficulty {
+        Difficulty::Easy => 6.0,
+        Difficulty::Hard => 10.0,
+    };
+
+    image.blur(sigma)
+}
+
+fn encode(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    match image.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png) {
+        Ok(_) => Some(bytes),
+        Err(why) => {
+            error!("Could not encode quiz image: {:?}", why);
+            None
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use super::{
+    clue::{render, RenderedClue, CLUE_FILE_NAME},
+    fetcher::fetcher as QuizFetcher,
+    game::{Difficulty, Guess, Round, RoundState, SystemClock},
+    leaderboard::{award_points, format_scores},
+};
+use crate::{
+    models::anilist_quiz::QuizMedia,
+    utils::message::{QUIZ_FETCH_FAILED, QUIZ_RUNNING},
+};
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::{channel::AttachmentType, channel::Message, id::UserId},
+    prelude::Mentionable,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::{Mutex, OnceLock},
+};
+use tokio::task;
+use tracing::{error, info};
+
+const DEFAULT_ROUNDS: u32 = 5;
+const MAX_ROUNDS: u32 = 20;
+const MAX_FETCH_ATTEMPTS: u32 = 3;
+
+// Channels with a quiz going on, two quizzes would both take every reply as a guess
+fn running_quizzes() -> &'static Mutex<HashSet<u64>> {
+    static RUNNING: OnceLock<Mutex<HashSet<u64>>> = OnceLock::new();
+    RUNNING.get_or_init(Default::default)
+}
+
+// Frees the channel when the quiz ends, however it ends
+struct RunningQuiz(u64);
+
+impl RunningQuiz {
+    fn start(channel_id: u64) -> Option<RunningQuiz> {
+        let started = running_quizzes().lock().unwrap().insert(channel_id);
+        started.then_some(RunningQuiz(channel_id))
+    }
+}
+
+impl Drop for RunningQuiz {
+    fn drop(&mut self) {
+        running_quizzes().lock().unwrap().remove(&self.0);
+    }
+}
+
+fn parse_arguments(mut args: Args) -> (Difficulty, u32) {
+    // Skips over the first arg because this is the command name
+    args.advance();
+
+    let mut difficulty = Difficulty::Easy;
+    let mut rounds = DEFAULT_ROUNDS;
+
+    for arg in args.iter::<String>().flatten() {
+        if let Some(parsed) = Difficulty::from_arg(&arg) {
+            difficulty = parsed;
+        } else if let Ok(parsed) = arg.parse::<u32>() {
+            rounds = parsed.clamp(1, MAX_ROUNDS);
+        }
+    }
+
+    (difficulty, rounds)
+}
+
+async fn prepare_round(difficulty: Difficulty) -> Option<(QuizMedia, RenderedClue)> {
+    for _ in 0..MAX_FETCH_ATTEMPTS {
+        let prepared = task::spawn_blocking(move || {
+            let media = QuizFetcher(difficulty)?;
+            let clue = render(&media, difficulty)?;
+            Some((media, clue))
+        })
+        .await
+        .ok()
+        .flatten();
+
+        if prepared.is_some() {
+            return prepared;
+        }
+    }
+
+    None
+}
+
+#[command]
+#[only_in(guilds)]
+#[bucket = "quiz"]
+async fn quiz(ctx: &Context, msg: &Message) -> CommandResult {
+    let args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
+    let (difficulty, rounds) = parse_arguments(args);
+    let Some(_running) = RunningQuiz::start(msg.channel_id.0) else {
+        if let Err(why) = msg.channel_id.say(&ctx.http, QUIZ_RUNNING).await {
+            error!("Error sending message: {:?}", why);
+        }
+        return Ok(());
+    };
+    let guild_id = msg.guild_id.unwrap().0;
+    let clock = SystemClock;
+    let mut session_scores: HashMap<u64, u32> = HashMap::new();
+
+    info!("Starting a {:?} quiz with {} rounds", difficulty, rounds);
+
+    for round_number in 1..=rounds {
+        let (media, clue) = match prepare_round(difficulty).await {
+            Some(prepared) => prepared,
+            None => {
+                let _ = msg.channel_id.say(&ctx.http, QUIZ_FETCH_FAILED).await;
+                break;
+            }
+        };
+
+        let sent = msg
+            .channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0x00ff00)
+                        .title(format!(
+                            "Round {}/{} ({})",
+                            round_number,
+                            rounds,
+                            difficulty.name()
+                        ))
+                        .description(clue.text.as_deref().unwrap_or(clue.clue.prompt()))
+                        .footer(|f| {
+                            f.text(format!(
+                                "{} - You have {} seconds!",
+                                clue.clue.prompt(),
+                                difficulty.round_duration().as_secs()
+                            ))
+                        });
+                    if clue.image.is_some() {
+                        e.image(format!("attachment://{}", CLUE_FILE_NAME));
+                    }
+                    e
+                });
+                if let Some(image) = &clue.image {
+                    m.add_file(AttachmentType::Bytes {
+                        data: Cow::from(image.to_vec()),
+                        filename: CLUE_FILE_NAME.to_string(),
+                    });
+                }
+                m
+            })
+            .await;
+
+        if let Err(why) = sent {
+            error!("Error sending message: {:?}", why);
+            break;
+        }
+
+        let mut round = Round::start(media.answers(), difficulty, &clock);
+
+        while let Some(remaining) = round.remaining(&clock) {
+            let reply = msg
+                .channel_id
+                .await_reply(ctx)
+                .filter(|reply| !reply.author.bot)
+                .timeout(remaining)
+                .await;
+
+            if let Some(reply) = reply {
+                if round.guess(reply.author.id.0, &reply.content, &clock) == Guess::Correct {
+                    let _ = reply.react(ctx, '✅').await;
+                }
+            }
+        }
+
+        let result = match round.state() {
+            RoundState::Won { user_id } => {
+                *session_scores.entry(user_id).or_default() += difficulty.points();
+                let total = award_points(ctx, guild_id, user_id, difficulty.points()).await;
+                format!(
+                    "{} got it! It was **{}** ({} pts total)",
+                    UserId(user_id).mention(),
+                    media.display_title(),
+                    total
+                )
+            }
+            _ => format!("Time's up! It was **{}**", media.display_title()),
+        };
+
+        if let Err(why) = msg.channel_id.say(&ctx.http, result).await {
+            error!("Error sending message: {:?}", why);
+        }
+    }
+
+    let mut standings: Vec<(u64, u32)> = session_scores.into_iter().collect();
+    standings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let summary = match standings.is_empty() {
+        true => "Nobody scored this time!".to_string(),
+        false => format_scores(&standings),
+    };
+
+    let msg = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.colour(0x00ff00).title("Quiz Over").description(summary))
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
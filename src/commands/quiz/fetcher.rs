@@ -0,0 +1,22 @@
+use super::{game::Difficulty, queries::FETCH_QUIZ_ANIME};
+use crate::{
+    models::{anilist_quiz::QuizMedia, media_list_response::FetchResponse},
+    utils::{anilist_request::anilist_url, fetchers::fetch_by_arguments::fetch_by_page},
+};
+use rand::Rng;
+use tracing::info;
+
+pub fn fetcher(difficulty: Difficulty) -> Option<QuizMedia> {
+    // Each page holds a single anime, so the page number is the popularity rank
+    let page = rand::thread_rng().gen_range(1..=difficulty.pool_size());
+    let fetched_data = fetch_by_page(&anilist_url(), FETCH_QUIZ_ANIME.to_string(), page).ok()?;
+    let fetch_response: FetchResponse<QuizMedia> = serde_json::from_str(&fetched_data).ok()?;
+    info!("Quiz pick at popularity rank {:#?}", page);
+
+    fetch_response
+        .checked(&FETCH_QUIZ_ANIME)
+        .ok()?
+        .into_media()
+        .into_iter()
+        .next()
+}
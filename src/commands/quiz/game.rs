@@ -0,0 +1,159 @@
+use crate::utils::fuzzy::fuzzy_matcher;
+use std::time::{Duration, Instant};
+
+// Rounds read the time through this so the state machine can be driven by a fake clock
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_arg(arg: &str) -> Option<Difficulty> {
+        match arg.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    // How deep into the popularity ranking we pick anime from
+    pub fn pool_size(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 150,
+            Difficulty::Hard => 1000,
+        }
+    }
+
+    pub fn round_duration(&self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_secs(45),
+            Difficulty::Hard => Duration::from_secs(30),
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clue {
+    Cover,
+    BlurredCover,
+    Description,
+    Character,
+}
+
+impl Clue {
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Clue::Cover => "Which anime is this cover from?",
+            Clue::BlurredCover => "Which anime is this blurry cover from?",
+            Clue::Description => "Which anime is this description from?",
+            Clue::Character => "Which anime is this character from?",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundState {
+    Running,
+    Won { user_id: u64 },
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guess {
+    Correct,
+    Wrong,
+    // The round was already over when the guess came in
+    Ignored,
+}
+
+#[derive(Debug)]
+pub struct Round {
+    answers: Vec<String>,
+    threshold: f32,
+    deadline: Instant,
+    state: RoundState,
+}
+
+impl Round {
+    pub fn start(answers: Vec<String>, difficulty: Difficulty, clock: &impl Clock) -> Round {
+        Round {
+            answers,
+            threshold: difficulty.threshold(),
+            deadline: clock.now() + difficulty.round_duration(),
+            state: RoundState::Running,
+        }
+    }
+
+    pub fn state(&self) -> RoundState {
+        self.state
+    }
+
+    // Moves a running round into TimedOut once the deadline has passed
+    pub fn tick(&mut self, clock: &impl Clock) -> RoundState {
+        if self.state == RoundState::Running && clock.now() >= self.deadline {
+            self.state = RoundState::TimedOut;
+        }
+        self.state
+    }
+
+    // Time left to answer, None once the round is over
+    pub fn remaining(&mut self, clock: &impl Clock) -> Option<Duration> {
+        match self.tick(clock) {
+            RoundState::Running => Some(self.deadline - clock.now()),
+            _ => None,
+        }
+    }
+
+    pub fn guess(&mut self, user_id: u64, text: &str, clock: &impl Clock) -> Guess {
+        if self.tick(clock) != RoundState::Running {
+            return Guess::Ignored;
+        }
+
+        let guess = text.trim();
+        if guess.is_empty() {
+            return Guess::Wrong;
+        }
+
+        match fuzzy_matcher(guess, self.answers.to_vec(), self.threshold) {
+            Some(_) => {
+                self.state = RoundState::Won { user_id };
+                Guess::Correct
+            }
+            None => Guess::Wrong,
+        }
+    }
+}
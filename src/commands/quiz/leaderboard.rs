@@ -0,0 +1,120 @@
+use crate::utils::{formatter::bold, message::EMPTY_LEADERBOARD, store::JsonStore};
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, CommandResult},
+    model::{channel::Message, id::UserId},
+    prelude::{Mentionable, RwLock, TypeMapKey},
+};
+use std::{collections::HashMap, sync::Arc};
+use tracing::error;
+
+const LEADERBOARD_STORE: &str = "leaderboard";
+const LEADERBOARD_SIZE: usize = 10;
+
+// Guild ID -> User ID -> Points
+pub type Scores = HashMap<u64, HashMap<u64, u32>>;
+
+pub struct Leaderboard;
+
+impl TypeMapKey for Leaderboard {
+    type Value = Arc<RwLock<JsonStore<Scores>>>;
+}
+
+impl Leaderboard {
+    pub fn open() -> Arc<RwLock<JsonStore<Scores>>> {
+        Arc::new(RwLock::new(JsonStore::open(LEADERBOARD_STORE)))
+    }
+}
+
+pub async fn award_points(ctx: &Context, guild_id: u64, user_id: u64, points: u32) -> u32 {
+    let leaderboard = {
+        let data = ctx.data.read().await;
+        data.get::<Leaderboard>().unwrap().clone()
+    };
+
+    let mut leaderboard = leaderboard.write().await;
+    let total = {
+        let user_points = leaderboard
+            .data
+            .entry(guild_id)
+            .or_default()
+            .entry(user_id)
+            .or_default();
+        *user_points += points;
+        *user_points
+    };
+    leaderboard.save_async().await;
+
+    total
+}
+
+pub async fn top_scores(ctx: &Context, guild_id: u64) -> Vec<(u64, u32)> {
+    let leaderboard = {
+        let data = ctx.data.read().await;
+        data.get::<Leaderboard>().unwrap().clone()
+    };
+
+    let leaderboard = leaderboard.read().await;
+    let mut scores: Vec<(u64, u32)> = leaderboard
+        .data
+        .get(&guild_id)
+        .map(|scores| {
+            scores
+                .iter()
+                .map(|(user, points)| (*user, *points))
+                .collect()
+        })
+        .unwrap_or_default();
+    scores.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    scores.truncate(LEADERBOARD_SIZE);
+
+    scores
+}
+
+pub fn format_scores(scores: &[(u64, u32)]) -> String {
+    scores
+        .iter()
+        .enumerate()
+        .map(|(index, (user_id, points))| {
+            format!(
+                "{}. {} - {}",
+                index + 1,
+                UserId(*user_id).mention(),
+                bold(format!("{} pts", points))
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[command]
+#[only_in(guilds)]
+async fn leaderboard(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap().0;
+    let scores = top_scores(ctx, guild_id).await;
+
+    let msg = match scores.is_empty() {
+        true => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| m.content(EMPTY_LEADERBOARD))
+                .await
+        }
+        false => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0x00ff00)
+                            .title("Quiz Leaderboard")
+                            .description(format_scores(&scores))
+                    })
+                })
+                .await
+        }
+    };
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
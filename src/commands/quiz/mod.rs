@@ -0,0 +1,6 @@
+mod clue;
+pub mod command;
+mod fetcher;
+pub mod game;
+pub mod leaderboard;
+pub mod queries;
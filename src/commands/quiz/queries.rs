@@ -0,0 +1,29 @@
+use crate::utils::graphql::{Query, CHARACTERS, TITLE};
+use std::sync::LazyLock;
+
+// Each page holds a single anime, picked by popularity
+pub static FETCH_QUIZ_ANIME: LazyLock<String> = LazyLock::new(|| {
+    Query::new(
+        "QuizAnime",
+        "media(type: ANIME, sort: POPULARITY_DESC, isAdult: false)",
+    )
+    .variable("page", "Int")
+    .page("Page(page: $page, perPage: 1)")
+    .field("id")
+    .nested("title", &TITLE)
+    .field("synonyms")
+    .field(
+        "coverImage {
+  extraLarge
+  large
+  medium
+  color
+}",
+    )
+    .field("description")
+    .nested(
+        "characters(sort: [ROLE, RELEVANCE], perPage: 5)",
+        &CHARACTERS,
+    )
+    .build()
+});
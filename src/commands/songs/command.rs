@@ -1,4 +1,11 @@
-use crate::{models::mal_response::MalResponse, utils::message::NOT_FOUND_ANIME};
+use crate::{
+    commands::config::settings::allow_adult_content,
+    models::{mal_response::MalResponse, media_type::MediaType as Type},
+    utils::{
+        embed_budget::{chunk_lines, continued, FIELD_LIMIT},
+        paginator::send_pages,
+    },
+};
 
 use super::fetcher::fetcher as SongFetcher;
 use serenity::{
@@ -11,22 +18,25 @@ use tokio::task;
 use tracing::error;
 
 #[command]
+#[bucket = "lookup"]
 async fn songs(ctx: &Context, msg: &Message) -> CommandResult {
     let args = Args::new(&msg.content, &[Delimiter::Single(' ')]);
-    let response = task::spawn_blocking(|| SongFetcher(args)).await?;
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let response = task::spawn_blocking(move || SongFetcher(args, allow_adult)).await?;
 
     let msg = match response {
-        None => {
+        Err(why) => {
             msg.channel_id
-                .send_message(&ctx.http, |m| m.content(NOT_FOUND_ANIME))
+                .send_message(&ctx.http, |m| m.content(why.message(Type::Anime)))
                 .await
         }
-        Some(song_response) => {
-            msg.channel_id
-                .send_message(&ctx.http, |m| {
-                    m.embed(|e| build_message_from_song_response(song_response, e))
-                })
-                .await
+        Ok(song_response) => {
+            send_pages(
+                ctx,
+                msg.channel_id,
+                build_pages_from_song_response(song_response),
+            )
+            .await
         }
     };
 
@@ -42,15 +52,27 @@ async fn songs(ctx: &Context, msg: &Message) -> CommandResult {
 //                 https://docs.rs/serenity/latest/serenity/model/channel/struct.Embed.html
 // and send proper embeds
 
-fn build_message_from_song_response(
-    mal_response: MalResponse,
-    embed: &mut CreateEmbed,
-) -> &mut CreateEmbed {
-    embed
-        .title(mal_response.transform_title())
-        .field("Openings", mal_response.transform_openings(), false)
-        .field("Endings", mal_response.transform_endings(), false)
-        .thumbnail(mal_response.transform_thumbnail())
-        // TODO: Also Add Anilist Link??
-        .field("\u{200b}", mal_response.transform_mal_link(), false)
+// Long running shows have dozens of songs, whatever doesn't fit a field goes on the next page
+fn build_pages_from_song_response(mal_response: MalResponse) -> Vec<CreateEmbed> {
+    let openings = chunk_lines(&mal_response.transform_openings(), FIELD_LIMIT);
+    let endings = chunk_lines(&mal_response.transform_endings(), FIELD_LIMIT);
+    let page_count = openings.len().max(endings.len()).max(1);
+
+    (0..page_count)
+        .map(|page| {
+            let mut embed = CreateEmbed::default();
+            embed
+                .title(mal_response.transform_title())
+                .thumbnail(mal_response.transform_thumbnail());
+            if let Some(openings) = openings.get(page) {
+                embed.field(continued("Openings", page), openings, false);
+            }
+            if let Some(endings) = endings.get(page) {
+                embed.field(continued("Endings", page), endings, false);
+            }
+            // TODO: Also Add Anilist Link??
+            embed.field("\u{200b}", mal_response.transform_mal_link(), false);
+            embed
+        })
+        .collect()
 }
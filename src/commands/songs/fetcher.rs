@@ -1,25 +1,54 @@
 use crate::{
     models::{
-        anilist_anime::Anime, mal_response::MalResponse, media_type::MediaType as Type,
+        anilist_anime::Anime,
+        fetcher::{FetchError, FetchOptions},
+        mal_response::MalResponse,
+        media_type::MediaType as Type,
         transformers::Transformers,
     },
-    utils::my_anime_list_request,
-    utils::response_fetcher::fetcher as anime_fetcher,
+    utils::response_fetcher::{fetcher as anime_fetcher, search},
+    utils::{
+        id_mapper::id_mapper,
+        providers::{jikan::Jikan, providers, with_failover},
+    },
 };
 use tracing::info;
 
-pub fn fetcher(args: serenity::framework::standard::Args) -> Option<MalResponse> {
-    let anime_response: Option<Anime> = anime_fetcher(Type::Anime, args);
+// Neither AniList nor the id mapper know every MAL id, Jikan can still find it by title
+fn find_mal_id(anime: &Anime, options: &FetchOptions) -> Option<u32> {
+    let title = anime.transform_romaji_title();
+    let response = search::<Anime>(&Jikan::default(), Type::Anime, &title, options).ok()?;
+    let mal_id = response
+        .fuzzy_match(&title, Type::Anime, options.allow_adult)?
+        .get_mal_id();
+
+    info!("Found MAL id {:?} for {} through Jikan", mal_id, title);
+    mal_id
+}
+
+pub fn fetcher(
+    args: serenity::framework::standard::Args,
+    allow_adult: bool,
+) -> Result<MalResponse, FetchError> {
+    let options = FetchOptions {
+        allow_adult,
+        ..Default::default()
+    };
+    let anime_response: Result<Anime, FetchError> =
+        anime_fetcher(Type::Anime, args, options.clone());
     match anime_response {
-        None => None,
-        Some(anime) => {
-            let mal_id = anime.get_mal_id();
-            mal_id?;
-            let mal_fetcher_response: String = my_anime_list_request::send_request(mal_id.unwrap());
-            let mal_response: MalResponse = serde_json::from_str(&mal_fetcher_response).unwrap();
+        Err(why) => Err(why),
+        Ok(anime) => {
+            // AniList misses some MAL links the offline dataset has
+            let mal_id = anime
+                .get_mal_id()
+                .or_else(|| anime.get_id().and_then(|id| id_mapper().to_mal(id)))
+                .or_else(|| find_mal_id(&anime, &options))
+                .ok_or(FetchError::NotFound)?;
+            let mal_response = with_failover(&providers(), |provider| provider.songs(mal_id))?;
 
             info!("Mal Response: {:#?}", mal_response);
-            Some(mal_response)
+            Ok(mal_response)
         }
     }
 }
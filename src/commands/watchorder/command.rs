@@ -0,0 +1,143 @@
+use super::{
+    fetcher::fetcher as WatchOrderFetcher,
+    graph::{Franchise, MediaNode, OrderOptions},
+};
+use crate::{
+    commands::config::settings::allow_adult_content,
+    models::media_type::MediaType as Type,
+    utils::{
+        embed_budget::{chunk_lines, continued, FIELD_LIMIT},
+        formatter::{linker, remove_underscores_and_titlecase},
+        paginator::send_pages,
+    },
+};
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult, Delimiter},
+    model::channel::Message,
+};
+use tokio::task;
+use tracing::error;
+
+const SKIP_SPECIALS_FLAG: &str = "--no-specials";
+const SKIP_RECAPS_FLAG: &str = "--no-recaps";
+
+// Pulls the flags out so the rest can go through the usual lookup
+fn parse_flags(content: &str) -> (String, OrderOptions) {
+    let mut options = OrderOptions::default();
+    let words = content
+        .split(' ')
+        .filter(|word| match word.to_lowercase().as_str() {
+            SKIP_SPECIALS_FLAG => {
+                options.skip_specials = true;
+                false
+            }
+            SKIP_RECAPS_FLAG => {
+                options.skip_recaps = true;
+                false
+            }
+            _ => true,
+        })
+        .collect::<Vec<&str>>();
+
+    (words.join(" "), options)
+}
+
+fn format_entry(index: usize, node: &MediaNode) -> String {
+    let details = [
+        node.format.as_deref().map(remove_underscores_and_titlecase),
+        node.year().map(|year| year.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<String>>()
+    .join(", ");
+
+    format!(
+        "{}. {} ({})",
+        index + 1,
+        linker(
+            node.title.to_string(),
+            format!("https://anilist.co/anime/{}", node.id)
+        ),
+        details
+    )
+}
+
+fn format_order(nodes: &[&MediaNode]) -> Vec<String> {
+    let lines = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| format_entry(index, node))
+        .collect::<Vec<String>>();
+
+    match lines.is_empty() {
+        true => vec!["Nothing to watch".to_string()],
+        false => chunk_lines(&lines, FIELD_LIMIT),
+    }
+}
+
+// Big franchises don't fit one embed, the rest of both orders go on later pages
+fn build_pages_from_franchise(franchise: Franchise, options: OrderOptions) -> Vec<CreateEmbed> {
+    let watch_order = franchise.watch_order(options);
+    let title = watch_order
+        .iter()
+        .find(|node| node.id == franchise.root)
+        .map(|node| node.title.to_string())
+        .unwrap_or_else(|| "Franchise".to_string());
+    let watch_order = format_order(&watch_order);
+    let release_order = format_order(&franchise.release_order(options));
+    let page_count = watch_order.len().max(release_order.len());
+
+    (0..page_count)
+        .map(|page| {
+            let mut embed = CreateEmbed::default();
+            embed
+                .colour(0x00ff00)
+                .title(format!("Watch Order: {}", title))
+                .description(format!(
+                    "Add {} or {} to hide specials or recaps",
+                    SKIP_SPECIALS_FLAG, SKIP_RECAPS_FLAG
+                ));
+            if let Some(entries) = watch_order.get(page) {
+                embed.field(continued("Watch Order", page), entries, false);
+            }
+            if let Some(entries) = release_order.get(page) {
+                embed.field(continued("Release Order", page), entries, false);
+            }
+            embed
+        })
+        .collect()
+}
+
+#[command]
+#[bucket = "heavy"]
+async fn watchorder(ctx: &Context, msg: &Message) -> CommandResult {
+    let (content, options) = parse_flags(&msg.content);
+    let args = Args::new(&content, &[Delimiter::Single(' ')]);
+    let allow_adult = allow_adult_content(ctx, msg).await;
+    let response = task::spawn_blocking(move || WatchOrderFetcher(args, allow_adult)).await?;
+
+    let msg = match response {
+        Err(why) => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| m.content(why.message(Type::Anime)))
+                .await
+        }
+        Ok(franchise) => {
+            send_pages(
+                ctx,
+                msg.channel_id,
+                build_pages_from_franchise(franchise, options),
+            )
+            .await
+        }
+    };
+
+    if let Err(why) = msg {
+        error!("Error sending message: {:?}", why);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use super::{
+    graph::{walk, Franchise, MediaNode, Relation, RelationType, WalkOptions},
+    queries::FETCH_RELATIONS_BY_ID,
+};
+use crate::{
+    models::{
+        anilist_anime::Anime,
+        fetcher::{FetchError, FetchOptions},
+        id_response::FetchResponse,
+        media_type::MediaType as Type,
+        relations_response::MediaRelations,
+        transformers::Transformers,
+    },
+    utils::{
+        anilist_request::anilist_url, fetchers::fetch_by_arguments::fetch_by_id,
+        response_fetcher::fetcher as anime_fetcher,
+    },
+};
+use tracing::{error, info};
+
+fn fetch_relations(id: u32) -> Option<MediaRelations> {
+    let fetched_data = fetch_by_id(&anilist_url(), FETCH_RELATIONS_BY_ID.to_string(), id).ok()?;
+    match serde_json::from_str::<FetchResponse<MediaRelations>>(&fetched_data) {
+        Ok(response) => response.into_media(&FETCH_RELATIONS_BY_ID).ok()?,
+        Err(why) => {
+            error!("Could not parse relations for {}: {:?}", id, why);
+            None
+        }
+    }
+}
+
+// Only anime end up in a watch order, and adult ones only where the channel allows them
+fn to_relations(media: &MediaRelations, allow_adult: bool) -> Vec<Relation> {
+    match &media.relations {
+        None => vec![],
+        Some(relations) => relations
+            .pairs()
+            .filter(|(_, node)| node.media_type.as_deref() == Some("ANIME"))
+            .filter(|(_, node)| allow_adult || !node.is_adult())
+            .filter_map(|(edge, node)| {
+                let relation_type =
+                    RelationType::from_anilist(edge.relation_type.as_deref().unwrap_or_default())?;
+                Some(Relation {
+                    relation_type,
+                    target: MediaNode::from_relation(node),
+                })
+            })
+            .collect(),
+    }
+}
+
+pub fn fetcher(
+    args: serenity::framework::standard::Args,
+    allow_adult: bool,
+) -> Result<Franchise, FetchError> {
+    let anime: Anime = anime_fetcher(
+        Type::Anime,
+        args,
+        FetchOptions {
+            allow_adult,
+            ..Default::default()
+        },
+    )?;
+    // Relations only come from AniList
+    let id = anime.get_id().ok_or(FetchError::Unmapped)?;
+    let root = fetch_relations(id).ok_or(FetchError::NotFound)?;
+    let root_node = MediaNode::new(
+        root.id,
+        anime.transform_romaji_title(),
+        root.format.to_owned(),
+        root.start_date.as_ref(),
+    );
+
+    let franchise = walk(root_node, WalkOptions::default(), |id| {
+        // The root was already fetched to build its node
+        let media = match id == root.id {
+            true => Some(root.clone()),
+            false => fetch_relations(id),
+        };
+        media.map(|media| to_relations(&media, allow_adult))
+    });
+
+    info!("Walked franchise of {}", root.id);
+    Ok(franchise)
+}
@@ -0,0 +1,320 @@
+use crate::models::{anilist_common::RelationNode, fuzzy_date::FuzzyDate};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationType {
+    Sequel,
+    Prequel,
+    SideStory,
+    SpinOff,
+    Alternative,
+}
+
+impl RelationType {
+    // Only the relations that make sense for a watch order, everything else is ignored
+    pub fn from_anilist(relation_type: &str) -> Option<RelationType> {
+        match relation_type {
+            "SEQUEL" => Some(RelationType::Sequel),
+            "PREQUEL" => Some(RelationType::Prequel),
+            "SIDE_STORY" => Some(RelationType::SideStory),
+            "SPIN_OFF" => Some(RelationType::SpinOff),
+            "ALTERNATIVE" => Some(RelationType::Alternative),
+            _ => None,
+        }
+    }
+
+    fn is_main_line(&self) -> bool {
+        matches!(self, RelationType::Sequel | RelationType::Prequel)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaNode {
+    pub id: u32,
+    pub title: String,
+    pub format: Option<String>,
+    // (year, month, day), missing parts sort last
+    pub start_date: (u32, u32, u32),
+}
+
+impl MediaNode {
+    pub fn new(id: u32, title: String, format: Option<String>, date: Option<&FuzzyDate>) -> Self {
+        let start_date = date.copied().unwrap_or_default().sort_key();
+
+        MediaNode {
+            id,
+            title,
+            format,
+            start_date,
+        }
+    }
+
+    pub fn from_relation(node: &RelationNode) -> Self {
+        MediaNode::new(
+            node.id,
+            node.display_title(),
+            node.format.to_owned(),
+            node.start_date.as_ref(),
+        )
+    }
+
+    pub fn year(&self) -> Option<u32> {
+        match self.start_date.0 {
+            u32::MAX => None,
+            year => Some(year),
+        }
+    }
+
+    fn is_special(&self) -> bool {
+        matches!(self.format.as_deref(), Some("SPECIAL") | Some("MUSIC"))
+    }
+
+    fn is_recap(&self) -> bool {
+        let title = self.title.to_lowercase();
+        title.contains("recap") || title.contains("summary") || title.contains("digest")
+    }
+
+    fn sort_key(&self) -> ((u32, u32, u32), u32) {
+        (self.start_date, self.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub relation_type: RelationType,
+    pub target: MediaNode,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+    pub max_depth: usize,
+    pub max_nodes: usize,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            max_depth: 4,
+            max_nodes: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderOptions {
+    pub skip_specials: bool,
+    pub skip_recaps: bool,
+}
+
+impl OrderOptions {
+    fn keeps(&self, node: &MediaNode) -> bool {
+        let skipped_special = self.skip_specials && node.is_special();
+        let skipped_recap = self.skip_recaps && node.is_recap();
+        !(skipped_special || skipped_recap)
+    }
+}
+
+#[derive(Debug)]
+pub struct Franchise {
+    pub root: u32,
+    nodes: HashMap<u32, MediaNode>,
+    // (from, relation, to) exactly as AniList reported them
+    edges: Vec<(u32, RelationType, u32)>,
+    // Which node we first reached every other node from
+    parents: HashMap<u32, u32>,
+}
+
+// Breadth first walk over the relation graph, `relations_of` does the actual lookups
+pub fn walk<F>(root: MediaNode, options: WalkOptions, mut relations_of: F) -> Franchise
+where
+    F: FnMut(u32) -> Option<Vec<Relation>>,
+{
+    let mut franchise = Franchise {
+        root: root.id,
+        nodes: HashMap::new(),
+        edges: Vec::new(),
+        parents: HashMap::new(),
+    };
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut queue: VecDeque<(u32, usize)> = VecDeque::new();
+
+    visited.insert(root.id);
+    queue.push_back((root.id, 0));
+    franchise.nodes.insert(root.id, root);
+
+    while let Some((id, depth)) = queue.pop_front() {
+        if depth >= options.max_depth {
+            continue;
+        }
+
+        for relation in relations_of(id).unwrap_or_default() {
+            let target_id = relation.target.id;
+            franchise
+                .edges
+                .push((id, relation.relation_type, target_id));
+
+            if visited.contains(&target_id) || franchise.nodes.len() >= options.max_nodes {
+                continue;
+            }
+
+            visited.insert(target_id);
+            franchise.parents.insert(target_id, id);
+            franchise.nodes.insert(target_id, relation.target);
+            queue.push_back((target_id, depth + 1));
+        }
+    }
+
+    franchise
+}
+
+impl Franchise {
+    pub fn release_order(&self, options: OrderOptions) -> Vec<&MediaNode> {
+        let mut nodes: Vec<&MediaNode> = self
+            .nodes
+            .values()
+            .filter(|node| options.keeps(node))
+            .collect();
+        nodes.sort_by_key(|node| node.sort_key());
+        nodes
+    }
+
+    // Main line (prequels and sequels) in story order with side stories slotted in after
+    // whatever main line entry they hang off
+    pub fn watch_order(&self, options: OrderOptions) -> Vec<&MediaNode> {
+        let main_line = self.main_line();
+        let ordered_main_line = self.order_main_line(&main_line);
+
+        let mut attachments: HashMap<u32, Vec<&MediaNode>> = HashMap::new();
+        let mut unanchored: Vec<&MediaNode> = Vec::new();
+        for node in self.nodes.values() {
+            if main_line.contains(&node.id) {
+                continue;
+            }
+            match self.anchor(node.id, &main_line) {
+                Some(anchor) => attachments.entry(anchor).or_default().push(node),
+                None => unanchored.push(node),
+            }
+        }
+
+        let mut order: Vec<&MediaNode> = Vec::new();
+        for id in ordered_main_line {
+            order.push(&self.nodes[&id]);
+            if let Some(attached) = attachments.get_mut(&id) {
+                attached.sort_by_key(|node| node.sort_key());
+                order.extend(attached.iter());
+            }
+        }
+        unanchored.sort_by_key(|node| node.sort_key());
+        order.extend(unanchored);
+
+        order
+            .into_iter()
+            .filter(|node| options.keeps(node))
+            .collect()
+    }
+
+    // Everything reachable from the root through prequel/sequel edges only
+    fn main_line(&self) -> HashSet<u32> {
+        let mut main_line: HashSet<u32> = HashSet::new();
+        let mut queue: VecDeque<u32> = VecDeque::from([self.root]);
+        main_line.insert(self.root);
+
+        while let Some(id) = queue.pop_front() {
+            for (from, relation_type, to) in self.edges.iter() {
+                if !relation_type.is_main_line() {
+                    continue;
+                }
+                let next = match (*from == id, *to == id) {
+                    (true, _) => *to,
+                    (_, true) => *from,
+                    _ => continue,
+                };
+                if self.nodes.contains_key(&next) && main_line.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        main_line
+    }
+
+    // Kahn's algorithm over "comes before" edges, ties (and cycles) broken by release date
+    fn order_main_line(&self, main_line: &HashSet<u32>) -> Vec<u32> {
+        let mut successors: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+        let mut in_degree: HashMap<u32, usize> = main_line.iter().map(|id| (*id, 0)).collect();
+
+        for (from, relation_type, to) in self.edges.iter() {
+            if !main_line.contains(from) || !main_line.contains(to) {
+                continue;
+            }
+            let (before, after) = match relation_type {
+                RelationType::Sequel => (*from, *to),
+                RelationType::Prequel => (*to, *from),
+                _ => continue,
+            };
+            if successors.entry(before).or_default().insert(after) {
+                *in_degree.get_mut(&after).unwrap() += 1;
+            }
+        }
+
+        let key = |id: &u32| self.nodes[id].sort_key();
+        let mut ready: BTreeSet<((u32, u32, u32), u32)> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(id, _)| key(id))
+            .collect();
+        let mut order: Vec<u32> = Vec::new();
+
+        while order.len() < main_line.len() {
+            let next = match ready.iter().next().copied() {
+                Some(next) => {
+                    ready.remove(&next);
+                    next.1
+                }
+                // Cycle in the data, fall back to the earliest entry not placed yet
+                None => {
+                    let placed: HashSet<u32> = order.iter().copied().collect();
+                    let mut remaining: Vec<u32> = main_line
+                        .iter()
+                        .filter(|id| !placed.contains(id))
+                        .copied()
+                        .collect();
+                    remaining.sort_by_key(key);
+                    in_degree.insert(remaining[0], 0);
+                    remaining[0]
+                }
+            };
+            order.push(next);
+
+            for after in successors.get(&next).cloned().unwrap_or_default() {
+                let degree = in_degree.get_mut(&after).unwrap();
+                if *degree == 0 {
+                    continue;
+                }
+                *degree -= 1;
+                if *degree == 0 && !order.contains(&after) {
+                    ready.insert(key(&after));
+                }
+            }
+        }
+
+        order
+    }
+
+    // Closest main line entry up the BFS tree
+    fn anchor(&self, id: u32, main_line: &HashSet<u32>) -> Option<u32> {
+        let mut current = id;
+        let mut steps = 0;
+        while let Some(parent) = self.parents.get(&current) {
+            if main_line.contains(parent) {
+                return Some(*parent);
+            }
+            current = *parent;
+            steps += 1;
+            if steps > self.nodes.len() {
+                break;
+            }
+        }
+        None
+    }
+}
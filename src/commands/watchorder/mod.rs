@@ -0,0 +1,4 @@
+pub mod command;
+mod fetcher;
+pub mod graph;
+pub mod queries;
@@ -0,0 +1,12 @@
+use crate::utils::graphql::{Query, DATE, RELATIONS};
+use std::sync::LazyLock;
+
+pub static FETCH_RELATIONS_BY_ID: LazyLock<String> = LazyLock::new(|| {
+    Query::new("WatchOrder", "Media(id: $id)")
+        .variable("id", "Int")
+        .field("id")
+        .field("format")
+        .nested("startDate", &DATE)
+        .fragment(&RELATIONS)
+        .build()
+});
@@ -0,0 +1,39 @@
+pub mod commands;
+pub mod models;
+pub mod utils;
+
+use commands::{
+    anime::command::*,
+    config::command::*,
+    help::*,
+    ids::*,
+    manga::command::*,
+    mangadex::{command::*, follows::*},
+    ping::*,
+    prefs::command::*,
+    quiz::{command::*, leaderboard::*},
+    songs::command::*,
+    watchorder::command::*,
+};
+use serenity::framework::standard::macros::group;
+
+// TODO: Add recommend system
+#[group]
+#[commands(
+    help,
+    ping,
+    anime,
+    manga,
+    ln,
+    chapters,
+    follow_manga,
+    unfollow_manga,
+    songs,
+    quiz,
+    leaderboard,
+    config,
+    prefs,
+    watchorder,
+    refresh_ids
+)]
+pub struct General;
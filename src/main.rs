@@ -1,20 +1,36 @@
-mod commands;
-mod models;
-pub mod utils;
-
-use std::env;
+use std::{
+    collections::HashSet,
+    env,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use commands::{anime::command::*, help::*, manga::command::*, ping::*, songs::command::*};
+use annie_mai::{
+    commands::{
+        config::{
+            cooldowns::{take_cooldown, Cooldowns},
+            settings::{guild_settings, GuildConfig},
+        },
+        mangadex::follows::{poll_follows, MangaFollows},
+        prefs::settings::UserConfig,
+        quiz::leaderboard::Leaderboard,
+    },
+    models::guild_settings::DEFAULT_PREFIX,
+    utils::{
+        id_mapper::refresh_periodically,
+        message::{try_again_in, COMMAND_DISABLED, MISSING_PERMISSIONS, OWNERS_ONLY},
+    },
+    GENERAL_GROUP,
+};
 use dotenv::dotenv;
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 use serenity::{
     async_trait,
     client::{Client, Context, EventHandler},
     framework::standard::{
-        macros::{group, hook},
-        CommandResult, DispatchError, StandardFramework,
+        buckets::LimitedFor, macros::hook, CommandResult, DispatchError, StandardFramework,
     },
+    http::Http,
     model::{channel::Message, event::ResumedEvent, gateway::Ready},
     prelude::*,
     utils::parse_emoji,
@@ -22,14 +38,42 @@ use serenity::{
 
 #[hook]
 #[instrument]
-async fn before(_: &Context, msg: &Message, command_name: &str) -> bool {
+async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
     info!(
         "Got command '{}' by user '{}'",
         command_name, msg.author.name
     );
+
+    let settings = guild_settings(ctx, msg.guild_id).await;
+    if !settings.is_enabled(command_name) {
+        info!("Command '{}' is disabled in this guild", command_name);
+        let _ = msg.channel_id.say(&ctx.http, COMMAND_DISABLED).await;
+        return false;
+    }
+
+    // The server's own cooldown, the default bucket has already been passed
+    if let Err(limited) = take_cooldown(ctx, msg, command_name).await {
+        info!("Command '{}' is on cooldown in this guild", command_name);
+        if limited.is_first_try {
+            let _ = msg
+                .channel_id
+                .say(&ctx.http, try_again_in(limited.wait))
+                .await;
+        }
+        return false;
+    }
+
     true
 }
 
+#[hook]
+async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    match msg.guild_id {
+        Some(_) => Some(guild_settings(ctx, msg.guild_id).await.prefix),
+        None => Some(DEFAULT_PREFIX.to_string()),
+    }
+}
+
 #[hook]
 #[instrument]
 async fn after(_: &Context, _msg: &Message, command_name: &str, command_result: CommandResult) {
@@ -47,40 +91,48 @@ async fn unknown_command(ctx: &Context, msg: &Message, unknown_command_name: &st
     let reaction = parse_emoji("<:wtf:953730408158228570>").unwrap();
     let _ = msg.react(ctx, reaction).await;
 }
-// TODO: Figure out how to use this
+// The command runs once the bucket allows it again, this shows it wasn't ignored
 #[hook]
 async fn delay_action(ctx: &Context, msg: &Message) {
-    // You may want to handle a Discord rate limit if this fails.
-    let _ = msg.react(ctx, '⏱').await;
+    if let Err(why) = msg.react(ctx, '⏱').await {
+        error!("Error reacting to message: {:?}", why);
+    }
 }
 
 #[hook]
 async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError, _command_name: &str) {
-    if let DispatchError::Ratelimited(info) = error {
+    match error {
         // We notify them only once.
-        if info.is_first_try {
+        DispatchError::Ratelimited(info) if info.is_first_try => {
             let _ = msg
                 .channel_id
-                .say(
-                    &ctx.http,
-                    &format!("Try this again in {} seconds.", info.as_secs()),
-                )
+                .say(&ctx.http, try_again_in(info.rate_limit))
                 .await;
         }
+        DispatchError::LackingPermissions(_) => {
+            let _ = msg.channel_id.say(&ctx.http, MISSING_PERMISSIONS).await;
+        }
+        DispatchError::OnlyForOwners => {
+            let _ = msg.channel_id.say(&ctx.http, OWNERS_ONLY).await;
+        }
+        _ => {}
     }
 }
 
-// TODO: Add recommend system
-#[group]
-#[commands(help, ping, anime, manga, songs)]
-struct General;
-
-struct Handler;
+struct Handler {
+    // Ready fires again on reconnects, the poller should only start once
+    polling: AtomicBool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         println!("{} is connected!", ready.user.name);
+
+        if !self.polling.swap(true, Ordering::SeqCst) {
+            tokio::spawn(poll_follows(ctx));
+            tokio::spawn(refresh_periodically());
+        }
     }
 
     #[instrument(skip(self, _ctx))]
@@ -95,24 +147,76 @@ async fn main() {
     dotenv().ok();
     tracing_subscriber::fmt::init();
 
+    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+
+    // Owner only commands are for whoever owns the application, or its team
+    let owners = match Http::new(&token).get_current_application_info().await {
+        Ok(info) => match info.team {
+            Some(team) => HashSet::from([team.owner_user_id]),
+            None => HashSet::from([info.owner.id]),
+        },
+        Err(why) => {
+            error!("Could not access application info: {:?}", why);
+            HashSet::new()
+        }
+    };
+
     let framework = StandardFramework::new()
-        .configure(|c| c.prefix("!"))
+        // The empty prefix disables serenity's default "~", every prefix comes from guild settings
+        .configure(|c| c.prefix("").dynamic_prefix(dynamic_prefix).owners(owners))
         .before(before)
         .after(after)
         .unrecognised_command(unknown_command)
         .on_dispatch_error(dispatch_error)
+        // Every server gets these, !config cooldown adds stricter ones for a single server.
+        // Delayed commands wait for their turn once, anything more is refused
+        .bucket("lookup", |b| {
+            b.limit_for(LimitedFor::User)
+                .delay(1)
+                .time_span(30)
+                .limit(10)
+                .await_ratelimits(1)
+                .delay_action(delay_action)
+        })
+        .await
+        // Walks whole franchises, a request per entry
+        .bucket("heavy", |b| {
+            b.limit_for(LimitedFor::User)
+                .delay(5)
+                .time_span(60)
+                .limit(4)
+                .await_ratelimits(1)
+                .delay_action(delay_action)
+        })
+        .await
+        .bucket("quiz", |b| b.limit_for(LimitedFor::Channel).delay(10))
+        .await
+        .bucket("follows", |b| {
+            b.limit_for(LimitedFor::Guild).time_span(60).limit(10)
+        })
+        .await
         .group(&GENERAL_GROUP);
-    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT;
 
     let mut client = Client::builder(&token, intents)
-        .event_handler(Handler)
+        .event_handler(Handler {
+            polling: AtomicBool::new(false),
+        })
         .framework(framework)
         .await
         .expect("Err creating client");
 
+    {
+        let mut data = client.data.write().await;
+        data.insert::<Leaderboard>(Leaderboard::open());
+        data.insert::<GuildConfig>(GuildConfig::open());
+        data.insert::<UserConfig>(UserConfig::open());
+        data.insert::<MangaFollows>(MangaFollows::open());
+        data.insert::<Cooldowns>(Cooldowns::open());
+    }
+
     if let Err(why) = client.start().await {
         println!("Client error: {:?}", why);
     }
@@ -1,5 +1,7 @@
 use super::{
-    anilist_common::{CoverImage, ExternalLinks, Tag, Title},
+    anilist_common::{CoverImage, ExternalLink, Relations, Tag, Title},
+    fuzzy_date::FuzzyDate,
+    guild_settings::LinkFilter,
     transformers::Transformers,
 };
 use crate::utils::{
@@ -7,33 +9,35 @@ use crate::utils::{
     EMPTY_STR,
 };
 use serde::Deserialize;
-use std::fmt::Write;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-
 pub struct Anime {
     #[serde(rename = "type")]
     media_type: Option<String>,
-    #[allow(dead_code)]
-    id: u32,
+    id: Option<u32>,
     id_mal: Option<u32>,
     title: Title,
     synonyms: Option<Vec<String>>,
     season: Option<String>,
     season_year: Option<u32>,
+    start_date: Option<FuzzyDate>,
+    end_date: Option<FuzzyDate>,
     format: Option<String>,
     status: Option<String>,
     episodes: Option<u32>,
     duration: Option<u32>,
     genres: Vec<String>,
+    is_adult: Option<bool>,
     source: Option<String>,
     cover_image: CoverImage,
     average_score: Option<u32>,
+    popularity: Option<u32>,
     studios: Option<Studios>,
     site_url: String,
-    external_links: Option<Vec<ExternalLinks>>,
+    external_links: Option<Vec<ExternalLink>>,
     trailer: Option<Trailer>,
+    relations: Option<Relations>,
     description: Option<String>,
     tags: Vec<Tag>,
 }
@@ -47,6 +51,7 @@ pub struct Studios {
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Edges {
+    #[allow(dead_code)]
     pub id: u32,
     pub is_main: bool,
 }
@@ -54,6 +59,7 @@ pub struct Edges {
 #[derive(Deserialize, Debug, Clone)]
 
 pub struct Nodes {
+    #[allow(dead_code)]
     pub id: u32,
     pub name: String,
 }
@@ -66,6 +72,11 @@ pub struct Trailer {
 }
 
 impl Anime {
+    // The source material list comes from relations, a SFW channel shouldn't see adult entries there
+    pub fn hide_adult_relations(&mut self) {
+        self.relations = self.relations.as_ref().map(Relations::without_adult);
+    }
+
     pub fn transform_season(&self) -> String {
         let season = match &self.season {
             Some(season) => season.to_string(),
@@ -76,7 +87,7 @@ impl Anime {
             None => "".to_string(),
         };
 
-        let built_string = vec![season, year];
+        let built_string = [season, year];
         let return_string = titlecase(built_string.join(" ").trim());
 
         match return_string {
@@ -137,60 +148,34 @@ impl Anime {
         main_studios.join(" x ")
     }
 
-    fn build_animixplay_link(&self) -> Option<String> {
-        self.id_mal
-            .as_ref()
-            .map(|id| format!("https://animixplay.to/anime/{}", id))
+    // Streaming links the guild wants to see, best first
+    pub fn transform_links(&self, filter: &LinkFilter) -> Vec<ExternalLink> {
+        filter.apply(self.external_links.as_deref().unwrap_or_default())
     }
 
-    pub fn transform_links(&self) -> String {
-        let mut return_string: String = match &self.external_links {
-            Some(links) => {
-                if links.is_empty() {
-                    EMPTY_STR.to_string()
-                } else {
-                    let parsed_links = links
-                        .iter()
-                        .filter(|link| link.url_type.to_lowercase() == "streaming")
-                        .map(|link| link.url.to_string())
-                        .collect::<Vec<String>>()
-                        .into_iter()
-                        .filter(|link| match link {
-                            _ if link.contains("hbo") => true,
-                            _ if link.contains("netflix") => true,
-                            _ if link.contains("crunchyroll") => true,
-                            _ => false,
-                        })
-                        .collect::<Vec<String>>()
-                        .into_iter()
-                        .map(|link| match link {
-                            _ if link.contains("hbo") => linker("HBO".to_string(), link),
-                            _ if link.contains("netflix") => linker("Netflix".to_string(), link),
-                            _ if link.contains("crunchyroll") => {
-                                linker("Crunchyroll".to_string(), link)
-                            }
-                            _ => "Invalid".to_string(),
-                        })
-                        .collect::<Vec<String>>()
-                        .join(" ");
-                    if !parsed_links.is_empty() {
-                        parsed_links
-                    } else {
-                        EMPTY_STR.to_string()
-                    }
-                }
-            }
-            None => EMPTY_STR.to_string(),
-        };
-        let animix_link = self.build_animixplay_link();
-        if let Some(url) = animix_link {
-            if return_string == *EMPTY_STR {
-                return_string = linker("AniMixPlay".to_string(), url);
-            } else {
-                write!(return_string, " {}", linker("AniMixPlay".to_string(), url)).unwrap();
-            }
+    // What this anime adapts, linked and with the command to look it up directly
+    pub fn transform_source_material(&self, prefix: &str) -> String {
+        let source = self.transform_source();
+        let source_material = self
+            .relations
+            .as_ref()
+            .map(|relations| relations.of_type("SOURCE", "MANGA"))
+            .unwrap_or_default();
+
+        match source_material.first() {
+            None => source,
+            Some(node) => format!(
+                "{}\n{} {}",
+                source,
+                linker(
+                    node.display_title(),
+                    node.site_url
+                        .to_owned()
+                        .unwrap_or_else(|| format!("https://anilist.co/manga/{}", node.id))
+                ),
+                code(format!("{}manga {}", prefix, node.id))
+            ),
         }
-        return_string
     }
 
     pub fn transform_trailer(&self) -> String {
@@ -210,6 +195,10 @@ impl Transformers for Anime {
         self.media_type.as_ref().unwrap().to_string().to_lowercase()
     }
 
+    fn get_id(&self) -> Option<u32> {
+        self.id
+    }
+
     fn get_mal_id(&self) -> Option<u32> {
         self.id_mal
     }
@@ -238,10 +227,22 @@ impl Transformers for Anime {
         self.status.to_owned()
     }
 
+    fn get_start_date(&self) -> Option<FuzzyDate> {
+        self.start_date
+    }
+
+    fn get_end_date(&self) -> Option<FuzzyDate> {
+        self.end_date
+    }
+
     fn get_genres(&self) -> Vec<String> {
         self.genres.to_owned()
     }
 
+    fn get_is_adult(&self) -> bool {
+        self.is_adult.unwrap_or(false)
+    }
+
     fn get_source(&self) -> Option<String> {
         self.source.to_owned()
     }
@@ -254,6 +255,10 @@ impl Transformers for Anime {
         self.average_score.to_owned()
     }
 
+    fn get_popularity(&self) -> Option<u32> {
+        self.popularity.to_owned()
+    }
+
     fn get_site_url(&self) -> String {
         self.site_url.to_owned()
     }
@@ -266,6 +271,10 @@ impl Transformers for Anime {
         self.tags.to_owned()
     }
 
+    fn get_relations(&self) -> Option<Relations> {
+        self.relations.to_owned()
+    }
+
     fn transform_mal_id(&self) -> Option<String> {
         self.id_mal
             .map(|mal_id| format!("https://www.myanimelist.net/anime/{}", mal_id))
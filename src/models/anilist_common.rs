@@ -1,3 +1,4 @@
+use super::fuzzy_date::FuzzyDate;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -17,13 +18,150 @@ pub struct CoverImage {
 }
 
 #[derive(Deserialize, Debug, Clone)]
-pub struct ExternalLinks {
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLink {
     pub url: String,
-    #[serde(alias = "type")]
-    pub url_type: String,
+    pub site: String,
+    #[serde(rename = "type")]
+    pub link_type: Option<String>,
+    // Region locked links carry the language they are for
+    pub language: Option<String>,
+    // Buttons can't show custom images, kept for whenever embeds can
+    #[allow(dead_code)]
+    pub icon: Option<String>,
+    pub is_disabled: Option<bool>,
+}
+
+impl ExternalLink {
+    pub fn is_streaming(&self) -> bool {
+        self.link_type.as_deref() == Some("STREAMING")
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.is_disabled.unwrap_or(false)
+    }
+
+    pub fn label(&self) -> String {
+        match &self.language {
+            Some(language) => format!("{} ({})", self.site, language),
+            None => self.site.to_string(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Tag {
     pub name: String,
+    pub rank: Option<u32>,
+    pub is_general_spoiler: Option<bool>,
+    pub is_media_spoiler: Option<bool>,
+}
+
+impl Tag {
+    pub fn is_spoiler(&self) -> bool {
+        self.is_general_spoiler.unwrap_or(false) || self.is_media_spoiler.unwrap_or(false)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Characters {
+    pub nodes: Vec<Character>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Character {
+    pub name: CharacterName,
+    pub image: Option<CharacterImage>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharacterName {
+    pub full: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharacterImage {
+    pub large: Option<String>,
+    pub medium: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Relations {
+    pub edges: Vec<RelationEdge>,
+    pub nodes: Vec<RelationNode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationEdge {
+    pub relation_type: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationNode {
+    pub id: u32,
+    #[serde(rename = "type")]
+    pub media_type: Option<String>,
+    pub format: Option<String>,
+    pub is_adult: Option<bool>,
+    pub title: Title,
+    pub start_date: Option<FuzzyDate>,
+    pub season: Option<String>,
+    pub season_year: Option<u32>,
+    pub studios: Option<RelationStudios>,
+    pub site_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelationStudios {
+    pub nodes: Vec<RelationStudio>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelationStudio {
+    pub name: String,
+}
+
+impl Relations {
+    // Edges and nodes come back as parallel lists
+    pub fn pairs(&self) -> impl Iterator<Item = (&RelationEdge, &RelationNode)> {
+        self.edges.iter().zip(self.nodes.iter())
+    }
+
+    // Nodes related through `relation_type` (e.g. SOURCE or ADAPTATION) of the given media type
+    pub fn of_type(&self, relation_type: &str, media_type: &str) -> Vec<&RelationNode> {
+        self.pairs()
+            .filter(|(edge, _)| edge.relation_type.as_deref() == Some(relation_type))
+            .filter(|(_, node)| node.media_type.as_deref() == Some(media_type))
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    // Drops adult entries along with their edges, for channels that can't show them
+    pub fn without_adult(&self) -> Relations {
+        let (edges, nodes) = self
+            .pairs()
+            .filter(|(_, node)| !node.is_adult())
+            .map(|(edge, node)| (edge.clone(), node.clone()))
+            .unzip();
+
+        Relations { edges, nodes }
+    }
+}
+
+impl RelationNode {
+    pub fn is_adult(&self) -> bool {
+        self.is_adult.unwrap_or(false)
+    }
+
+    pub fn display_title(&self) -> String {
+        self.title
+            .romaji
+            .to_owned()
+            .or_else(|| self.title.english.to_owned())
+            .or_else(|| self.title.native.to_owned())
+            .unwrap_or_default()
+    }
 }
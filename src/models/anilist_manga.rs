@@ -1,48 +1,79 @@
 use super::{
-    anilist_common::{CoverImage, ExternalLinks, Tag, Title},
+    anilist_common::{CoverImage, ExternalLink, Relations, Tag, Title},
+    fuzzy_date::FuzzyDate,
+    guild_settings::LinkFilter,
     transformers::Transformers,
 };
-use crate::utils::{formatter::code, EMPTY_STR};
-use chrono::NaiveDate;
+use crate::utils::{
+    formatter::{code, linker, remove_underscores_and_titlecase},
+    EMPTY_STR,
+};
 use serde::Deserialize;
 use titlecase::titlecase;
 
+// Long running franchises have a lot of these, discord hates large embeds
+const MAX_ADAPTATIONS: usize = 3;
+// Same goes for anthologies and their artists
+const MAX_STAFF_NAMES: usize = 3;
+
+// Assistants, translators and the like don't get credited
+fn is_supporting_role(role: &str) -> bool {
+    ["assistant", "translat", "letter", "edit", "touch-up"]
+        .iter()
+        .any(|supporting| role.contains(supporting))
+}
+
+fn is_writer_role(role: &str) -> bool {
+    role.contains("story") || role.contains("original creator") || role.contains("author")
+}
+
+fn is_artist_role(role: &str) -> bool {
+    role.contains("art") || role.contains("illustration")
+}
+
+fn format_staff_names(names: &[String]) -> String {
+    let shown = names
+        .iter()
+        .take(MAX_STAFF_NAMES)
+        .map(|name| code(name.to_string()))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    match names.len() > MAX_STAFF_NAMES {
+        true => format!("{} and {} more", shown, names.len() - MAX_STAFF_NAMES),
+        false => shown,
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Manga {
     #[serde(rename = "type")]
     media_type: Option<String>,
-    #[allow(dead_code)]
-    id: u32,
+    id: Option<u32>,
     id_mal: Option<u32>,
     title: Title,
     synonyms: Option<Vec<String>>,
-    start_date: Option<AnilistDate>,
-    end_date: Option<AnilistDate>,
+    start_date: Option<FuzzyDate>,
+    end_date: Option<FuzzyDate>,
     format: Option<String>,
     status: Option<String>,
     chapters: Option<u32>,
     volumes: Option<u32>,
     genres: Vec<String>,
+    is_adult: Option<bool>,
     source: Option<String>,
     cover_image: CoverImage,
     average_score: Option<u32>,
+    popularity: Option<u32>,
     staff: Option<Staff>,
     site_url: String,
-    // TODO: Decide what to do with this
-    #[allow(dead_code)]
-    external_links: Option<Vec<ExternalLinks>>,
+    external_links: Option<Vec<ExternalLink>>,
+    relations: Option<Relations>,
     description: Option<String>,
     tags: Vec<Tag>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct AnilistDate {
-    pub year: Option<u32>,
-    pub month: Option<u32>,
-    pub day: Option<u32>,
-}
-
 #[derive(Deserialize, Debug, Clone)]
 pub struct Staff {
     pub edges: Vec<Edges>,
@@ -51,6 +82,7 @@ pub struct Staff {
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Edges {
+    #[allow(dead_code)]
     pub id: u32,
     pub role: String,
 }
@@ -58,8 +90,10 @@ pub struct Edges {
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Nodes {
+    #[allow(dead_code)]
     pub id: u32,
     pub name: StaffName,
+    #[allow(dead_code)]
     pub site_url: String,
 }
 
@@ -69,36 +103,9 @@ pub struct StaffName {
 }
 
 impl Manga {
-    pub fn transform_date(&self) -> String {
-        let start_date = self.start_date.clone().unwrap();
-        let start_date_string = NaiveDate::from_ymd(
-            start_date.year.unwrap_or(0).try_into().unwrap(),
-            start_date.month.unwrap_or(0),
-            start_date.day.unwrap_or(0),
-        );
-
-        let formatted_start_date = start_date_string.format("%b %e %Y").to_string();
-
-        let is_end_date_available = if let Some(end_date) = &self.end_date {
-            end_date.year.is_some() && end_date.month.is_some() && end_date.day.is_some()
-        } else {
-            false
-        };
-
-        if is_end_date_available {
-            let end_date = &self.end_date.clone().unwrap();
-            let end_date_string = NaiveDate::from_ymd(
-                end_date.year.unwrap_or(0).try_into().unwrap(),
-                end_date.month.unwrap_or(0),
-                end_date.day.unwrap_or(0),
-            );
-
-            let formatted_end_date = end_date_string.format("%b %e %Y").to_string();
-
-            format!("{} - {}", formatted_start_date, formatted_end_date)
-        } else {
-            formatted_start_date
-        }
+    // The adaptations list comes from relations, a SFW channel shouldn't see adult entries there
+    pub fn hide_adult_relations(&mut self) {
+        self.relations = self.relations.as_ref().map(Relations::without_adult);
     }
 
     pub fn transform_chapters(&self) -> String {
@@ -115,49 +122,122 @@ impl Manga {
         }
     }
 
+    // Writers x artists, novels credit an author and an illustrator and anthologies can have
+    // a whole crowd of artists so those get cut off
     pub fn transform_staff(&self) -> String {
-        if self.staff.is_none() {
-            return EMPTY_STR.to_string();
-        }
-
-        let staff = &self.staff.as_ref().unwrap();
+        let staff = match &self.staff {
+            Some(staff) => staff,
+            None => return EMPTY_STR.to_string(),
+        };
 
-        if staff.edges.is_empty() || staff.nodes.is_empty() {
-            return EMPTY_STR.to_string();
-        }
+        let mut writers: Vec<String> = Vec::new();
+        let mut artists: Vec<String> = Vec::new();
 
-        let mut mangaka_index = 0_usize;
-        let mut artist_index = 0_usize;
+        for (edge, node) in staff.edges.iter().zip(staff.nodes.iter()) {
+            let role = edge.role.to_lowercase();
+            if is_supporting_role(&role) {
+                continue;
+            }
 
-        for (index, edge) in staff.edges.iter().enumerate() {
-            if edge.role.to_lowercase().contains("story") {
-                mangaka_index = index;
+            // Roles look like "Story & Art (ch 1-20)", only the part before the brackets matters
+            let base_role = role.split('(').next().unwrap_or_default();
+            let name = titlecase(&node.name.full);
+            if is_writer_role(base_role) && !writers.contains(&name) {
+                writers.push(name.to_string());
             }
-            if edge.role.to_lowercase().contains("art") {
-                artist_index = index;
+            if is_artist_role(base_role) && !artists.contains(&name) {
+                artists.push(name);
             }
         }
 
-        let mangaka_name = staff.nodes[mangaka_index].name.full.to_string();
-        let artist_name = staff.nodes[artist_index].name.full.to_string();
-
-        if mangaka_name == artist_name {
-            code(titlecase(&mangaka_name))
-        } else {
-            format!(
+        match (writers.is_empty(), artists.is_empty()) {
+            (true, true) => EMPTY_STR.to_string(),
+            (false, true) => format_staff_names(&writers),
+            (true, false) => format_staff_names(&artists),
+            (false, false) if writers == artists => format_staff_names(&writers),
+            (false, false) => format!(
                 "{} x {}",
-                code(titlecase(&mangaka_name)),
-                code(titlecase(&artist_name))
-            )
+                format_staff_names(&writers),
+                format_staff_names(&artists)
+            ),
         }
     }
 
-    // TODO: Make this for MangaDex I guess?
-    // fn build_animixplay_link(&self) -> Option<String> {
-    //     self.id_mal
-    //         .as_ref()
-    //         .map(|id| format!("https://animixplay.to/anime/{}", id))
-    // }
+    // Official places to read it, filtered the same way as streaming links
+    pub fn transform_links(&self, filter: &LinkFilter) -> Vec<ExternalLink> {
+        filter.apply(self.external_links.as_deref().unwrap_or_default())
+    }
+
+    // Anime adaptations with their season and studio, linked and with the command to look them up.
+    // Only the first few make it in, the rest are counted like staff names
+    pub fn transform_adaptations(&self, prefix: &str) -> String {
+        let adaptations = self
+            .relations
+            .as_ref()
+            .map(|relations| relations.of_type("ADAPTATION", "ANIME"))
+            .unwrap_or_default();
+
+        if adaptations.is_empty() {
+            return EMPTY_STR.to_string();
+        }
+
+        let shown = adaptations
+            .iter()
+            .take(MAX_ADAPTATIONS)
+            .map(|node| {
+                let season = [
+                    node.season.as_deref().map(remove_underscores_and_titlecase),
+                    node.season_year.map(|year| year.to_string()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(" ");
+                let studios = node
+                    .studios
+                    .as_ref()
+                    .map(|studios| {
+                        studios
+                            .nodes
+                            .iter()
+                            .map(|studio| code(studio.name.to_string()))
+                            .collect::<Vec<String>>()
+                            .join(" x ")
+                    })
+                    .unwrap_or_default();
+                let details = [season, studios]
+                    .into_iter()
+                    .filter(|detail| !detail.is_empty())
+                    .collect::<Vec<String>>()
+                    .join(" - ");
+
+                format!(
+                    "{} {}{}",
+                    linker(
+                        node.display_title(),
+                        node.site_url
+                            .to_owned()
+                            .unwrap_or_else(|| format!("https://anilist.co/anime/{}", node.id))
+                    ),
+                    code(format!("{}anime {}", prefix, node.id)),
+                    match details.is_empty() {
+                        true => "".to_string(),
+                        false => format!("\n{}", details),
+                    }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        match adaptations.len() > MAX_ADAPTATIONS {
+            true => format!(
+                "{}\nand {} more",
+                shown,
+                adaptations.len() - MAX_ADAPTATIONS
+            ),
+            false => shown,
+        }
+    }
 }
 
 impl Transformers for Manga {
@@ -165,6 +245,10 @@ impl Transformers for Manga {
         self.media_type.as_ref().unwrap().to_string().to_lowercase()
     }
 
+    fn get_id(&self) -> Option<u32> {
+        self.id
+    }
+
     fn get_mal_id(&self) -> Option<u32> {
         self.id_mal
     }
@@ -193,10 +277,22 @@ impl Transformers for Manga {
         self.status.to_owned()
     }
 
+    fn get_start_date(&self) -> Option<FuzzyDate> {
+        self.start_date
+    }
+
+    fn get_end_date(&self) -> Option<FuzzyDate> {
+        self.end_date
+    }
+
     fn get_genres(&self) -> Vec<String> {
         self.genres.to_owned()
     }
 
+    fn get_is_adult(&self) -> bool {
+        self.is_adult.unwrap_or(false)
+    }
+
     fn get_source(&self) -> Option<String> {
         self.source.to_owned()
     }
@@ -209,6 +305,10 @@ impl Transformers for Manga {
         self.average_score.to_owned()
     }
 
+    fn get_popularity(&self) -> Option<u32> {
+        self.popularity.to_owned()
+    }
+
     fn get_site_url(&self) -> String {
         self.site_url.to_owned()
     }
@@ -221,6 +321,10 @@ impl Transformers for Manga {
         self.tags.to_owned()
     }
 
+    fn get_relations(&self) -> Option<Relations> {
+        self.relations.to_owned()
+    }
+
     fn transform_mal_id(&self) -> Option<String> {
         self.id_mal
             .map(|mal_id| format!("https://www.myanimelist.net/manga/{}", mal_id))
@@ -0,0 +1,128 @@
+use super::anilist_common::{Character, Characters, CoverImage, Title};
+use html2md::parse_html;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+const REDACTED: &str = "█████";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizMedia {
+    #[allow(dead_code)]
+    id: u32,
+    title: Title,
+    synonyms: Option<Vec<String>>,
+    cover_image: CoverImage,
+    description: Option<String>,
+    characters: Option<Characters>,
+}
+
+impl QuizMedia {
+    // Every title a player could reasonably answer with
+    pub fn answers(&self) -> Vec<String> {
+        let mut answers: Vec<String> = vec![
+            self.title.romaji.to_owned(),
+            self.title.english.to_owned(),
+            self.title.native.to_owned(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        answers.extend(self.synonyms.to_owned().unwrap_or_default());
+        // Synonyms often repeat a title, keeps the first of each in order
+        let mut seen = HashSet::new();
+        answers.retain(|answer| !answer.trim().is_empty() && seen.insert(answer.to_string()));
+        answers
+    }
+
+    pub fn display_title(&self) -> String {
+        match (&self.title.romaji, &self.title.english) {
+            (Some(romaji), Some(english)) if romaji != english => {
+                format!("{} ({})", romaji, english)
+            }
+            (Some(romaji), _) => romaji.to_string(),
+            (None, Some(english)) => english.to_string(),
+            (None, None) => self.title.native.to_owned().unwrap_or_default(),
+        }
+    }
+
+    pub fn cover_url(&self) -> Option<String> {
+        self.cover_image
+            .extra_large
+            .to_owned()
+            .or_else(|| self.cover_image.large.to_owned())
+            .or_else(|| self.cover_image.medium.to_owned())
+    }
+
+    // The first character that actually has a picture
+    pub fn character(&self) -> Option<&Character> {
+        self.characters.as_ref().and_then(|characters| {
+            characters.nodes.iter().find(|character| {
+                character
+                    .image
+                    .as_ref()
+                    .map(|image| image.large.is_some() || image.medium.is_some())
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    pub fn character_image_url(&self) -> Option<String> {
+        self.character()
+            .and_then(|character| character.image.to_owned())
+            .and_then(|image| image.large.or(image.medium))
+    }
+
+    // Description with every title (and every significant word of a title) blanked out
+    pub fn redacted_description(&self) -> Option<String> {
+        let mut description = parse_html(self.description.as_ref()?);
+
+        let mut words: Vec<String> = self.answers();
+        for answer in self.answers() {
+            words.extend(
+                answer
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| word.chars().count() > 3)
+                    .map(|word| word.to_string()),
+            );
+        }
+        if let Some(name) = self.character().and_then(|c| c.name.full.to_owned()) {
+            words.extend(name.split_whitespace().map(|word| word.to_string()));
+        }
+        // Longest first so whole titles get redacted before their pieces
+        words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+
+        for word in words {
+            description = replace_case_insensitive(&description, &word, REDACTED);
+        }
+
+        match description.trim().is_empty() {
+            true => None,
+            false => Some(description),
+        }
+    }
+}
+
+fn replace_case_insensitive(text: &str, pattern: &str, replacement: &str) -> String {
+    if pattern.is_empty() {
+        return text.to_string();
+    }
+
+    let lower_text = text.to_lowercase();
+    let lower_pattern = pattern.to_lowercase();
+    // Lowercasing can change byte lengths for some scripts, bail out rather than slice badly
+    if lower_text.len() != text.len() || lower_pattern.len() != pattern.len() {
+        return text.replace(pattern, replacement);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (start, _) in lower_text.match_indices(&lower_pattern) {
+        result.push_str(&text[last_end..start]);
+        result.push_str(replacement);
+        last_end = start + pattern.len();
+    }
+    result.push_str(&text[last_end..]);
+    result
+}
@@ -3,77 +3,150 @@ use crate::commands::{
     manga::queries::{FETCH_MANGA, FETCH_MANGA_BY_ID},
 };
 use crate::models::{
-    id_response::FetchResponse as IdResponse,
-    media_list_response::FetchResponse as MediaListResponse, media_type::MediaType as Type,
-    transformers::Transformers,
+    id_response::FetchResponse as IdResponse, media_format::MediaFormat,
+    media_list_response::FetchResponse as MediaListResponse, media_query::QueryError,
+    media_type::MediaType as Type, transformers::Transformers,
+};
+use crate::utils::providers::ProviderError;
+use crate::utils::{
+    fetchers::fetch_by_arguments::{fetch_by_id, fetch_by_mal_id, fetch_by_name},
+    message::{
+        ADULT_CONTENT, NOT_FOUND_ANIME, NOT_FOUND_MANGA, PROVIDERS_UNAVAILABLE, UNMAPPED_ID,
+    },
 };
-use crate::utils::fetchers::fetch_by_arguments::{fetch_by_id, fetch_by_name};
 use tracing::info;
 
 pub struct AnimeConfig {
-    argument: Argument,
     id_query: String,
     search_query: String,
 }
 
 pub struct MangaConfig {
-    argument: Argument,
     id_query: String,
     search_query: String,
 }
 
 pub enum Argument {
     Id(u32),
-    Search(String),
+    MalId(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    pub allow_adult: bool,
+    // Only search these formats, None searches everything
+    pub format_in: Option<Vec<MediaFormat>>,
+    // Only search entries that started this year
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchError {
+    NotFound,
+    // Adult entry requested somewhere adult content isn't allowed
+    AdultContent,
+    InvalidQuery(QueryError),
+    // An id none of the providers could find an entry for
+    Unmapped,
+    // Every provider is down or rate limiting us
+    Unavailable,
+}
+
+impl FetchError {
+    pub fn message(&self, media_type: Type) -> &'static str {
+        match (self, media_type) {
+            (FetchError::NotFound, Type::Anime) => NOT_FOUND_ANIME,
+            (FetchError::NotFound, Type::Manga) => NOT_FOUND_MANGA,
+            (FetchError::AdultContent, _) => ADULT_CONTENT,
+            (FetchError::InvalidQuery(why), _) => why.message(),
+            (FetchError::Unmapped, _) => UNMAPPED_ID,
+            (FetchError::Unavailable, _) => PROVIDERS_UNAVAILABLE,
+        }
+    }
+}
+
+// What every lookup does with whatever it ended up matching
+pub fn check_match<T: Transformers>(
+    media: Option<T>,
+    options: &FetchOptions,
+) -> Result<T, FetchError> {
+    match media {
+        None => Err(FetchError::NotFound),
+        Some(media) if media.get_is_adult() && !options.allow_adult => {
+            Err(FetchError::AdultContent)
+        }
+        // Searches only get these formats back, ids and links can point at anything
+        Some(media) if !format_allowed(&media, options) => {
+            Err(FetchError::InvalidQuery(QueryError::WrongMediaType))
+        }
+        Some(media) => Ok(media),
+    }
+}
+
+pub(crate) fn format_allowed<T: Transformers>(media: &T, options: &FetchOptions) -> bool {
+    match (&options.format_in, media.get_format()) {
+        (Some(formats), Some(format)) => formats
+            .iter()
+            .any(|allowed| allowed.anilist_name() == format),
+        _ => true,
+    }
 }
 
 pub trait Response {
-    fn new(argument: Argument) -> Self;
-    fn get_argument(&self) -> &Argument;
+    fn new() -> Self;
     fn get_id_query(&self) -> String;
     fn get_search_query(&self) -> String;
 
-    fn fetch<
-        T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
-    >(
+    // Every search result, before any matching
+    fn search<T: serde::de::DeserializeOwned + std::fmt::Debug>(
         &self,
-        media_type: Type,
-    ) -> Option<T> {
-        let response = match self.get_argument() {
-            Argument::Id(value) => {
-                let fetched_data = fetch_by_id(self.get_id_query(), *value);
-                let fetch_response: IdResponse<T> = serde_json::from_str(&fetched_data).unwrap();
-                info!("Deserialized response: {:#?}", fetch_response);
-                fetch_response.data.unwrap().media
-            }
-            Argument::Search(value) => {
-                let fetched_data = fetch_by_name(self.get_search_query(), value.to_string());
-                let fetch_response: MediaListResponse<T> =
-                    serde_json::from_str(&fetched_data).unwrap();
-                info!("Deserialized response: {:#?}", fetch_response);
-                let result = fetch_response.fuzzy_match(value, media_type);
-                info!("Fuzzy Response: {:#?}", result);
-                result
-            }
-        };
+        url: &str,
+        search: &str,
+        options: &FetchOptions,
+    ) -> Result<MediaListResponse<T>, ProviderError> {
+        let format_in = options.format_in.as_ref().map(|formats| {
+            formats
+                .iter()
+                .map(|format| format.anilist_name().to_string())
+                .collect()
+        });
+        let fetched_data = fetch_by_name(
+            url,
+            self.get_search_query(),
+            search.to_string(),
+            format_in,
+            options.year,
+        )?;
+        let fetch_response: MediaListResponse<T> =
+            serde_json::from_str(&fetched_data).map_err(|_| ProviderError::Malformed)?;
+        info!("Deserialized response: {:#?}", fetch_response);
+        fetch_response.checked(&self.get_search_query())
+    }
 
-        response
+    fn get<T: serde::de::DeserializeOwned + std::fmt::Debug>(
+        &self,
+        url: &str,
+        argument: &Argument,
+    ) -> Result<Option<T>, ProviderError> {
+        let fetched_data = match argument {
+            Argument::Id(value) => fetch_by_id(url, self.get_id_query(), *value)?,
+            Argument::MalId(value) => fetch_by_mal_id(url, self.get_id_query(), *value)?,
+        };
+        let fetch_response: IdResponse<T> =
+            serde_json::from_str(&fetched_data).map_err(|_| ProviderError::Malformed)?;
+        info!("Deserialized response: {:#?}", fetch_response);
+        fetch_response.into_media(&self.get_id_query())
     }
 }
 
 impl Response for AnimeConfig {
-    fn new(argument: Argument) -> AnimeConfig {
+    fn new() -> AnimeConfig {
         AnimeConfig {
-            argument,
             id_query: FETCH_ANIME_BY_ID.to_string(),
             search_query: FETCH_ANIME.to_string(),
         }
     }
 
-    fn get_argument(&self) -> &Argument {
-        &self.argument
-    }
-
     fn get_id_query(&self) -> String {
         self.id_query.to_owned()
     }
@@ -83,20 +156,14 @@ impl Response for AnimeConfig {
     }
 }
 
-// TODO: Figure out whats common and can be moved to make it reusable
 impl Response for MangaConfig {
-    fn new(argument: Argument) -> MangaConfig {
+    fn new() -> MangaConfig {
         MangaConfig {
-            argument,
             id_query: FETCH_MANGA_BY_ID.to_string(),
             search_query: FETCH_MANGA.to_string(),
         }
     }
 
-    fn get_argument(&self) -> &Argument {
-        &self.argument
-    }
-
     fn get_id_query(&self) -> String {
         self.id_query.to_owned()
     }
@@ -0,0 +1,100 @@
+use crate::utils::EMPTY_STR;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+const ONGOING: &str = "Ongoing";
+const UNKNOWN: &str = "?";
+
+// AniList's FuzzyDate, any part of it can be missing
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct FuzzyDate {
+    pub year: Option<u32>,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+impl FuzzyDate {
+    // A part only counts if everything before it is known and it is a real date,
+    // so 2019-??-05 is just 2019 and Feb 30 is just Feb
+    fn parts(&self) -> (Option<u32>, Option<u32>, Option<u32>) {
+        let year = match self.year {
+            Some(year) if year > 0 => year,
+            _ => return (None, None, None),
+        };
+        let month = match self.month {
+            Some(month) if (1..=12).contains(&month) => month,
+            _ => return (Some(year), None, None),
+        };
+        let day = self
+            .day
+            .filter(|day| NaiveDate::from_ymd_opt(year as i32, month, *day).is_some());
+
+        (Some(year), Some(month), day)
+    }
+
+    // "2019", "Apr 2019" or "Apr 5 2019"
+    pub fn format(&self) -> Option<String> {
+        match self.parts() {
+            (Some(year), Some(month), Some(day)) => {
+                NaiveDate::from_ymd_opt(year as i32, month, day)
+                    .map(|date| date.format("%b %-d %Y").to_string())
+            }
+            (Some(year), Some(month), None) => NaiveDate::from_ymd_opt(year as i32, month, 1)
+                .map(|date| date.format("%b %Y").to_string()),
+            (Some(year), _, _) => Some(year.to_string()),
+            _ => None,
+        }
+    }
+
+    // Missing parts sort after everything known at the same precision
+    pub fn sort_key(&self) -> (u32, u32, u32) {
+        let (year, month, day) = self.parts();
+        (
+            year.unwrap_or(u32::MAX),
+            month.unwrap_or(u32::MAX),
+            day.unwrap_or(u32::MAX),
+        )
+    }
+
+    // "Apr 2019 - Ongoing", "2019 - Mar 2021" or a single date when it started and ended together
+    pub fn format_range(
+        start: Option<&FuzzyDate>,
+        end: Option<&FuzzyDate>,
+        is_ongoing: bool,
+    ) -> String {
+        let start = start.and_then(|start| start.format());
+        let end = end.and_then(|end| end.format());
+
+        if is_ongoing {
+            return format!("{} - {}", start.as_deref().unwrap_or(UNKNOWN), ONGOING);
+        }
+
+        match (start, end) {
+            (None, None) => EMPTY_STR.to_string(),
+            (Some(start), None) => start,
+            (Some(start), Some(end)) if start == end => start,
+            (start, Some(end)) => format!("{} - {}", start.as_deref().unwrap_or(UNKNOWN), end),
+        }
+    }
+}
+
+impl PartialEq for FuzzyDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for FuzzyDate {}
+
+impl PartialOrd for FuzzyDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FuzzyDate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
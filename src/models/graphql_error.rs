@@ -0,0 +1,69 @@
+use crate::utils::providers::ProviderError;
+use serde::Deserialize;
+use tracing::{error, info};
+
+const NOT_FOUND: &str = "Not Found.";
+
+// One entry of the errors list AniList sends next to, or instead of, the data
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GraphQLError {
+    pub message: String,
+    // The HTTP status AniList would have answered with, the response itself is often a 200
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub locations: Vec<ErrorLocation>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl GraphQLError {
+    pub fn is_not_found(&self) -> bool {
+        self.message == NOT_FOUND || self.status == Some(404)
+    }
+}
+
+// "query AnimeById($id: Int) {" is AnimeById
+pub fn operation_name(query: &str) -> &str {
+    query
+        .trim_start()
+        .strip_prefix("query")
+        .and_then(|rest| rest.split(['(', '{']).next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("anonymous query")
+}
+
+// Whether a response with these errors can still be used. Data next to errors is partial and
+// still worth showing, no data and only "Not Found." is a normal miss. Anything else is
+// AniList being down or refusing the query or its variables
+pub fn check_errors(
+    query: &str,
+    errors: &[GraphQLError],
+    has_data: bool,
+) -> Result<(), ProviderError> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let name = operation_name(query);
+    if has_data {
+        error!("{} answered with partial data: {:?}", name, errors);
+        return Ok(());
+    }
+    if errors.iter().all(GraphQLError::is_not_found) {
+        info!("{} found nothing", name);
+        return Ok(());
+    }
+
+    error!("{} was rejected by AniList: {:?}", name, errors);
+    let statuses: Vec<u16> = errors.iter().filter_map(|why| why.status).collect();
+    match statuses {
+        statuses if statuses.contains(&429) => Err(ProviderError::RateLimited),
+        statuses if statuses.iter().any(|status| *status >= 500) => Err(ProviderError::Unavailable),
+        _ => Err(ProviderError::Rejected),
+    }
+}
@@ -0,0 +1,284 @@
+use super::anilist_common::ExternalLink;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const DEFAULT_PREFIX: &str = "!";
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+// Discord fits five buttons in a row
+const MAX_LINKS: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleLanguage {
+    Romaji,
+    English,
+    Native,
+}
+
+impl TitleLanguage {
+    pub fn from_arg(arg: &str) -> Option<TitleLanguage> {
+        match arg.to_lowercase().as_str() {
+            "romaji" => Some(TitleLanguage::Romaji),
+            "english" => Some(TitleLanguage::English),
+            "native" => Some(TitleLanguage::Native),
+            _ => None,
+        }
+    }
+
+    // The footer shows the title in this language
+    pub fn secondary(&self) -> TitleLanguage {
+        match self {
+            TitleLanguage::English => TitleLanguage::Romaji,
+            _ => TitleLanguage::English,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TitleLanguage::Romaji => "romaji",
+            TitleLanguage::English => "english",
+            TitleLanguage::Native => "native",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Full,
+    Compact,
+}
+
+impl Verbosity {
+    pub fn from_arg(arg: &str) -> Option<Verbosity> {
+        match arg.to_lowercase().as_str() {
+            "full" => Some(Verbosity::Full),
+            "compact" => Some(Verbosity::Compact),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verbosity::Full => "full",
+            Verbosity::Compact => "compact",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ScoreFormat {
+    Point100,
+    Point10Decimal,
+    Point10,
+    Point5,
+    Point3,
+    Stars,
+}
+
+impl ScoreFormat {
+    pub fn from_arg(arg: &str) -> Option<ScoreFormat> {
+        match arg.to_lowercase().as_str() {
+            "point_100" | "100" => Some(ScoreFormat::Point100),
+            "point_10_decimal" | "10" => Some(ScoreFormat::Point10Decimal),
+            "point_10" => Some(ScoreFormat::Point10),
+            "point_5" | "5" => Some(ScoreFormat::Point5),
+            "point_3" | "3" => Some(ScoreFormat::Point3),
+            "stars" => Some(ScoreFormat::Stars),
+            _ => None,
+        }
+    }
+
+    // Takes AniList's 0-100 average score
+    pub fn format(&self, score: u32) -> String {
+        match self {
+            ScoreFormat::Point100 => format!("{}/100", score),
+            ScoreFormat::Point10Decimal => format!("{:.1}/10", score as f32 / 10.0),
+            ScoreFormat::Point10 => format!("{}/10", (score as f32 / 10.0).round() as u32),
+            ScoreFormat::Point5 => format!("{}/5", (score as f32 / 20.0).round() as u32),
+            // AniList's own smileys, split where AniList splits them
+            ScoreFormat::Point3 => match score {
+                0..=35 => "🙁",
+                36..=60 => "😐",
+                _ => "🙂",
+            }
+            .to_string(),
+            // Scores under 10 round to no stars, not one. An empty star keeps that apart from
+            // having no score at all
+            ScoreFormat::Stars => match (score as f32 / 20.0).round() as usize {
+                0 => "☆".to_string(),
+                stars => "⭐".repeat(stars),
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreFormat::Point100 => "point_100",
+            ScoreFormat::Point10Decimal => "point_10_decimal",
+            ScoreFormat::Point10 => "point_10",
+            ScoreFormat::Point5 => "point_5",
+            ScoreFormat::Point3 => "point_3",
+            ScoreFormat::Stars => "stars",
+        }
+    }
+}
+
+// Which streaming links end up as buttons, sites are matched case insensitively
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct LinkFilter {
+    // Shown first, in this order
+    pub preferred_sites: Vec<String>,
+    pub blocked_sites: Vec<String>,
+    // AniList marks region locked links with a language, empty allows all of them
+    pub languages: Vec<String>,
+}
+
+impl LinkFilter {
+    fn rank(&self, link: &ExternalLink) -> usize {
+        self.preferred_sites
+            .iter()
+            .position(|site| site.eq_ignore_ascii_case(&link.site))
+            .unwrap_or(self.preferred_sites.len())
+    }
+
+    fn allows(&self, link: &ExternalLink) -> bool {
+        let blocked = self
+            .blocked_sites
+            .iter()
+            .any(|site| site.eq_ignore_ascii_case(&link.site));
+        let language_allowed = match &link.language {
+            Some(language) if !self.languages.is_empty() => self
+                .languages
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(language)),
+            _ => true,
+        };
+
+        link.is_streaming() && !link.is_disabled() && !blocked && language_allowed
+    }
+
+    pub fn apply(&self, links: &[ExternalLink]) -> Vec<ExternalLink> {
+        let mut links: Vec<ExternalLink> = links
+            .iter()
+            .filter(|link| self.allows(link))
+            .cloned()
+            .collect();
+        // Stable, so anything not preferred keeps AniList's order
+        links.sort_by_key(|link| self.rank(link));
+        links.truncate(MAX_LINKS);
+        links
+    }
+}
+
+// Who shares the uses of a cooldown
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CooldownScope {
+    User,
+    Channel,
+    Guild,
+}
+
+impl CooldownScope {
+    pub fn from_arg(arg: &str) -> Option<CooldownScope> {
+        match arg.to_lowercase().as_str() {
+            "user" => Some(CooldownScope::User),
+            "channel" => Some(CooldownScope::Channel),
+            "guild" | "server" => Some(CooldownScope::Guild),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CooldownScope::User => "user",
+            CooldownScope::Channel => "channel",
+            CooldownScope::Guild => "guild",
+        }
+    }
+}
+
+// A command can be used this many times every this many seconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    pub uses: u32,
+    pub seconds: u64,
+    pub scope: CooldownScope,
+}
+
+impl Cooldown {
+    // "3/60", optionally followed by the scope, per user when there is none
+    pub fn from_args(limit: &str, scope: Option<&str>) -> Option<Cooldown> {
+        let (uses, seconds) = limit.split_once('/')?;
+        let uses = uses.trim().parse().ok().filter(|uses| *uses > 0)?;
+        let seconds = seconds.trim().parse().ok().filter(|seconds| *seconds > 0)?;
+        let scope = match scope {
+            Some(scope) => CooldownScope::from_arg(scope)?,
+            None => CooldownScope::User,
+        };
+
+        Some(Cooldown {
+            uses,
+            seconds,
+            scope,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{}/{}s per {}", self.uses, self.seconds, self.scope.name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GuildSettings {
+    pub prefix: String,
+    pub title_language: TitleLanguage,
+    // Adult entries are only ever shown in NSFW channels, this can turn them off entirely
+    pub allow_nsfw: bool,
+    pub verbosity: Verbosity,
+    pub score_format: ScoreFormat,
+    pub show_spoilers: bool,
+    pub timezone: String,
+    pub disabled_commands: HashSet<String>,
+    pub links: LinkFilter,
+    // Command name -> Cooldown, applied on top of the default buckets
+    pub cooldowns: HashMap<String, Cooldown>,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        GuildSettings {
+            prefix: DEFAULT_PREFIX.to_string(),
+            title_language: TitleLanguage::Romaji,
+            allow_nsfw: true,
+            verbosity: Verbosity::Full,
+            score_format: ScoreFormat::Point100,
+            show_spoilers: false,
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            disabled_commands: HashSet::new(),
+            links: LinkFilter::default(),
+            cooldowns: HashMap::new(),
+        }
+    }
+}
+
+impl GuildSettings {
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse::<Tz>().unwrap_or(Tz::UTC)
+    }
+
+    pub fn is_enabled(&self, command_name: &str) -> bool {
+        !self
+            .disabled_commands
+            .contains(&command_name.to_lowercase())
+    }
+
+    pub fn cooldown(&self, command_name: &str) -> Option<&Cooldown> {
+        self.cooldowns.get(&command_name.to_lowercase())
+    }
+}
@@ -1,8 +1,12 @@
+use super::graphql_error::{check_errors, GraphQLError};
+use crate::utils::providers::ProviderError;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub struct FetchResponse<T> {
     pub data: Option<FetchData<T>>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -10,3 +14,13 @@ pub struct FetchData<T> {
     #[serde(rename = "Media")]
     pub media: Option<T>,
 }
+
+impl<T> FetchResponse<T> {
+    // The media the query asked for, None when AniList couldn't find it
+    pub fn into_media(self, query: &str) -> Result<Option<T>, ProviderError> {
+        let media = self.data.and_then(|data| data.media);
+        check_errors(query, &self.errors, media.is_some())?;
+
+        Ok(media)
+    }
+}
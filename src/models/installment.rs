@@ -0,0 +1,195 @@
+// Which season and part of a show a search asks for, "attack on titan season 3 part 2" is the
+// second part of the third season of whatever "attack on titan" finds
+
+use crate::utils::japanese::is_japanese;
+
+const ORDINAL_WORDS: [&str; 10] = [
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+];
+const ROMAN_NUMERALS: [&str; 10] = ["i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x"];
+// ニ is katakana, but it looks just like 二 and gets typed for it all the time
+const KANJI_NUMERALS: [(char, u32); 11] = [
+    ('一', 1),
+    ('二', 2),
+    ('ニ', 2),
+    ('三', 3),
+    ('四', 4),
+    ('五', 5),
+    ('六', 6),
+    ('七', 7),
+    ('八', 8),
+    ('九', 9),
+    ('十', 10),
+];
+const SEASON_WORDS: [&str; 2] = ["season", "s"];
+const PART_WORDS: [&str; 3] = ["part", "pt", "cour"];
+// A number at the end of a title only means a season when it is small, Mob Psycho 100 and
+// Steins;Gate 0 keep theirs
+pub const MAX_TRAILING_SEASON: u32 = 20;
+
+// "2", "２", "2nd", "second", "II", "二" or "ニ"
+pub fn ordinal(token: &str) -> Option<u32> {
+    let token = token.to_lowercase();
+    // Full-width digits from Japanese keyboards
+    let token: String = token
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from(b'0' + (c as u32 - '０' as u32) as u8),
+            c => c,
+        })
+        .collect();
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| token.strip_suffix(suffix))
+        .filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(&token);
+
+    if let Ok(number) = digits.parse::<u32>() {
+        return Some(number);
+    }
+
+    let position = |list: &[&str]| {
+        list.iter()
+            .position(|word| *word == token)
+            .map(|position| position as u32 + 1)
+    };
+    let kanji = || match token.chars().collect::<Vec<char>>().as_slice() {
+        [numeral] => KANJI_NUMERALS
+            .iter()
+            .find(|(kanji, _)| kanji == numeral)
+            .map(|(_, number)| *number),
+        _ => None,
+    };
+
+    position(&ORDINAL_WORDS)
+        .or_else(|| position(&ROMAN_NUMERALS))
+        .or_else(kanji)
+}
+
+// A numeral standing on its own, without "season" or "part" in front. I, V and X are words too
+// often for that (Spy x Family)
+pub fn lone_ordinal(token: &str) -> Option<u32> {
+    match token.to_lowercase().as_str() {
+        "i" | "v" | "x" => None,
+        _ => ordinal(token),
+    }
+}
+
+// "2期", "第2期", "二期" or "ニ期" at the end of a token, how Japanese titles number seasons.
+// Returns whatever came before it, "進撃の巨人3期" is "進撃の巨人" and 3
+pub fn japanese_season(token: &str) -> Option<(&str, u32)> {
+    let numbered = token.strip_suffix('期')?;
+
+    numbered
+        .char_indices()
+        .map(|(index, _)| index)
+        .find_map(|index| {
+            let (before, number) = numbered.split_at(index);
+            ordinal(number.strip_prefix('第').unwrap_or(number)).map(|number| (before, number))
+        })
+}
+
+// "s2" as people type it
+pub fn short_season(token: &str) -> Option<u32> {
+    token
+        .to_lowercase()
+        .strip_prefix('s')
+        .filter(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|number| number.parse().ok())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installment {
+    // The search without the season and part
+    pub title: String,
+    pub season: u32,
+    pub part: u32,
+}
+
+impl Installment {
+    // None when the search doesn't mention a season or part at all
+    pub fn parse(search: &str) -> Option<Installment> {
+        let tokens: Vec<&str> = search.split_whitespace().collect();
+        let is = |index: usize, words: &[&str]| {
+            tokens
+                .get(index)
+                .is_some_and(|token| words.contains(&token.to_lowercase().as_str()))
+        };
+        let mut title: Vec<&str> = vec![];
+        let mut season = None;
+        let mut part = None;
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let token = tokens[index];
+            let next = tokens.get(index + 1).and_then(|next| ordinal(next));
+            // "3 part 2" is the third season's second part, not a third part
+            let trailing_keyword = |words: &[&str]| {
+                is(index + 1, words)
+                    && ordinal(token).is_some()
+                    && tokens
+                        .get(index + 2)
+                        .and_then(|after| ordinal(after))
+                        .is_none()
+            };
+
+            if is(index, &SEASON_WORDS) && next.is_some() {
+                season = next;
+                index += 2;
+            } else if is(index, &PART_WORDS) && next.is_some() {
+                part = next;
+                index += 2;
+            } else if trailing_keyword(&SEASON_WORDS) {
+                season = ordinal(token);
+                index += 2;
+            } else if trailing_keyword(&PART_WORDS) {
+                part = ordinal(token);
+                index += 2;
+            } else if let Some(number) = short_season(token) {
+                season = Some(number);
+                index += 1;
+            } else if let Some((before, number)) = japanese_season(token) {
+                if !before.is_empty() {
+                    title.push(before);
+                }
+                season = Some(number);
+                index += 1;
+            } else {
+                title.push(token);
+                index += 1;
+            }
+        }
+
+        // Japanese titles don't put a space before the number, "ワンパンマン2"
+        if let Some(last) = title.pop() {
+            let base = last.trim_end_matches(|c: char| c.is_ascii_digit());
+            let glued = base.len() < last.len() && base.chars().last().is_some_and(is_japanese);
+            match glued {
+                true => title.extend([base, &last[base.len()..]]),
+                false => title.push(last),
+            }
+        }
+
+        // "mob psycho 3", only once the rest has been taken out so "3 part 2" still works
+        if season.is_none() && title.len() > 1 {
+            let trailing = title
+                .last()
+                .and_then(|token| lone_ordinal(token))
+                .filter(|number| (2..=MAX_TRAILING_SEASON).contains(number));
+            if trailing.is_some() {
+                title.pop();
+                season = trailing;
+            }
+        }
+
+        if title.is_empty() || (season.is_none() && part.is_none()) {
+            return None;
+        }
+
+        Some(Installment {
+            title: title.join(" "),
+            season: season.unwrap_or(1).max(1),
+            part: part.unwrap_or(1).max(1),
+        })
+    }
+}
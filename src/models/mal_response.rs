@@ -27,7 +27,7 @@ struct SongInfo {
 }
 
 impl MalResponse {
-    fn format_songs_for_display(songs: Vec<SongInfo>) -> String {
+    fn format_songs_for_display(songs: Vec<SongInfo>) -> Vec<String> {
         let mut return_string: Vec<String> = vec![];
         let mut parsed_songs: HashSet<u32> = HashSet::new();
         for (index, song) in songs.iter().enumerate() {
@@ -58,18 +58,18 @@ impl MalResponse {
             write!(song_string, "{}", bold(song_name)).unwrap();
 
             // Add artist names if they exist
-            if artist_names.is_some() {
-                write!(song_string, " by {}", artist_names.unwrap()).unwrap();
+            if let Some(artist_names) = artist_names {
+                write!(song_string, " by {}", artist_names).unwrap();
             }
 
             // Add episode numbers if they exist
-            if episode_numbers.is_some() {
+            if let Some(episode_numbers) = episode_numbers {
                 // Use write
-                write!(song_string, " | {}", episode_numbers.unwrap()).unwrap();
+                write!(song_string, " | {}", episode_numbers).unwrap();
             }
             return_string.push(song_string);
         }
-        return_string.join("\n")
+        return_string
     }
 
     fn get_artist_names(song: &str) -> Option<String> {
@@ -131,7 +131,7 @@ impl MalResponse {
         Some(song[start_index + 1..end_index].parse::<u32>().unwrap())
     }
 
-    pub fn transform_endings(&self) -> String {
+    pub fn transform_endings(&self) -> Vec<String> {
         self.transform_songs(self.ending_themes.clone())
     }
 
@@ -140,19 +140,17 @@ impl MalResponse {
         linker("MyAnimeList".to_string(), link)
     }
 
-    pub fn transform_openings(&self) -> String {
+    pub fn transform_openings(&self) -> Vec<String> {
         self.transform_songs(self.opening_themes.clone())
     }
 
-    fn transform_songs(&self, songs: Option<Vec<SongInfo>>) -> String {
+    // One line per song, the embed splits them up into pages
+    fn transform_songs(&self, songs: Option<Vec<SongInfo>>) -> Vec<String> {
         match songs {
-            None => "No information available".to_string(),
-            Some(mut songs_list) => {
-                // Only use first 10 entries, because discord hates large embeds
-                songs_list.truncate(10);
-                songs_list.shrink_to_fit();
+            Some(songs_list) if !songs_list.is_empty() => {
                 Self::format_songs_for_display(songs_list)
             }
+            _ => vec!["No information available".to_string()],
         }
     }
 
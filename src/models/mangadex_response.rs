@@ -0,0 +1,142 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const MANGADEX_BASE: &str = "https://mangadex.org";
+
+// Localized strings, language code -> text
+pub type LocalizedString = HashMap<String, String>;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MangaList {
+    pub data: Vec<MangaDexManga>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MangaDexManga {
+    pub id: String,
+    pub attributes: MangaAttributes,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MangaAttributes {
+    pub title: LocalizedString,
+    #[serde(default)]
+    pub alt_titles: Vec<LocalizedString>,
+    // Site key -> id or url, "al" is AniList
+    pub links: Option<HashMap<String, String>>,
+}
+
+pub fn title_url(manga_id: &str) -> String {
+    format!("{}/title/{}", MANGADEX_BASE, manga_id)
+}
+
+impl MangaDexManga {
+    pub fn anilist_id(&self) -> Option<u32> {
+        self.attributes
+            .links
+            .as_ref()
+            .and_then(|links| links.get("al"))
+            .and_then(|id| id.parse::<u32>().ok())
+    }
+
+    pub fn titles(&self) -> impl Iterator<Item = &String> {
+        self.attributes.title.values().chain(
+            self.attributes
+                .alt_titles
+                .iter()
+                .flat_map(|alt| alt.values()),
+        )
+    }
+
+    pub fn url(&self) -> String {
+        title_url(&self.id)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChapterList {
+    pub data: Vec<Chapter>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Chapter {
+    pub id: String,
+    pub attributes: ChapterAttributes,
+    #[serde(default)]
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterAttributes {
+    pub volume: Option<String>,
+    pub chapter: Option<String>,
+    pub title: Option<String>,
+    pub translated_language: String,
+    pub publish_at: String,
+    // Officially licensed chapters only link out
+    pub external_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Relationship {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub relationship_type: String,
+    pub attributes: Option<RelationshipAttributes>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelationshipAttributes {
+    pub name: Option<String>,
+}
+
+impl Chapter {
+    pub fn group(&self) -> Option<String> {
+        self.relationships
+            .iter()
+            .find(|relationship| relationship.relationship_type == "scanlation_group")
+            .and_then(|group| group.attributes.as_ref())
+            .and_then(|attributes| attributes.name.to_owned())
+    }
+
+    pub fn published_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.attributes.publish_at).ok()
+    }
+
+    pub fn url(&self) -> String {
+        self.attributes
+            .external_url
+            .to_owned()
+            .unwrap_or_else(|| format!("{}/chapter/{}", MANGADEX_BASE, self.id))
+    }
+
+    // "Vol. 3 Ch. 21 - Title", oneshots have neither number
+    pub fn label(&self) -> String {
+        let number = [
+            self.attributes
+                .volume
+                .as_ref()
+                .map(|volume| format!("Vol. {}", volume)),
+            self.attributes
+                .chapter
+                .as_ref()
+                .map(|chapter| format!("Ch. {}", chapter)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(" ");
+        let number = match number.is_empty() {
+            true => "Oneshot".to_string(),
+            false => number,
+        };
+
+        match self.attributes.title.as_deref() {
+            Some(title) if !title.is_empty() => format!("{} - {}", number, title),
+            _ => number,
+        }
+    }
+}
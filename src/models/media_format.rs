@@ -0,0 +1,56 @@
+use super::media_type::MediaType;
+
+// AniList's MediaFormat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaFormat {
+    Tv,
+    TvShort,
+    Movie,
+    Special,
+    Ova,
+    Ona,
+    Music,
+    Manga,
+    Novel,
+    OneShot,
+}
+
+impl MediaFormat {
+    pub fn from_arg(arg: &str) -> Option<MediaFormat> {
+        match arg.to_lowercase().replace('-', "_").as_str() {
+            "tv" => Some(MediaFormat::Tv),
+            "tv_short" | "short" => Some(MediaFormat::TvShort),
+            "movie" | "film" => Some(MediaFormat::Movie),
+            "special" => Some(MediaFormat::Special),
+            "ova" => Some(MediaFormat::Ova),
+            "ona" => Some(MediaFormat::Ona),
+            "music" => Some(MediaFormat::Music),
+            "manga" => Some(MediaFormat::Manga),
+            "novel" | "ln" | "light_novel" => Some(MediaFormat::Novel),
+            "oneshot" | "one_shot" => Some(MediaFormat::OneShot),
+            _ => None,
+        }
+    }
+
+    pub fn anilist_name(&self) -> &'static str {
+        match self {
+            MediaFormat::Tv => "TV",
+            MediaFormat::TvShort => "TV_SHORT",
+            MediaFormat::Movie => "MOVIE",
+            MediaFormat::Special => "SPECIAL",
+            MediaFormat::Ova => "OVA",
+            MediaFormat::Ona => "ONA",
+            MediaFormat::Music => "MUSIC",
+            MediaFormat::Manga => "MANGA",
+            MediaFormat::Novel => "NOVEL",
+            MediaFormat::OneShot => "ONE_SHOT",
+        }
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        match self {
+            MediaFormat::Manga | MediaFormat::Novel | MediaFormat::OneShot => MediaType::Manga,
+            _ => MediaType::Anime,
+        }
+    }
+}
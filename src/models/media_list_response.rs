@@ -1,11 +1,31 @@
-use super::{media_type::MediaType, transformers::Transformers};
-use crate::utils::fuzzy::{fuzzy_matcher, fuzzy_matcher_synonyms};
+use super::{
+    graphql_error::{check_errors, GraphQLError},
+    media_type::MediaType,
+    transformers::Transformers,
+};
+use crate::utils::{
+    providers::ProviderError,
+    ranking::{rank, Candidate, Ranking},
+};
 use log::info;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+// How a single search result scored against the user input
+#[derive(Serialize, Debug, Clone)]
+pub struct CandidateScore {
+    pub id: Option<u32>,
+    pub title: String,
+    // The title or synonym that matched best
+    pub matched: String,
+    pub title_score: f32,
+    pub score: f32,
+}
 
 #[derive(Deserialize, Debug)]
 pub struct FetchResponse<T> {
     pub data: Option<Page<T>>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -16,6 +36,7 @@ pub struct Page<T> {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PageData<T> {
+    #[allow(dead_code)]
     pub page_info: Option<PageInfo>,
     #[serde(rename = "media")]
     pub media_list: Option<Vec<T>>,
@@ -23,6 +44,7 @@ pub struct PageData<T> {
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
 pub struct PageInfo {
     pub total: Option<u32>,
     pub current_page: Option<u32>,
@@ -31,119 +53,114 @@ pub struct PageInfo {
     pub per_page: Option<u32>,
 }
 
-impl<T: Transformers + std::clone::Clone> FetchResponse<T> {
-    pub fn no_results(&self) -> bool {
-        let media_list = self
-            .data
-            .as_ref()
-            .unwrap()
-            .page
-            .as_ref()
-            .unwrap()
-            .media_list
-            .as_ref()
-            .unwrap()
-            .clone();
+impl<T> FetchResponse<T> {
+    // A single page holding these, for results that didn't come from an AniList search
+    pub fn from_media(media_list: Vec<T>) -> FetchResponse<T> {
+        FetchResponse {
+            data: Some(Page {
+                page: Some(PageData {
+                    page_info: None,
+                    media_list: Some(media_list),
+                }),
+            }),
+            errors: vec![],
+        }
+    }
+
+    // Errors without any results fail the whole search, except for "Not Found." which is just
+    // an empty page
+    pub fn checked(self, query: &str) -> Result<FetchResponse<T>, ProviderError> {
+        let has_data = self.media_list().is_some();
+        check_errors(query, &self.errors, has_data)?;
 
-        media_list.is_empty()
+        Ok(self)
     }
 
-    pub fn filter(&self, media_type: MediaType) -> Vec<T> {
-        let media_list = self
-            .data
-            .as_ref()
-            .unwrap()
-            .page
+    fn media_list(&self) -> Option<&Vec<T>> {
+        self.data
             .as_ref()
-            .unwrap()
-            .media_list
-            .as_ref()
-            .unwrap()
-            .clone();
+            .and_then(|data| data.page.as_ref())
+            .and_then(|page| page.media_list.as_ref())
+    }
+
+    pub fn into_media(self) -> Vec<T> {
+        self.data
+            .and_then(|data| data.page)
+            .and_then(|page| page.media_list)
+            .unwrap_or_default()
+    }
+}
 
-        media_list
-            .iter()
+impl<T: Transformers + std::clone::Clone> FetchResponse<T> {
+    pub fn no_results(&self) -> bool {
+        self.media_list()
+            .is_none_or(|media_list| media_list.is_empty())
+    }
+
+    pub fn filter(&self, media_type: MediaType, allow_adult: bool) -> Vec<T> {
+        self.media_list()
+            .into_iter()
+            .flatten()
             .filter(|media| match media_type {
                 MediaType::Anime => media.get_type() == "anime",
                 MediaType::Manga => media.get_type() == "manga",
             })
+            .filter(|media| allow_adult || !media.get_is_adult())
             .cloned()
             .collect()
     }
 
-    pub fn fuzzy_match(&self, user_input: &str, media_type: MediaType) -> Option<T> {
-        let no_result = &self.no_results();
+    // Every result ranked against the user input, best first
+    pub fn rankings(
+        &self,
+        user_input: &str,
+        media_type: MediaType,
+        allow_adult: bool,
+    ) -> Vec<(T, Ranking)> {
+        let media_list = self.filter(media_type, allow_adult);
+        let candidates: Vec<Candidate> = media_list.iter().map(Candidate::from_media).collect();
+
+        rank(user_input, &candidates)
+            .into_iter()
+            .map(|ranking| (media_list[ranking.index].clone(), ranking))
+            .collect()
+    }
 
-        if *no_result {
-            return None;
-        }
+    pub fn candidate_scores(
+        &self,
+        user_input: &str,
+        media_type: MediaType,
+        allow_adult: bool,
+    ) -> Vec<CandidateScore> {
+        self.rankings(user_input, media_type, allow_adult)
+            .into_iter()
+            .map(|(media, ranking)| CandidateScore {
+                id: ranking.id,
+                title: media.transform_romaji_title(),
+                matched: ranking.matched,
+                title_score: ranking.title_score,
+                score: ranking.score,
+            })
+            .collect()
+    }
 
-        let name = user_input.to_lowercase();
-        let media_list = &self.filter(media_type);
-        let english_titles: Vec<String> = media_list
-            .iter()
-            .map(|media| media.get_english_title().unwrap_or_default())
-            .collect();
-        let romaji_titles: Vec<String> = media_list
-            .iter()
-            .map(|media| media.get_romaji_title().unwrap_or_default())
-            .collect();
-
-        let top_english_title_match =
-            fuzzy_matcher(&*name, english_titles, 0.5).unwrap_or_default();
-        let top_romaji_title_match = fuzzy_matcher(&*name, romaji_titles, 0.5).unwrap_or_default();
-
-        let is_english_match_available = top_english_title_match.index != usize::MAX;
-        let is_english_match_good = top_english_title_match.result.similarity >= 0.85;
-        let is_romaji_match_available = top_romaji_title_match.index != usize::MAX;
-        let is_romaji_match_good = top_romaji_title_match.result.similarity >= 0.85;
-
-        let need_to_match_synonyms = !((is_english_match_available && is_english_match_good)
-            || (is_romaji_match_available && is_romaji_match_good));
-
-        info!("English Match - {:#?}", is_english_match_available);
-        info!("Romaji Match - {:#?}", is_romaji_match_available);
-        info!("English Match Good - {:#?}", is_english_match_good);
-        info!("Romaji Match Good - {:#?}", is_romaji_match_good);
-        info!("Matching Synonyms - {:#?}", need_to_match_synonyms);
-
-        let english_score = top_english_title_match.result.similarity;
-        let romaji_score = top_romaji_title_match.result.similarity;
-        let top_match = match english_score < romaji_score {
-            true => top_romaji_title_match,
-            false => top_english_title_match,
-        };
-
-        if !need_to_match_synonyms {
-            info!(
-                "Title match says: {:#?} at Index: {:#?}",
-                media_list[top_match.index].get_english_title(),
-                top_match.index
-            );
-            Some(media_list[top_match.index].clone())
-        } else {
-            let synonyms: Vec<Vec<String>> = media_list
-                .iter()
-                .map(|media| media.get_synonyms().unwrap_or_else(|| [].to_vec()))
-                .collect();
-            let top_synonym_match = fuzzy_matcher_synonyms(&*name, synonyms).unwrap_or_default();
-            match top_synonym_match.index {
-                usize::MAX => match top_match.index {
-                    usize::MAX => match media_list.is_empty() {
-                        true => None,
-                        false => Some(media_list[0].clone()),
-                    },
-                    _ => Some(media_list[top_match.index].clone()),
-                },
-                _ => {
-                    info!(
-                        "Synonym match says: {:#?}  at Index: {:#?}",
-                        media_list[top_synonym_match.index].get_romaji_title(),
-                        top_synonym_match.index
-                    );
-                    Some(media_list[top_synonym_match.index].clone())
-                }
-            }
-        }
+    // The best ranked result. AniList only sends what its own search matched, so there is always
+    // one as long as anything came back
+    pub fn fuzzy_match(
+        &self,
+        user_input: &str,
+        media_type: MediaType,
+        allow_adult: bool,
+    ) -> Option<T> {
+        let (media, ranking) = self
+            .rankings(user_input, media_type, allow_adult)
+            .into_iter()
+            .next()?;
+
+        info!(
+            "Best match for {:?} is {:?} through {:?} at index {} with {}",
+            user_input, ranking.id, ranking.matched, ranking.index, ranking.score
+        );
+        Some(media)
     }
 }
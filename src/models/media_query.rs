@@ -0,0 +1,282 @@
+use super::{media_format::MediaFormat, media_type::MediaType};
+
+const FORMAT_FLAG: &str = "--format";
+const YEAR_FLAG: &str = "--year";
+const MAL_FLAG: &str = "--mal";
+const URL_FLAG: &str = "--url";
+// AniList has nothing older than this and years are always four digits
+const MIN_YEAR: i32 = 1900;
+const MAX_YEAR: i32 = 2100;
+
+// What the user asked for, before anything is looked up
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueryTarget {
+    AniList(u32),
+    Mal(u32),
+    // Kitsu links use either the numeric id or the slug
+    Kitsu(String),
+    AniDb(u32),
+    Search(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaQuery {
+    pub target: QueryTarget,
+    pub year: Option<i32>,
+    // Empty searches every format
+    pub formats: Vec<MediaFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    Empty,
+    MissingValue(&'static str),
+    InvalidYear,
+    InvalidMalId,
+    InvalidId,
+    InvalidFormat,
+    UnknownUrl,
+    // An anime link or format passed to a manga command or the other way around
+    WrongMediaType,
+    // More than one of a link, `--mal` and `--url`
+    ConflictingTargets,
+    // A link or id with a search next to it, one of them would be ignored
+    ExtraText,
+}
+
+impl QueryError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            QueryError::Empty => "Tell me what to look up",
+            QueryError::MissingValue(FORMAT_FLAG) => "`--format` needs a format like `movie`",
+            QueryError::MissingValue(YEAR_FLAG) => "`--year` needs a year like `2019`",
+            QueryError::MissingValue(MAL_FLAG) => "`--mal` needs a MyAnimeList id",
+            QueryError::MissingValue(_) => "`--url` needs a link",
+            QueryError::InvalidYear => "The year should look like `2019`",
+            QueryError::InvalidMalId => "MyAnimeList ids are numbers",
+            QueryError::InvalidId => "Ids look like `mal:5114`, `anidb:69` or `kitsu:cowboy-bebop`",
+            QueryError::InvalidFormat => {
+                "Formats are `tv`, `short`, `movie`, `special`, `ova`, `ona`, `music`, `manga`, `novel` and `oneshot`"
+            }
+            QueryError::UnknownUrl => "Only AniList, MyAnimeList, Kitsu and AniDB links work",
+            QueryError::WrongMediaType => "That link, id or format is for a different media type",
+            QueryError::ConflictingTargets => "Use only one link or id at a time",
+            QueryError::ExtraText => "Look up either a link or id, or a title, not both",
+        }
+    }
+}
+
+// Splits `--flag value` and `--flag=value`, flags are matched case insensitively
+fn flag(token: &str) -> Option<(&'static str, Option<&str>)> {
+    let (name, value) = match token.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (token, None),
+    };
+
+    [FORMAT_FLAG, YEAR_FLAG, MAL_FLAG, URL_FLAG]
+        .into_iter()
+        .find(|known| known.eq_ignore_ascii_case(name))
+        .map(|known| (known, value))
+}
+
+fn is_url(token: &str) -> bool {
+    // Discord users wrap links in <> to hide the preview
+    let lowercase = token.trim_start_matches('<').to_lowercase();
+    lowercase.starts_with("http://")
+        || lowercase.starts_with("https://")
+        || [
+            "anilist.co/",
+            "myanimelist.net/",
+            "kitsu.io/",
+            "kitsu.app/",
+            "anidb.net/",
+        ]
+        .iter()
+        .any(|host| lowercase.starts_with(host) || lowercase.starts_with(&format!("www.{}", host)))
+}
+
+fn media_type_from_path(segment: &str) -> Option<MediaType> {
+    match segment {
+        "anime" => Some(MediaType::Anime),
+        "manga" => Some(MediaType::Manga),
+        _ => None,
+    }
+}
+
+// Links shaped like /anime/<id>/<slug>
+fn typed_id<'a>(segments: &[&'a str], media_type: MediaType) -> Result<&'a str, QueryError> {
+    match segments {
+        [kind, id, ..] => match media_type_from_path(&kind.to_lowercase()) {
+            Some(kind) if kind != media_type => Err(QueryError::WrongMediaType),
+            Some(_) => Ok(*id),
+            None => Err(QueryError::UnknownUrl),
+        },
+        _ => Err(QueryError::UnknownUrl),
+    }
+}
+
+// Pulls `key=value` out of a query string
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value)
+}
+
+pub fn parse_url(url: &str, media_type: MediaType) -> Result<QueryTarget, QueryError> {
+    let url = url.trim().trim_start_matches('<').trim_end_matches('>');
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let location = without_scheme.split('#').next().unwrap_or_default();
+    let (location, query) = location.split_once('?').unwrap_or((location, ""));
+    let (host, path) = location.split_once('/').unwrap_or((location, ""));
+    let host = host.to_lowercase();
+    let host = host.trim_start_matches("www.");
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let number = |id: &str| id.parse::<u32>().map_err(|_| QueryError::UnknownUrl);
+
+    match host {
+        "anilist.co" => typed_id(&segments, media_type)
+            .and_then(number)
+            .map(QueryTarget::AniList),
+        "myanimelist.net" => match segments.as_slice() {
+            // Old style links like anime.php?id=5114
+            [page] if page.ends_with(".php") => {
+                let kind = page.trim_end_matches(".php").to_lowercase();
+                match media_type_from_path(&kind) {
+                    Some(kind) if kind != media_type => Err(QueryError::WrongMediaType),
+                    Some(_) => query_param(query, "id")
+                        .ok_or(QueryError::UnknownUrl)
+                        .and_then(number)
+                        .map(QueryTarget::Mal),
+                    None => Err(QueryError::UnknownUrl),
+                }
+            }
+            _ => typed_id(&segments, media_type)
+                .and_then(number)
+                .map(QueryTarget::Mal),
+        },
+        "kitsu.io" | "kitsu.app" => {
+            typed_id(&segments, media_type).map(|id| QueryTarget::Kitsu(id.to_lowercase()))
+        }
+        // AniDB only lists anime
+        "anidb.net" if media_type == MediaType::Manga => Err(QueryError::WrongMediaType),
+        "anidb.net" => match segments.as_slice() {
+            ["anime", id, ..] => number(id).map(QueryTarget::AniDb),
+            // Short links like anidb.net/a69
+            [short] if short.starts_with('a') => number(&short[1..]).map(QueryTarget::AniDb),
+            [.., page] if page.ends_with(".pl") => query_param(query, "aid")
+                .ok_or(QueryError::UnknownUrl)
+                .and_then(number)
+                .map(QueryTarget::AniDb),
+            _ => Err(QueryError::UnknownUrl),
+        },
+        _ => Err(QueryError::UnknownUrl),
+    }
+}
+
+// Ids written like `mal:5114`, Kitsu takes slugs as well
+fn parse_prefixed_id(token: &str) -> Option<Result<QueryTarget, QueryError>> {
+    let (site, id) = token.split_once(':')?;
+    let number = |error: QueryError| id.parse::<u32>().map_err(|_| error);
+
+    match site.to_lowercase().as_str() {
+        "anilist" => Some(number(QueryError::InvalidId).map(QueryTarget::AniList)),
+        "mal" | "idmal" => Some(number(QueryError::InvalidMalId).map(QueryTarget::Mal)),
+        "anidb" => Some(number(QueryError::InvalidId).map(QueryTarget::AniDb)),
+        "kitsu" if !id.is_empty() => Some(Ok(QueryTarget::Kitsu(id.to_lowercase()))),
+        "kitsu" => Some(Err(QueryError::InvalidId)),
+        _ => None,
+    }
+}
+
+fn parse_year(value: &str) -> Result<i32, QueryError> {
+    match value.parse::<i32>() {
+        Ok(year) if (MIN_YEAR..=MAX_YEAR).contains(&year) => Ok(year),
+        _ => Err(QueryError::InvalidYear),
+    }
+}
+
+fn parse_format(value: &str, media_type: MediaType) -> Result<MediaFormat, QueryError> {
+    match MediaFormat::from_arg(value) {
+        None => Err(QueryError::InvalidFormat),
+        Some(format) if format.media_type() != media_type => Err(QueryError::WrongMediaType),
+        Some(format) => Ok(format),
+    }
+}
+
+impl MediaQuery {
+    pub fn parse(input: &str, media_type: MediaType) -> Result<MediaQuery, QueryError> {
+        let mut words: Vec<&str> = Vec::new();
+        let mut explicit: Option<QueryTarget> = None;
+        let mut year = None;
+        let mut formats: Vec<MediaFormat> = Vec::new();
+        let mut tokens = input.split_whitespace();
+
+        let mut set_target = |target: QueryTarget| match explicit {
+            Some(_) => Err(QueryError::ConflictingTargets),
+            None => {
+                explicit = Some(target);
+                Ok(())
+            }
+        };
+
+        while let Some(token) = tokens.next() {
+            if is_url(token) {
+                set_target(parse_url(token, media_type)?)?;
+                continue;
+            }
+            if let Some(target) = parse_prefixed_id(token) {
+                set_target(target?)?;
+                continue;
+            }
+
+            let (name, value) = match flag(token) {
+                Some(flag) => flag,
+                None => {
+                    words.push(token);
+                    continue;
+                }
+            };
+            let value = value
+                .filter(|value| !value.is_empty())
+                .or_else(|| tokens.next())
+                .ok_or(QueryError::MissingValue(name))?;
+
+            match name {
+                FORMAT_FLAG => {
+                    for format in value.split(',') {
+                        formats.push(parse_format(format, media_type)?);
+                    }
+                }
+                YEAR_FLAG => year = Some(parse_year(value)?),
+                MAL_FLAG => set_target(QueryTarget::Mal(
+                    value.parse::<u32>().map_err(|_| QueryError::InvalidMalId)?,
+                ))?,
+                _ => set_target(parse_url(value, media_type)?)?,
+            }
+        }
+
+        let text = words.join(" ");
+        let target = match explicit {
+            Some(_) if !text.is_empty() => return Err(QueryError::ExtraText),
+            Some(target) => target,
+            None if text.is_empty() => return Err(QueryError::Empty),
+            // A bare number has always been an AniList id
+            None => match text.parse::<u32>() {
+                Ok(id) => QueryTarget::AniList(id),
+                Err(_) => QueryTarget::Search(text),
+            },
+        };
+
+        Ok(MediaQuery {
+            target,
+            year,
+            formats,
+        })
+    }
+}
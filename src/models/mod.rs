@@ -1,9 +1,19 @@
 pub mod anilist_anime;
 pub mod anilist_common;
 pub mod anilist_manga;
+pub mod anilist_quiz;
 pub mod fetcher;
+pub mod fuzzy_date;
+pub mod graphql_error;
+pub mod guild_settings;
 pub mod id_response;
+pub mod installment;
 pub mod mal_response;
+pub mod mangadex_response;
+pub mod media_format;
 pub mod media_list_response;
+pub mod media_query;
 pub mod media_type;
+pub mod relations_response;
 pub mod transformers;
+pub mod user_preferences;
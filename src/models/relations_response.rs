@@ -0,0 +1,11 @@
+use super::{anilist_common::Relations, fuzzy_date::FuzzyDate};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaRelations {
+    pub id: u32,
+    pub format: Option<String>,
+    pub start_date: Option<FuzzyDate>,
+    pub relations: Option<Relations>,
+}
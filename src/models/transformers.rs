@@ -1,29 +1,46 @@
 use html2md::parse_html;
 
-use super::anilist_common::{CoverImage, Tag};
-use crate::utils::{formatter::*, EMPTY_STR};
+use super::{
+    anilist_common::{CoverImage, Relations, Tag},
+    fuzzy_date::FuzzyDate,
+    guild_settings::{ScoreFormat, TitleLanguage},
+};
+use crate::utils::{embed_budget::EmbedBudget, formatter::*, EMPTY_STR};
 
 pub trait Transformers {
     fn get_type(&self) -> String;
+    // None for fallback entries that could not be matched to AniList
+    fn get_id(&self) -> Option<u32>;
     fn get_mal_id(&self) -> Option<u32>;
     fn get_english_title(&self) -> Option<String>;
     fn get_romaji_title(&self) -> Option<String>;
-    // TODO: Will fuzzy work with this?
     fn get_native_title(&self) -> Option<String>;
     fn get_synonyms(&self) -> Option<Vec<String>>;
     fn get_format(&self) -> Option<String>;
     fn get_status(&self) -> Option<String>;
+    fn get_start_date(&self) -> Option<FuzzyDate>;
+    fn get_end_date(&self) -> Option<FuzzyDate>;
     fn get_genres(&self) -> Vec<String>;
+    fn get_is_adult(&self) -> bool;
     fn get_source(&self) -> Option<String>;
     fn get_cover_image(&self) -> CoverImage;
     fn get_average_score(&self) -> Option<u32>;
+    fn get_popularity(&self) -> Option<u32>;
     fn get_site_url(&self) -> String;
     fn get_description(&self) -> Option<String>;
     fn get_tags(&self) -> Vec<Tag>;
+    fn get_relations(&self) -> Option<Relations>;
 
     fn transform_mal_id(&self) -> Option<String>;
 
-    // TODO: Maybe let people give preference for Primary and Secondary language
+    fn transform_title(&self, language: TitleLanguage) -> String {
+        match language {
+            TitleLanguage::Romaji => self.transform_romaji_title(),
+            TitleLanguage::English => self.transform_english_title(),
+            TitleLanguage::Native => self.transform_native_title(),
+        }
+    }
+
     fn transform_english_title(&self) -> String {
         let english_title = self.get_english_title();
         let return_title = match english_title {
@@ -71,6 +88,15 @@ pub trait Transformers {
         }
     }
 
+    // Releasing entries have no end date yet, those are left open
+    fn transform_date(&self) -> String {
+        FuzzyDate::format_range(
+            self.get_start_date().as_ref(),
+            self.get_end_date().as_ref(),
+            self.get_status().as_deref() == Some("RELEASING"),
+        )
+    }
+
     fn transform_genres(&self) -> String {
         let genres = self
             .get_genres()
@@ -120,9 +146,9 @@ pub trait Transformers {
         medium.unwrap()
     }
 
-    fn transform_score(&self) -> String {
+    fn transform_score(&self, score_format: ScoreFormat) -> String {
         match self.get_average_score() {
-            Some(score) => format!("{}/100", score),
+            Some(score) => score_format.format(score),
             None => EMPTY_STR.to_string(),
         }
     }
@@ -131,32 +157,44 @@ pub trait Transformers {
         self.get_site_url()
     }
 
-    fn transform_description_and_mal_link(&self) -> String {
-        let description = parse_html(
+    // Long synopses get cut to what the embed has left, the MAL link always survives
+    fn transform_description_and_mal_link(
+        &self,
+        show_spoilers: bool,
+        budget: &mut EmbedBudget,
+    ) -> String {
+        let description = parse_html(&mark_anilist_spoilers(
             &self
                 .get_description()
                 .unwrap_or_else(|| "<i>No Description Yet<i>".to_string()),
-        );
+        ));
+        let description = render_spoilers(&description, show_spoilers);
 
-        let url = self.transform_mal_id();
+        let mal_link = match self.transform_mal_id() {
+            Some(link) => format!("\n\n**{}**", linker("MyAnimeList".to_string(), link)),
+            None => "".to_string(),
+        };
 
-        match url {
-            Some(link) => format!(
-                "{}\n\n**{}**",
-                description,
-                linker("MyAnimeList".to_string(), link),
-            ),
-            None => description,
-        }
+        budget.description(&description, &mal_link, &self.transform_anilist())
     }
 
-    fn transform_tags(&self) -> String {
+    // Highest ranked tag that isn't a spoiler, a spoiler tag only shows up if there is nothing else
+    fn transform_tags(&self, show_spoilers: bool) -> String {
         let tags_list = self.get_tags();
+        let top_tag = |spoilers: bool| {
+            tags_list
+                .iter()
+                .filter(|tag| tag.is_spoiler() == spoilers)
+                // AniList sends tags best first, rev keeps the first of equally ranked tags
+                .rev()
+                .max_by_key(|tag| tag.rank.unwrap_or(0))
+        };
 
-        if tags_list.is_empty() {
-            EMPTY_STR.to_string()
-        } else {
-            italics(tags_list.first().unwrap().name.to_string())
+        match (top_tag(false), top_tag(true)) {
+            (Some(tag), _) => italics(tag.name.to_string()),
+            (None, Some(tag)) if show_spoilers => italics(tag.name.to_string()),
+            (None, Some(tag)) => spoiler(italics(tag.name.to_string())),
+            (None, None) => EMPTY_STR.to_string(),
         }
     }
 }
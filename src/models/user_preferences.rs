@@ -0,0 +1,42 @@
+use super::guild_settings::{GuildSettings, LinkFilter, ScoreFormat, TitleLanguage, Verbosity};
+use serde::{Deserialize, Serialize};
+
+// Anything left as None falls back to the guild settings
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UserPreferences {
+    pub title_language: Option<TitleLanguage>,
+    pub score_format: Option<ScoreFormat>,
+    pub show_spoilers: Option<bool>,
+}
+
+// What an embed should actually use, resolved as user -> guild -> default
+#[derive(Debug, Clone)]
+pub struct DisplayPreferences {
+    pub title_language: TitleLanguage,
+    pub score_format: ScoreFormat,
+    pub show_spoilers: bool,
+    pub verbosity: Verbosity,
+    // Used when an embed suggests a follow up command
+    pub prefix: String,
+    pub links: LinkFilter,
+}
+
+impl DisplayPreferences {
+    pub fn resolve(user: &UserPreferences, guild: &GuildSettings) -> DisplayPreferences {
+        DisplayPreferences {
+            title_language: user.title_language.unwrap_or(guild.title_language),
+            score_format: user.score_format.unwrap_or(guild.score_format),
+            show_spoilers: user.show_spoilers.unwrap_or(guild.show_spoilers),
+            verbosity: guild.verbosity,
+            prefix: guild.prefix.to_string(),
+            links: guild.links.clone(),
+        }
+    }
+}
+
+impl Default for DisplayPreferences {
+    fn default() -> Self {
+        DisplayPreferences::resolve(&UserPreferences::default(), &GuildSettings::default())
+    }
+}
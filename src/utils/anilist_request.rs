@@ -1,18 +1,23 @@
+use crate::utils::providers::{check_response, ProviderError};
 use reqwest::blocking::Client;
 use serde_json::Value;
+use std::env;
 
-pub fn send_request(json: Value) -> String {
+const ANILIST_URL: &str = "https://graphql.anilist.co/";
+
+// ANILIST_URL points every AniList request somewhere else, like a local mock
+pub fn anilist_url() -> String {
+    env::var("ANILIST_URL").unwrap_or_else(|_| ANILIST_URL.to_string())
+}
+
+pub fn send_request(url: &str, json: Value) -> Result<String, ProviderError> {
     let client = Client::new();
     let response = client
-        .post("https://graphql.anilist.co/")
+        .post(url)
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
         .body(json.to_string())
-        .send()
-        .unwrap()
-        .text();
-
-    let result = &response.unwrap();
+        .send();
 
-    result.to_string()
+    check_response(response)
 }
@@ -0,0 +1,27 @@
+use crate::models::anilist_common::ExternalLink;
+use serenity::{builder::CreateComponents, model::application::component::ButtonStyle};
+
+// Discord cuts button labels off at 80 characters
+const MAX_LABEL_LENGTH: usize = 80;
+
+pub fn link_buttons<'a>(
+    components: &'a mut CreateComponents,
+    links: &[ExternalLink],
+) -> &'a mut CreateComponents {
+    components.create_action_row(|row| {
+        for link in links {
+            row.create_button(|button| {
+                button
+                    .style(ButtonStyle::Link)
+                    .label(
+                        link.label()
+                            .chars()
+                            .take(MAX_LABEL_LENGTH)
+                            .collect::<String>(),
+                    )
+                    .url(&link.url)
+            });
+        }
+        row
+    })
+}
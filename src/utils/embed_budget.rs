@@ -0,0 +1,223 @@
+use super::formatter::linker;
+
+// Discord's limits, counted in characters
+pub const TITLE_LIMIT: usize = 256;
+pub const DESCRIPTION_LIMIT: usize = 4096;
+pub const FIELD_NAME_LIMIT: usize = 256;
+pub const FIELD_LIMIT: usize = 1024;
+pub const FOOTER_LIMIT: usize = 2048;
+pub const EMBED_LIMIT: usize = 6000;
+
+const ELLIPSIS: &str = "…";
+
+// Where a cut can happen, better kinds win over longer text
+#[derive(Default)]
+struct Boundaries {
+    sentence: Option<usize>,
+    word: Option<usize>,
+    any: Option<usize>,
+}
+
+// Walks the markdown and remembers the last places (in chars) at most `max` in where nothing
+// is left open, so a cut never lands inside bold, code, spoiler bars or a link
+fn safe_boundaries(chars: &[char], max: usize) -> Boundaries {
+    let mut boundaries = Boundaries::default();
+    let (mut bold, mut italics, mut strike, mut spoiler, mut underline, mut code) =
+        (false, false, false, false, false, false);
+    // 0 outside a link, 1 in its text, 2 in its url
+    let mut link = 0;
+    let mut index = 0;
+
+    while index < chars.len() && index < max {
+        let current = chars[index];
+        let next = chars.get(index + 1).copied();
+        let mut step = 1;
+
+        match (current, next) {
+            ('\\', Some(_)) if !code => step = 2,
+            ('`', _) => code = !code,
+            _ if code => {}
+            ('*', Some('*')) => {
+                bold = !bold;
+                step = 2;
+            }
+            // A star before a space opens a list item, not italics
+            ('*', next) if italics || !next.is_none_or(char::is_whitespace) => italics = !italics,
+            ('|', Some('|')) => {
+                spoiler = !spoiler;
+                step = 2;
+            }
+            ('~', Some('~')) => {
+                strike = !strike;
+                step = 2;
+            }
+            ('_', Some('_')) => {
+                underline = !underline;
+                step = 2;
+            }
+            ('[', _) if link == 0 => link = 1,
+            (']', Some('(')) if link == 1 => {
+                link = 2;
+                step = 2;
+            }
+            (']', _) if link == 1 => link = 0,
+            (')', _) if link == 2 => link = 0,
+            _ => {}
+        }
+
+        index += step;
+        let is_open = bold || italics || strike || spoiler || underline || code || link != 0;
+        if is_open || index > max {
+            continue;
+        }
+
+        let following = chars.get(index).copied();
+        let ends_word = following.is_none_or(char::is_whitespace);
+        let ends_sentence =
+            chars[index - 1] == '\n' || (ends_word && matches!(chars[index - 1], '.' | '!' | '?'));
+
+        boundaries.any = Some(index);
+        if ends_word {
+            boundaries.word = Some(index);
+        }
+        if ends_sentence {
+            boundaries.sentence = Some(index);
+        }
+    }
+
+    boundaries
+}
+
+// Cuts markdown down to `limit` characters on the nicest safe boundary, with an optional
+// "Read more" link to wherever the full text lives
+pub fn truncate_markdown(text: &str, limit: usize, read_more: Option<&str>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= limit {
+        return text.to_string();
+    }
+
+    let suffix = match read_more {
+        Some(url) => format!(
+            "{} {}",
+            ELLIPSIS,
+            linker("Read more".to_string(), url.to_string())
+        ),
+        None => ELLIPSIS.to_string(),
+    };
+    let suffix_length = suffix.chars().count();
+    if suffix_length >= limit {
+        return chars.iter().take(limit).collect();
+    }
+
+    let max = limit - suffix_length;
+    let boundaries = safe_boundaries(&chars, max);
+    // A sentence boundary is only worth it if it doesn't throw away most of the text
+    let cut = match boundaries.sentence {
+        Some(sentence) if sentence * 2 >= max => sentence,
+        _ => boundaries
+            .word
+            .or(boundaries.sentence)
+            .or(boundaries.any)
+            .unwrap_or(0),
+    };
+
+    let kept: String = chars[..cut].iter().collect();
+    format!("{}{}", kept.trim_end(), suffix)
+}
+
+// Packs lines into chunks of at most `limit` characters, lines that are too long on their own
+// get truncated instead of split
+pub fn chunk_lines(lines: &[String], limit: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        let line = truncate_markdown(line, limit, None);
+        let needed = match current.is_empty() {
+            true => line.chars().count(),
+            false => current.chars().count() + 1 + line.chars().count(),
+        };
+        if needed > limit && !current.is_empty() {
+            chunks.push(current);
+            current = String::new();
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+// Field name for the later pages of a list that got split up
+pub fn continued(name: &str, page: usize) -> String {
+    match page {
+        0 => name.to_string(),
+        _ => format!("{} (cont.)", name),
+    }
+}
+
+// Tracks what is left of the 6000 characters a whole embed gets
+pub struct EmbedBudget {
+    remaining: usize,
+}
+
+impl Default for EmbedBudget {
+    fn default() -> Self {
+        EmbedBudget {
+            remaining: EMBED_LIMIT,
+        }
+    }
+}
+
+impl EmbedBudget {
+    fn fit(&mut self, text: &str, limit: usize, read_more: Option<&str>) -> String {
+        let fitted = truncate_markdown(text, limit.min(self.remaining), read_more);
+        self.remaining = self.remaining.saturating_sub(fitted.chars().count());
+        fitted
+    }
+
+    pub fn title(&mut self, title: &str) -> String {
+        self.fit(title, TITLE_LIMIT, None)
+    }
+
+    pub fn footer(&mut self, footer: &str) -> String {
+        self.fit(footer, FOOTER_LIMIT, None)
+    }
+
+    pub fn fields<N: AsRef<str>, V: AsRef<str>>(
+        &mut self,
+        fields: Vec<(N, V, bool)>,
+    ) -> Vec<(String, String, bool)> {
+        fields
+            .into_iter()
+            .map(|(name, value, inline)| {
+                (
+                    self.fit(name.as_ref(), FIELD_NAME_LIMIT, None),
+                    self.fit(value.as_ref(), FIELD_LIMIT, None),
+                    inline,
+                )
+            })
+            .collect()
+    }
+
+    // Call this last, the description gets whatever the rest of the embed left over.
+    // `trailer` always stays at the end, even when the description itself gets cut
+    pub fn description(&mut self, description: &str, trailer: &str, read_more: &str) -> String {
+        let trailer_length = trailer.chars().count();
+        let limit = DESCRIPTION_LIMIT
+            .min(self.remaining)
+            .saturating_sub(trailer_length);
+        let fitted = format!(
+            "{}{}",
+            truncate_markdown(description, limit, Some(read_more)),
+            trailer
+        );
+        self.remaining = self.remaining.saturating_sub(fitted.chars().count());
+        fitted
+    }
+}
@@ -1,28 +1,54 @@
 use serde_json::json;
 use tracing::info;
-use wana_kana::{ConvertJapanese, IsJapaneseStr};
 
-use crate::utils::anilist_request::send_request;
+use crate::utils::{anilist_request::send_request, providers::ProviderError};
 
-pub fn fetch_by_id(query: String, id: u32) -> String {
+pub fn fetch_by_id(url: &str, query: String, id: u32) -> Result<String, ProviderError> {
     let json = json!({"query": query, "variables": {"id":id}});
-    let result: String = send_request(json);
+    let result = send_request(url, json);
 
     info!("Fetched By ID: {:#?}", id);
 
     result
 }
 
-pub fn fetch_by_name(query: String, name: String) -> String {
-    let searchable_name = match name.is_japanese() {
-        true => name.clone().to_romaji(),
-        false => name.clone(),
-    };
-    let json = json!({"query": query, "variables": {"search":searchable_name}});
-    let result: String = send_request(json);
+pub fn fetch_by_mal_id(url: &str, query: String, id_mal: u32) -> Result<String, ProviderError> {
+    let json = json!({"query": query, "variables": {"idMal":id_mal}});
+    let result = send_request(url, json);
 
-    info!("User input Name: {:#?}", name);
-    info!("Fetched By Name: {:#?}", searchable_name);
+    info!("Fetched By MAL ID: {:#?}", id_mal);
 
     result
-}
\ No newline at end of file
+}
+
+pub fn fetch_by_name(
+    url: &str,
+    query: String,
+    name: String,
+    format_in: Option<Vec<String>>,
+    year: Option<i32>,
+) -> Result<String, ProviderError> {
+    // AniList compares dates as YYYYMMDD, the bounds are exclusive
+    let start_after = year.map(|year| (year - 1) * 10000 + 9999);
+    let start_before = year.map(|year| (year + 1) * 10000);
+    let json = json!({"query": query, "variables": {
+        "search": name,
+        "format_in": format_in,
+        "startDate_greater": start_after,
+        "startDate_lesser": start_before,
+    }});
+    let result = send_request(url, json);
+
+    info!("Fetched By Name: {:#?}", name);
+
+    result
+}
+
+pub fn fetch_by_page(url: &str, query: String, page: u32) -> Result<String, ProviderError> {
+    let json = json!({"query": query, "variables": {"page":page}});
+    let result = send_request(url, json);
+
+    info!("Fetched Page: {:#?}", page);
+
+    result
+}
@@ -20,6 +20,85 @@ pub fn linker(text: String, link: String) -> String {
     format!("[{}]({})", text, link)
 }
 
+pub fn spoiler(input: String) -> String {
+    format!("||{}||", input)
+}
+
+// Private use characters survive html2md untouched, unlike AniList's ~! and !~
+const SPOILER_START: char = '\u{E000}';
+const SPOILER_END: char = '\u{E001}';
+
+// AniList wraps spoilers as ~!text!~, only matched pairs get swapped for placeholders
+pub fn mark_anilist_spoilers(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("~!") {
+        result.push_str(&rest[..start]);
+        let after_start = &rest[start + 2..];
+        match after_start.find("!~") {
+            Some(end) => {
+                result.push(SPOILER_START);
+                result.push_str(&after_start[..end]);
+                result.push(SPOILER_END);
+                rest = &after_start[end + 2..];
+            }
+            // Unterminated marker, keep the text as is
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+// Turns the placeholders from mark_anilist_spoilers into spoiler bars, or drops them
+pub fn render_spoilers(markdown: &str, show_spoilers: bool) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+
+    while let Some(start) = rest.find(SPOILER_START) {
+        result.push_str(&rest[..start]);
+        let after_start = &rest[start + SPOILER_START.len_utf8()..];
+        let (hidden, remaining) = match after_start.find(SPOILER_END) {
+            Some(end) => (
+                &after_start[..end],
+                &after_start[end + SPOILER_END.len_utf8()..],
+            ),
+            None => (after_start, ""),
+        };
+
+        if show_spoilers {
+            result.push_str(hidden);
+        } else {
+            result.push_str(&spoiler_lines(hidden));
+        }
+        rest = remaining;
+    }
+    result.push_str(rest);
+
+    result
+}
+
+// Spoiler bars don't survive paragraph breaks, so every line gets its own
+fn spoiler_lines(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let content = line.trim();
+            if content.is_empty() {
+                return line.to_string();
+            }
+            let leading = &line[..line.len() - line.trim_start().len()];
+            let trailing = &line[line.trim_end().len()..];
+            format!("{}{}{}", leading, spoiler(content.to_string()), trailing)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 pub fn remove_underscores_and_titlecase(text: &str) -> String {
     match text {
         "TV" | "OVA" | "ONA" => text.to_string(),
@@ -40,7 +40,7 @@ pub fn fuzzy_matcher(
 
     let results = corpus.search(pattern, threshold);
 
-    let response: Option<FuzzyResponse> = if results.first().is_some() {
+    let response: Option<FuzzyResponse> = if !results.is_empty() {
         let top_match = results.first();
         info!("Top Match: {:#?}", top_match);
         let top_match_index = string_list
@@ -59,27 +59,3 @@ pub fn fuzzy_matcher(
 
     response
 }
-
-pub fn fuzzy_matcher_synonyms(
-    pattern: &str,
-    synonyms_list: Vec<Vec<String>>,
-) -> Option<FuzzyResponse> {
-    info!(
-        "Matching {:#?} against Synonyms: {:#?}",
-        pattern, synonyms_list
-    );
-
-    let results: Vec<Option<FuzzyResponse>> = synonyms_list
-        .iter()
-        .map(|synonyms| fuzzy_matcher(pattern, synonyms.to_vec(), 1.0))
-        .collect();
-
-    let match_index = results.iter().position(|result| result.is_some());
-
-    info!("Synonyms Results:  {:#?}", results);
-
-    match_index.map(|top_match_index| FuzzyResponse {
-        index: top_match_index,
-        result: results[top_match_index].as_ref().unwrap().result.clone(),
-    })
-}
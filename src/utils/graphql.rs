@@ -0,0 +1,331 @@
+// AniList queries built out of shared fragments, so every query asks for the same fields the
+// same way and only spreads the parts it needs. tests/graphql_schema.rs checks them against the
+// vendored schema in schema/anilist.json
+
+pub struct Fragment {
+    pub name: &'static str,
+    // The type the fragment can be spread on
+    pub on: &'static str,
+    pub selection: &'static str,
+    // Fragments spread inside this one, sent along with it
+    pub uses: &'static [&'static Fragment],
+}
+
+pub const TITLE: Fragment = Fragment {
+    name: "Title",
+    on: "MediaTitle",
+    selection: "
+  romaji
+  english
+  native",
+    uses: &[],
+};
+
+pub const DATE: Fragment = Fragment {
+    name: "Date",
+    on: "FuzzyDate",
+    selection: "
+  year
+  month
+  day",
+    uses: &[],
+};
+
+// Everything the anime and manga embeds have in common
+pub const MEDIA_CORE: Fragment = Fragment {
+    name: "MediaCore",
+    on: "Media",
+    selection: "
+  type
+  id
+  idMal
+  title {
+    ...Title
+  }
+  synonyms
+  startDate {
+    ...Date
+  }
+  endDate {
+    ...Date
+  }
+  format
+  status
+  genres
+  isAdult
+  source
+  coverImage {
+    extraLarge
+    large
+    medium
+    color
+  }
+  averageScore
+  popularity
+  siteUrl
+  externalLinks {
+    url
+    site
+    type
+    language
+    icon
+    isDisabled
+  }
+  description
+  tags {
+    name
+    rank
+    isGeneralSpoiler
+    isMediaSpoiler
+  }",
+    uses: &[&TITLE, &DATE],
+};
+
+pub const ANIME_DETAILS: Fragment = Fragment {
+    name: "AnimeDetails",
+    on: "Media",
+    selection: "
+  season
+  seasonYear
+  episodes
+  duration
+  studios {
+    edges {
+      id
+      isMain
+    }
+    nodes {
+      id
+      name
+    }
+  }
+  trailer {
+    id
+    site
+  }",
+    uses: &[],
+};
+
+pub const MANGA_DETAILS: Fragment = Fragment {
+    name: "MangaDetails",
+    on: "Media",
+    selection: "
+  chapters
+  volumes
+  staff {
+    edges {
+      id
+      role
+    }
+    nodes {
+      id
+      name {
+        full
+      }
+      siteUrl
+    }
+  }",
+    uses: &[],
+};
+
+// Enough of every related entry for the embeds, adaptations and the watch order
+pub const RELATIONS: Fragment = Fragment {
+    name: "Relations",
+    on: "Media",
+    selection: "
+  relations {
+    edges {
+      relationType
+    }
+    nodes {
+      id
+      type
+      format
+      isAdult
+      title {
+        ...Title
+      }
+      startDate {
+        ...Date
+      }
+      season
+      seasonYear
+      studios(isMain: true) {
+        nodes {
+          name
+        }
+      }
+      siteUrl
+    }
+  }",
+    uses: &[&TITLE, &DATE],
+};
+
+// Spread on a characters(...) connection, callers pick the sort and how many
+pub const CHARACTERS: Fragment = Fragment {
+    name: "Characters",
+    on: "CharacterConnection",
+    selection: "
+  edges {
+    role
+  }
+  nodes {
+    name {
+      full
+    }
+    image {
+      large
+      medium
+    }
+  }",
+    uses: &[],
+};
+
+const PAGE_INFO: &str = "pageInfo {
+      total
+      currentPage
+      lastPage
+      hasNextPage
+      perPage
+    }";
+
+pub struct Query {
+    // Shows up in AniList's errors and our logs
+    name: &'static str,
+    variables: Vec<(&'static str, &'static str)>,
+    page: Option<&'static str>,
+    field: &'static str,
+    selection: Vec<String>,
+    fragments: Vec<&'static Fragment>,
+}
+
+impl Query {
+    // The root field with its arguments, like Media(id: $id)
+    pub fn new(name: &'static str, field: &'static str) -> Query {
+        Query {
+            name,
+            variables: vec![],
+            page: None,
+            field,
+            selection: vec![],
+            fragments: vec![],
+        }
+    }
+
+    // A single anime or manga by its AniList or MAL id
+    pub fn media_by_id(name: &'static str, field: &'static str) -> Query {
+        Query::new(name, field)
+            .variable("id", "Int")
+            .variable("idMal", "Int")
+    }
+
+    // A page of search results, filtered the way fetch_by_name fills it in
+    pub fn media_search(name: &'static str) -> Query {
+        Query::new(
+            name,
+            "media(search: $search, format_in: $format_in, \
+             startDate_greater: $startDate_greater, startDate_lesser: $startDate_lesser)",
+        )
+        .variable("page", "Int")
+        .variable("perPage", "Int")
+        .variable("search", "String")
+        .variable("format_in", "[MediaFormat]")
+        .variable("startDate_greater", "FuzzyDateInt")
+        .variable("startDate_lesser", "FuzzyDateInt")
+        .page("Page(page: $page, perPage: $perPage)")
+    }
+
+    pub fn variable(mut self, name: &'static str, graphql_type: &'static str) -> Query {
+        self.variables.push((name, graphql_type));
+        self
+    }
+
+    // Wraps the root field in a Page, which also brings its pageInfo along
+    pub fn page(mut self, page: &'static str) -> Query {
+        self.page = Some(page);
+        self
+    }
+
+    // A plain field, or one with its own selection written out
+    pub fn field(mut self, field: &str) -> Query {
+        self.selection.push(field.to_string());
+        self
+    }
+
+    pub fn fragment(mut self, fragment: &'static Fragment) -> Query {
+        self.selection.push(format!("...{}", fragment.name));
+        self.add_fragment(fragment);
+        self
+    }
+
+    // A field whose whole selection is a fragment, like characters(perPage: 5) { ...Characters }
+    pub fn nested(mut self, field: &str, fragment: &'static Fragment) -> Query {
+        self.selection
+            .push(format!("{} {{\n  ...{}\n}}", field, fragment.name));
+        self.add_fragment(fragment);
+        self
+    }
+
+    fn add_fragment(&mut self, fragment: &'static Fragment) {
+        if self
+            .fragments
+            .iter()
+            .any(|added| added.name == fragment.name)
+        {
+            return;
+        }
+        self.fragments.push(fragment);
+        for used in fragment.uses {
+            self.add_fragment(used);
+        }
+    }
+
+    pub fn build(&self) -> String {
+        let variables = self
+            .variables
+            .iter()
+            .map(|(name, graphql_type)| format!("${}: {}", name, graphql_type))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let selection = self
+            .selection
+            .iter()
+            .map(|field| indent(field, "    "))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let field = format!("{} {{\n{}\n  }}", self.field, selection);
+        let root = match self.page {
+            Some(page) => format!(
+                "{} {{\n    {}\n    {}\n  }}",
+                page,
+                PAGE_INFO,
+                indent(&field, "  ").trim_start()
+            ),
+            None => field,
+        };
+        let header = match variables.is_empty() {
+            true => format!("query {}", self.name),
+            false => format!("query {}({})", self.name, variables),
+        };
+        let fragments = self
+            .fragments
+            .iter()
+            .map(|fragment| {
+                format!(
+                    "fragment {} on {} {{{}\n}}",
+                    fragment.name, fragment.on, fragment.selection
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n");
+
+        format!("{} {{\n  {}\n}}\n\n{}\n", header, root, fragments)
+    }
+}
+
+fn indent(text: &str, by: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", by, line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,233 @@
+use crate::{
+    models::{
+        media_query::{parse_url, QueryTarget},
+        media_type::MediaType,
+    },
+    utils::{
+        store::data_dir,
+        title_index::{self, TitleIndex},
+    },
+};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    sync::{Arc, OnceLock, RwLock},
+    time::Duration,
+};
+use tracing::{error, info};
+
+const DATASET_FILE: &str = "anime-offline-database.json";
+// The dataset is released weekly
+const REFRESH_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const DATASET_URL: &str = "https://github.com/manami-project/anime-offline-database/releases/latest/download/anime-offline-database-minified.json";
+
+// The parts of the dataset the id mapper and the title index use
+#[derive(Deserialize, Debug)]
+pub(crate) struct Dataset {
+    pub data: Vec<DatasetEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DatasetEntry {
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    // TV, MOVIE, OVA, ONA, SPECIAL or UNKNOWN
+    #[serde(rename = "type")]
+    pub format: Option<String>,
+    pub anime_season: Option<AnimeSeason>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct AnimeSeason {
+    pub year: Option<i32>,
+}
+
+// The same anime on every site the dataset knows about
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappedIds {
+    pub anilist: Option<u32>,
+    pub mal: Option<u32>,
+    pub kitsu: Option<String>,
+    pub anidb: Option<u32>,
+}
+
+impl MappedIds {
+    pub(crate) fn from_sources(sources: &[String]) -> MappedIds {
+        let mut ids = MappedIds::default();
+        for source in sources {
+            match parse_url(source, MediaType::Anime) {
+                Ok(QueryTarget::AniList(id)) => ids.anilist = Some(id),
+                Ok(QueryTarget::Mal(id)) => ids.mal = Some(id),
+                Ok(QueryTarget::Kitsu(id)) => ids.kitsu = Some(id),
+                Ok(QueryTarget::AniDb(id)) => ids.anidb = Some(id),
+                // Sites we don't look up from
+                _ => {}
+            }
+        }
+        ids
+    }
+
+    fn targets(&self) -> Vec<QueryTarget> {
+        let mut targets = Vec::new();
+        targets.extend(self.anilist.map(QueryTarget::AniList));
+        targets.extend(self.mal.map(QueryTarget::Mal));
+        targets.extend(self.kitsu.clone().map(QueryTarget::Kitsu));
+        targets.extend(self.anidb.map(QueryTarget::AniDb));
+        targets
+    }
+}
+
+// Cross site ids for anime, the dataset has no manga
+#[derive(Debug, Default)]
+pub struct IdMapper {
+    entries: Vec<MappedIds>,
+    index: HashMap<QueryTarget, usize>,
+}
+
+impl IdMapper {
+    pub fn from_json(json: &str) -> Result<IdMapper, serde_json::Error> {
+        Ok(IdMapper::from_dataset(&serde_json::from_str(json)?))
+    }
+
+    pub(crate) fn from_dataset(dataset: &Dataset) -> IdMapper {
+        let mut mapper = IdMapper::default();
+
+        for entry in &dataset.data {
+            let ids = MappedIds::from_sources(&entry.sources);
+            // Entries only on sites we don't know are no use
+            if ids.anilist.is_none() {
+                continue;
+            }
+            for target in ids.targets() {
+                mapper.index.entry(target).or_insert(mapper.entries.len());
+            }
+            mapper.entries.push(ids);
+        }
+
+        mapper
+    }
+
+    pub fn load() -> IdMapper {
+        let path = dataset_path();
+        let mapper = fs::read_to_string(&path)
+            .map_err(|why| info!("No id dataset found at {:#?}: {:?}", path, why))
+            .and_then(|json| {
+                IdMapper::from_json(&json)
+                    .map_err(|why| error!("Could not parse id dataset {:#?}: {:?}", path, why))
+            })
+            .unwrap_or_default();
+
+        info!("Loaded {} id mappings", mapper.len());
+        mapper
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn ids(&self, target: &QueryTarget) -> Option<&MappedIds> {
+        self.index.get(target).map(|index| &self.entries[*index])
+    }
+
+    pub fn to_anilist(&self, target: &QueryTarget) -> Option<u32> {
+        match target {
+            QueryTarget::AniList(id) => Some(*id),
+            _ => self.ids(target).and_then(|ids| ids.anilist),
+        }
+    }
+
+    pub fn to_mal(&self, anilist_id: u32) -> Option<u32> {
+        self.ids(&QueryTarget::AniList(anilist_id))
+            .and_then(|ids| ids.mal)
+    }
+}
+
+pub(crate) fn dataset_path() -> PathBuf {
+    match env::var("ANIME_OFFLINE_DATABASE") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => data_dir().join(DATASET_FILE),
+    }
+}
+
+fn mapper_lock() -> &'static RwLock<Arc<IdMapper>> {
+    static MAPPER: OnceLock<RwLock<Arc<IdMapper>>> = OnceLock::new();
+    MAPPER.get_or_init(|| RwLock::new(Arc::new(IdMapper::load())))
+}
+
+// Loaded from disk the first time it is needed
+pub fn id_mapper() -> Arc<IdMapper> {
+    mapper_lock().read().unwrap().clone()
+}
+
+// Downloads the latest dataset, saves it and swaps it in. Blocking, returns the number of entries
+pub fn refresh() -> Result<usize, String> {
+    let json = Client::new()
+        .get(DATASET_URL)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|why| format!("Could not download the id dataset: {}", why))?;
+    let entries = use_dataset(&json)?;
+
+    let path = dataset_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(why) = fs::write(&path, json) {
+        error!("Could not save id dataset {:#?}: {:?}", path, why);
+    }
+    info!("Refreshed {} id mappings", entries);
+
+    Ok(entries)
+}
+
+// Swaps in the id mapper and title index built from this dataset instead of the one on disk,
+// returns the number of entries
+pub fn use_dataset(json: &str) -> Result<usize, String> {
+    let dataset: Dataset = serde_json::from_str(json)
+        .map_err(|why| format!("Could not parse the id dataset: {}", why))?;
+    let mapper = IdMapper::from_dataset(&dataset);
+    title_index::replace(TitleIndex::from_dataset(&dataset));
+
+    let entries = mapper.len();
+    *mapper_lock().write().unwrap() = Arc::new(mapper);
+    Ok(entries)
+}
+
+// Loads what is on disk, then downloads a new dataset every week. Right away when there was
+// nothing on disk
+pub async fn refresh_periodically() {
+    let loaded = tokio::task::spawn_blocking(|| {
+        let mapper = id_mapper();
+        title_index::title_index();
+        !mapper.is_empty()
+    })
+    .await
+    .unwrap_or(false);
+
+    let start = match loaded {
+        true => tokio::time::Instant::now() + REFRESH_INTERVAL,
+        false => tokio::time::Instant::now(),
+    };
+    let mut interval = tokio::time::interval_at(start, REFRESH_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        match tokio::task::spawn_blocking(refresh).await {
+            Ok(Err(why)) => error!("{}", why),
+            Err(why) => error!("Id dataset refresh panicked: {:?}", why),
+            Ok(Ok(_)) => {}
+        }
+    }
+}
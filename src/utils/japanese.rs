@@ -0,0 +1,52 @@
+use unicode_normalization::UnicodeNormalization;
+use wana_kana::{ConvertJapanese, IsJapaneseChar};
+
+// Full-width Latin (ＳＰＹ) and half-width katakana (ｽﾊﾟｲ) as the regular letters and kana
+// AniList has them under
+pub fn normalize_width(text: &str) -> String {
+    text.nfkc().collect()
+}
+
+pub fn is_japanese(c: char) -> bool {
+    c.is_kana() || c.is_kanji()
+}
+
+pub fn has_japanese(text: &str) -> bool {
+    text.chars().any(is_japanese)
+}
+
+// Japanese doesn't put spaces between words, "ワンパンマン2" is "ワンパンマン 2" everywhere else
+pub fn split_scripts(text: &str) -> String {
+    let mut split = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in text.chars() {
+        if let Some(previous) = previous {
+            let boundary = previous.is_alphanumeric()
+                && c.is_alphanumeric()
+                && is_japanese(previous) != is_japanese(c);
+            if boundary {
+                split.push(' ');
+            }
+        }
+        split.push(c);
+        previous = Some(c);
+    }
+
+    split
+}
+
+// What to send to AniList for a search. Japanese goes as typed so it meets the native titles,
+// kana also goes as romaji since a lot of entries only have kanji there. Kanji can't be romanized
+pub fn search_forms(search: &str) -> Vec<String> {
+    let mut forms = vec![search.to_string()];
+
+    if has_japanese(search) && !search.chars().any(|c| c.is_kanji()) {
+        let romaji = search.to_romaji();
+        if romaji != search {
+            forms.push(romaji);
+        }
+    }
+
+    forms
+}
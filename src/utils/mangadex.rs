@@ -0,0 +1,185 @@
+use crate::models::{
+    anilist_manga::Manga,
+    mangadex_response::{Chapter, ChapterList, MangaDexManga, MangaList},
+    transformers::Transformers,
+};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use std::{cmp::Reverse, env, fs, path::PathBuf};
+use tracing::{error, info};
+
+const MANGADEX_API_BASE: &str = "https://api.mangadex.org";
+// MangaDex rejects requests without one
+const USER_AGENT: &str = "annie-mai";
+const SEARCH_LIMIT: &str = "10";
+pub const CHAPTER_LIMIT: usize = 10;
+pub const DEFAULT_LANGUAGE: &str = "en";
+// Every rating but pornographic, adult entries are already gated through AniList
+const CONTENT_RATINGS: [&str; 3] = ["safe", "suggestive", "erotica"];
+
+pub trait MangaDex {
+    fn search(&self, title: &str) -> Option<Vec<MangaDexManga>>;
+    // Newest chapters first
+    fn feed(&self, manga_id: &str, language: &str) -> Option<Vec<Chapter>>;
+}
+
+pub struct HttpMangaDex {
+    client: Client,
+}
+
+impl Default for HttpMangaDex {
+    fn default() -> Self {
+        HttpMangaDex {
+            client: Client::new(),
+        }
+    }
+}
+
+impl HttpMangaDex {
+    fn get<T: DeserializeOwned>(&self, url: String, query: Vec<(&str, &str)>) -> Option<T> {
+        info!("Sent MangaDex Request to URL: {:#?}", url);
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", USER_AGENT)
+            .query(&query)
+            .send()
+            .and_then(|response| response.text());
+
+        match response {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|why| error!("Could not parse MangaDex response: {:?}", why))
+                .ok(),
+            Err(why) => {
+                error!("MangaDex request failed: {:?}", why);
+                None
+            }
+        }
+    }
+}
+
+impl MangaDex for HttpMangaDex {
+    fn search(&self, title: &str) -> Option<Vec<MangaDexManga>> {
+        let mut query = vec![("title", title), ("limit", SEARCH_LIMIT)];
+        query.extend(
+            CONTENT_RATINGS
+                .iter()
+                .map(|rating| ("contentRating[]", *rating)),
+        );
+
+        self.get::<MangaList>(format!("{}/manga", MANGADEX_API_BASE), query)
+            .map(|list| list.data)
+    }
+
+    fn feed(&self, manga_id: &str, language: &str) -> Option<Vec<Chapter>> {
+        let limit = CHAPTER_LIMIT.to_string();
+        let mut query = vec![
+            ("translatedLanguage[]", language),
+            ("order[publishAt]", "desc"),
+            ("includes[]", "scanlation_group"),
+            ("limit", &limit),
+        ];
+        query.extend(
+            CONTENT_RATINGS
+                .iter()
+                .map(|rating| ("contentRating[]", *rating)),
+        );
+
+        self.get::<ChapterList>(
+            format!("{}/manga/{}/feed", MANGADEX_API_BASE, manga_id),
+            query,
+        )
+        .map(|list| list.data)
+    }
+}
+
+// Local stand-in that reads `search.json` and `feed/<manga id>.json` from a directory
+pub struct FixtureMangaDex {
+    dir: PathBuf,
+}
+
+impl FixtureMangaDex {
+    pub fn new(dir: PathBuf) -> Self {
+        FixtureMangaDex { dir }
+    }
+
+    fn read<T: DeserializeOwned>(&self, file: PathBuf) -> Option<T> {
+        let path = self.dir.join(file);
+        let contents = fs::read_to_string(&path)
+            .map_err(|why| error!("Could not read fixture {:#?}: {:?}", path, why))
+            .ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|why| error!("Could not parse fixture {:#?}: {:?}", path, why))
+            .ok()
+    }
+}
+
+impl MangaDex for FixtureMangaDex {
+    fn search(&self, _title: &str) -> Option<Vec<MangaDexManga>> {
+        self.read::<MangaList>(PathBuf::from("search.json"))
+            .map(|list| list.data)
+    }
+
+    fn feed(&self, manga_id: &str, language: &str) -> Option<Vec<Chapter>> {
+        let mut chapters: Vec<Chapter> = self
+            .read::<ChapterList>(PathBuf::from("feed").join(format!("{}.json", manga_id)))?
+            .data
+            .into_iter()
+            .filter(|chapter| chapter.attributes.translated_language == language)
+            .collect();
+        chapters.sort_by_key(|chapter| Reverse(chapter.published_at()));
+        chapters.truncate(CHAPTER_LIMIT);
+
+        Some(chapters)
+    }
+}
+
+// MANGADEX_FIXTURES points at a fixture directory to run without hitting MangaDex
+pub fn client() -> Box<dyn MangaDex> {
+    match env::var("MANGADEX_FIXTURES") {
+        Ok(dir) => Box::new(FixtureMangaDex::new(PathBuf::from(dir))),
+        Err(_) => Box::new(HttpMangaDex::default()),
+    }
+}
+
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+// Prefers whatever MangaDex linked back to this AniList entry, then an exact title match
+pub fn find_manga(mangadex: &dyn MangaDex, manga: &Manga) -> Option<MangaDexManga> {
+    let titles: Vec<String> = [manga.get_romaji_title(), manga.get_english_title()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let normalized: Vec<String> = titles.iter().map(|title| normalize(title)).collect();
+
+    for title in titles.iter() {
+        let results = mangadex.search(title).unwrap_or_default();
+
+        if let Some(linked) = results
+            .iter()
+            .find(|result| result.anilist_id().is_some() && result.anilist_id() == manga.get_id())
+        {
+            return Some(linked.clone());
+        }
+
+        // Entries linked to some other AniList id are not this one
+        let unlinked_match = results.into_iter().find(|result| {
+            result.anilist_id().is_none()
+                && result
+                    .titles()
+                    .any(|title| normalized.contains(&normalize(title)))
+        });
+        if unlinked_match.is_some() {
+            return unlinked_match;
+        }
+    }
+
+    info!("No MangaDex entry for AniList id {:?}", manga.get_id());
+    None
+}
@@ -1,4 +1,42 @@
 pub const NOT_FOUND_ANIME: &str = "No such anime";
 pub const NOT_FOUND_MANGA: &str = "No such manga";
+pub const ADULT_CONTENT: &str =
+    "That entry is for adults only and can only be shown in NSFW channels";
+pub const UNMAPPED_ID: &str = "Could not match that id to an AniList entry, try searching by title";
+pub const PROVIDERS_UNAVAILABLE: &str =
+    "Could not reach AniList or any of the fallbacks, try again later";
+pub const OWNERS_ONLY: &str = "Only the bot owner can do that";
+pub const QUIZ_FETCH_FAILED: &str = "Could not find an anime for the quiz, try again later";
+pub const QUIZ_RUNNING: &str = "There is already a quiz going on in this channel";
+pub const CONFIG_USAGE: &str =
+    "Usage: config [prefix|title|nsfw|verbosity|score|spoilers|timezone|prefer|block|languages|enable|disable|cooldown|reset] <value>";
+pub const COOLDOWN_USAGE: &str =
+    "Usage: config cooldown <command> <uses>/<seconds> [user|channel|guild], or none for the default";
+pub const PREFS_USAGE: &str = "Usage: prefs [title|score|spoilers|reset] <value|default>";
+pub const COMMAND_DISABLED: &str = "That command is disabled on this server";
+pub const MISSING_PERMISSIONS: &str = "You need the Manage Server permission to do that";
+pub const EMPTY_LEADERBOARD: &str = "Nobody has scored any points yet";
+pub const NOT_ON_MANGADEX: &str = "Could not find that manga on MangaDex";
+pub const MANGADEX_UNAVAILABLE: &str = "Could not reach MangaDex, try again later";
+pub const NO_CHAPTERS: &str = "No chapters in that language yet";
+pub const NO_FOLLOWS: &str = "This channel is not following any manga";
+pub const NOT_FOLLOWING: &str = "This channel is not following that manga";
+
+// Other entries the search could have meant, with the id that looks each one up
+pub fn did_you_mean(entries: &[(String, u32)]) -> String {
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|(title, id)| format!("{} `anilist:{}`", title, id))
+        .collect();
+    format!("Did you mean {}?", entries.join(", "))
+}
+
+pub fn try_again_in(wait: std::time::Duration) -> String {
+    // Rounded up, "0 seconds" reads like it should have worked
+    format!(
+        "Try this again in {} seconds.",
+        wait.as_millis().div_ceil(1000).max(1)
+    )
+}
 
 // TODO: Add reaction => <:sadge:868530481208123403>
@@ -1,9 +1,20 @@
 pub mod anilist_request;
+pub mod components;
+pub mod embed_budget;
+pub mod fetchers;
 pub mod formatter;
 pub mod fuzzy;
-pub mod my_anime_list_request;
+pub mod graphql;
+pub mod id_mapper;
+pub mod japanese;
+pub mod mangadex;
 pub mod message;
+pub mod my_anime_list_request;
+pub mod paginator;
+pub mod providers;
+pub mod ranking;
 pub mod response_fetcher;
-pub mod fetchers;
+pub mod store;
+pub mod title_index;
 
 pub const EMPTY_STR: &str = "-";
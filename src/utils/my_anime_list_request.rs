@@ -1,3 +1,4 @@
+use crate::utils::providers::{check_response, ProviderError};
 use reqwest::blocking::Client;
 use std::env;
 use tracing::info;
@@ -5,10 +6,15 @@ use tracing::info;
 const MY_ANIME_LIST_BASE: &str = "https://api.myanimelist.net/v2";
 const FIELDS_TO_FETCH: [&str; 3] = ["id", "opening_themes", "ending_themes"];
 
-fn build_mal_url(mal_id: u32) -> String {
+// MAL_URL points the official API somewhere else, like a local mock
+pub fn mal_url() -> String {
+    env::var("MAL_URL").unwrap_or_else(|_| MY_ANIME_LIST_BASE.to_string())
+}
+
+fn build_mal_url(base_url: &str, mal_id: u32) -> String {
     let mal_url = format!(
         "{}/anime/{}?fields={}",
-        MY_ANIME_LIST_BASE,
+        base_url,
         mal_id,
         FIELDS_TO_FETCH.join(",")
     );
@@ -17,18 +23,17 @@ fn build_mal_url(mal_id: u32) -> String {
     mal_url
 }
 
-pub fn send_request(mal_id: u32) -> String {
-    let mal_client_id =
-        env::var("MAL_CLIENT_ID").expect("Expected a MAL Client ID in the environment");
+// Without a client id the official API is skipped and Jikan answers instead
+pub fn send_request(base_url: &str, mal_id: u32) -> Result<String, ProviderError> {
+    let mal_client_id = env::var("MAL_CLIENT_ID").map_err(|_| {
+        info!("No MAL_CLIENT_ID set, skipping the official MAL API");
+        ProviderError::Unsupported
+    })?;
     let client = Client::new();
     let response = client
-        .get(build_mal_url(mal_id))
+        .get(build_mal_url(base_url, mal_id))
         .header("X-MAL-CLIENT-ID", mal_client_id)
-        .send()
-        .unwrap()
-        .text();
-
-    let result = &response.unwrap();
+        .send();
 
-    result.to_string()
+    check_response(response)
 }
@@ -0,0 +1,118 @@
+use serde_json::{json, Value};
+use serenity::{
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    futures::StreamExt,
+    model::{
+        application::{component::ButtonStyle, interaction::InteractionResponseType},
+        channel::Message,
+        id::ChannelId,
+    },
+};
+use std::time::Duration;
+use tracing::error;
+
+const PREVIOUS_PAGE: &str = "page_previous";
+const NEXT_PAGE: &str = "page_next";
+// Buttons stop working after this long without a click
+const PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+fn page_buttons(
+    components: &mut CreateComponents,
+    page: usize,
+    total: usize,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|button| {
+            button
+                .custom_id(PREVIOUS_PAGE)
+                .style(ButtonStyle::Secondary)
+                .label("Previous")
+                .disabled(page == 0)
+        })
+        .create_button(|button| {
+            button
+                .custom_id(NEXT_PAGE)
+                .style(ButtonStyle::Secondary)
+                .label("Next")
+                .disabled(page + 1 == total)
+        })
+    })
+}
+
+// The page number goes after whatever footer the page already has
+pub fn numbered(mut embed: CreateEmbed, page: usize, total: usize) -> CreateEmbed {
+    if total < 2 {
+        return embed;
+    }
+
+    let number = format!("Page {}/{}", page + 1, total);
+    let footer = embed.0.entry("footer").or_insert_with(|| json!({}));
+    let text = match footer["text"].as_str() {
+        Some(text) if !text.is_empty() => format!("{} • {}", text, number),
+        _ => number,
+    };
+    footer["text"] = Value::from(text);
+    embed
+}
+
+// Sends the first page and lets anyone flip through the rest with buttons until it times out
+pub async fn send_pages(
+    ctx: &Context,
+    channel_id: ChannelId,
+    pages: Vec<CreateEmbed>,
+) -> serenity::Result<Message> {
+    let total = pages.len();
+    let mut message = channel_id
+        .send_message(&ctx.http, |m| {
+            if let Some(first) = pages.first() {
+                m.set_embed(numbered(first.clone(), 0, total));
+            }
+            if total > 1 {
+                m.components(|c| page_buttons(c, 0, total));
+            }
+            m
+        })
+        .await?;
+
+    if total < 2 {
+        return Ok(message);
+    }
+
+    let mut page: usize = 0;
+    let mut interactions = message
+        .await_component_interactions(ctx)
+        .timeout(PAGE_TIMEOUT)
+        .build();
+
+    while let Some(interaction) = interactions.next().await {
+        page = match interaction.data.custom_id.as_str() {
+            PREVIOUS_PAGE => page.saturating_sub(1),
+            NEXT_PAGE => (page + 1).min(total - 1),
+            _ => page,
+        };
+
+        let response = interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.set_embed(numbered(pages[page].clone(), page, total))
+                            .components(|c| page_buttons(c, page, total))
+                    })
+            })
+            .await;
+        if let Err(why) = response {
+            error!("Error turning page: {:?}", why);
+        }
+    }
+
+    // Leave the last page up without dead buttons
+    if let Err(why) = message
+        .edit(&ctx.http, |m| m.set_components(CreateComponents::default()))
+        .await
+    {
+        error!("Error removing page buttons: {:?}", why);
+    }
+
+    Ok(message)
+}
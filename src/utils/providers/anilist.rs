@@ -0,0 +1,97 @@
+use super::{MetadataProvider, ProviderError};
+use crate::{
+    models::{
+        fetcher::{AnimeConfig, Argument, FetchOptions, MangaConfig, Response},
+        mal_response::MalResponse,
+        media_query::QueryTarget,
+        media_type::MediaType as Type,
+    },
+    utils::{
+        anilist_request::anilist_url,
+        id_mapper::id_mapper,
+        japanese::search_forms,
+        my_anime_list_request::{self, mal_url},
+    },
+};
+use serde_json::Value;
+
+// The default provider, everything else only fills in when AniList can't answer
+pub struct AniList {
+    url: String,
+    // Songs come from the official MAL API
+    mal_url: String,
+}
+
+impl AniList {
+    pub fn new(url: &str, mal_url: &str) -> AniList {
+        AniList {
+            url: url.to_string(),
+            mal_url: mal_url.to_string(),
+        }
+    }
+}
+
+// ANILIST_URL and MAL_URL, or the real services
+impl Default for AniList {
+    fn default() -> AniList {
+        AniList::new(&anilist_url(), &mal_url())
+    }
+}
+
+// AniList takes its own and MAL ids, anything else has to go through the id mapper
+fn argument(target: &QueryTarget) -> Result<Argument, ProviderError> {
+    match target {
+        QueryTarget::AniList(id) => Ok(Argument::Id(*id)),
+        QueryTarget::Mal(id) => Ok(Argument::MalId(*id)),
+        target => id_mapper()
+            .to_anilist(target)
+            .map(Argument::Id)
+            .ok_or(ProviderError::Unsupported),
+    }
+}
+
+impl MetadataProvider for AniList {
+    fn name(&self) -> &'static str {
+        "AniList"
+    }
+
+    fn search(
+        &self,
+        media_type: Type,
+        search: &str,
+        options: &FetchOptions,
+    ) -> Result<Vec<Value>, ProviderError> {
+        // Results of every form in the order they came, without the ones already found
+        let mut media_list: Vec<Value> = vec![];
+        for search in search_forms(search) {
+            let response = match media_type {
+                Type::Anime => AnimeConfig::new().search::<Value>(&self.url, &search, options)?,
+                Type::Manga => MangaConfig::new().search::<Value>(&self.url, &search, options)?,
+            };
+            for media in response.into_media() {
+                if !media_list.iter().any(|found| found["id"] == media["id"]) {
+                    media_list.push(media);
+                }
+            }
+        }
+        Ok(media_list)
+    }
+
+    fn get_by_id(
+        &self,
+        media_type: Type,
+        target: &QueryTarget,
+    ) -> Result<Option<Value>, ProviderError> {
+        let argument = argument(target)?;
+        match media_type {
+            Type::Anime => AnimeConfig::new().get::<Value>(&self.url, &argument),
+            Type::Manga => MangaConfig::new().get::<Value>(&self.url, &argument),
+        }
+    }
+
+    // AniList has no songs, these come from the official MAL API
+    fn songs(&self, mal_id: u32) -> Result<MalResponse, ProviderError> {
+        let response = my_anime_list_request::send_request(&self.mal_url, mal_id)?;
+        serde_json::from_str(&response).map_err(|_| ProviderError::Malformed)
+    }
+}
@@ -0,0 +1,294 @@
+use super::{fuzzy_date, get_json, MetadataProvider, ProviderError};
+use crate::{
+    models::{
+        fetcher::FetchOptions, mal_response::MalResponse, media_query::QueryTarget,
+        media_type::MediaType as Type,
+    },
+    utils::id_mapper::id_mapper,
+};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::env;
+
+const JIKAN_URL: &str = "https://api.jikan.moe/v4";
+const SEARCH_LIMIT: &str = "10";
+
+// Unofficial MyAnimeList API, needs no credentials
+pub struct Jikan {
+    base_url: String,
+}
+
+impl Jikan {
+    pub fn new(base_url: &str) -> Jikan {
+        Jikan {
+            base_url: base_url.to_string(),
+        }
+    }
+}
+
+// JIKAN_URL points every Jikan request somewhere else, like a local mock
+impl Default for Jikan {
+    fn default() -> Jikan {
+        Jikan::new(&env::var("JIKAN_URL").unwrap_or_else(|_| JIKAN_URL.to_string()))
+    }
+}
+
+fn path(media_type: Type) -> &'static str {
+    match media_type {
+        Type::Anime => "anime",
+        Type::Manga => "manga",
+    }
+}
+
+fn mal_id(media_type: Type, target: &QueryTarget) -> Result<u32, ProviderError> {
+    match (media_type, target) {
+        (_, QueryTarget::Mal(id)) => Ok(*id),
+        // The id mapper only knows anime
+        (Type::Anime, target) => id_mapper()
+            .ids(target)
+            .and_then(|ids| ids.mal)
+            .ok_or(ProviderError::Unsupported),
+        (Type::Manga, _) => Err(ProviderError::Unsupported),
+    }
+}
+
+fn format(jikan_type: Option<&str>) -> Option<&'static str> {
+    match jikan_type?.to_lowercase().as_str() {
+        "tv" => Some("TV"),
+        "movie" => Some("MOVIE"),
+        "ova" => Some("OVA"),
+        "ona" => Some("ONA"),
+        "special" | "tv special" => Some("SPECIAL"),
+        "music" => Some("MUSIC"),
+        "manga" | "manhwa" | "manhua" | "doujinshi" => Some("MANGA"),
+        "novel" | "light novel" => Some("NOVEL"),
+        "one-shot" => Some("ONE_SHOT"),
+        _ => None,
+    }
+}
+
+fn status(jikan_status: Option<&str>) -> Option<&'static str> {
+    match jikan_status?.to_lowercase().as_str() {
+        "finished airing" | "finished" => Some("FINISHED"),
+        "currently airing" | "publishing" => Some("RELEASING"),
+        "not yet aired" | "not yet published" => Some("NOT_YET_RELEASED"),
+        "on hiatus" => Some("HIATUS"),
+        "discontinued" => Some("CANCELLED"),
+        _ => None,
+    }
+}
+
+// "24 min per ep" or "1 hr 55 min"
+fn duration(jikan_duration: Option<&str>) -> Option<u32> {
+    let words: Vec<&str> = jikan_duration?.split_whitespace().collect();
+    let minutes = words
+        .windows(2)
+        .map(|pair| match (pair[0].parse::<u32>(), pair[1]) {
+            (Ok(hours), "hr") => hours * 60,
+            (Ok(minutes), "min") => minutes,
+            _ => 0,
+        })
+        .sum();
+
+    (minutes > 0).then_some(minutes)
+}
+
+fn names(list: &Value) -> Vec<String> {
+    list.as_array()
+        .map(|list| {
+            list.iter()
+                .filter_map(|item| item["name"].as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// AniList's parallel edges and nodes lists
+fn connection(names: &[String], pair: impl Fn(usize, &str) -> (Value, Value)) -> Value {
+    let (edges, nodes): (Vec<Value>, Vec<Value>) = names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| pair(id, name))
+        .unzip();
+
+    json!({"edges": edges, "nodes": nodes})
+}
+
+// A Jikan anime or manga in AniList's shape
+pub fn to_anilist(media_type: Type, entry: &Value) -> Option<Value> {
+    let mal_id = entry["mal_id"].as_u64()? as u32;
+    let dates = match media_type {
+        Type::Anime => &entry["aired"],
+        Type::Manga => &entry["published"],
+    };
+    let genres = names(&entry["genres"]);
+    let is_adult = entry["rating"]
+        .as_str()
+        .is_some_and(|rating| rating.starts_with("Rx"))
+        || names(&entry["explicit_genres"]).contains(&"Hentai".to_string());
+    let anilist_id = match media_type {
+        Type::Anime => id_mapper().to_anilist(&QueryTarget::Mal(mal_id)),
+        Type::Manga => None,
+    };
+    let streaming: Vec<Value> = entry["streaming"]
+        .as_array()
+        .map(|links| {
+            links
+                .iter()
+                .map(|link| json!({"url": link["url"], "site": link["name"], "type": "STREAMING"}))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(json!({
+        "type": path(media_type).to_uppercase(),
+        "id": anilist_id,
+        "idMal": mal_id,
+        "title": {
+            "romaji": entry["title"],
+            "english": entry["title_english"],
+            "native": entry["title_japanese"],
+        },
+        "synonyms": entry["title_synonyms"],
+        "season": entry["season"].as_str().map(str::to_uppercase),
+        "seasonYear": entry["year"],
+        "startDate": fuzzy_date(dates["from"].as_str()),
+        "endDate": fuzzy_date(dates["to"].as_str()),
+        "format": format(entry["type"].as_str()),
+        "status": status(entry["status"].as_str()),
+        "episodes": entry["episodes"],
+        "duration": duration(entry["duration"].as_str()),
+        "chapters": entry["chapters"],
+        "volumes": entry["volumes"],
+        "genres": genres,
+        "isAdult": is_adult,
+        "source": entry["source"]
+            .as_str()
+            .map(|source| source.to_uppercase().replace(' ', "_")),
+        "coverImage": {
+            "extraLarge": entry.pointer("/images/jpg/large_image_url"),
+            "large": entry.pointer("/images/jpg/image_url"),
+            "medium": entry.pointer("/images/jpg/small_image_url"),
+        },
+        "averageScore": entry["score"].as_f64().map(|score| (score * 10.0).round() as u32),
+        // Members is what MAL sorts popularity by
+        "popularity": entry["members"],
+        "studios": connection(&names(&entry["studios"]), |id, name| {
+            (json!({"id": id, "isMain": true}), json!({"id": id, "name": name}))
+        }),
+        // Jikan lists authors without their role
+        "staff": connection(&names(&entry["authors"]), |id, name| {
+            (
+                json!({"id": id, "role": "Story & Art"}),
+                json!({"id": id, "name": {"full": name}, "siteUrl": ""}),
+            )
+        }),
+        "siteUrl": entry["url"],
+        "externalLinks": streaming,
+        "trailer": entry
+            .pointer("/trailer/youtube_id")
+            .and_then(Value::as_str)
+            .map(|id| json!({"id": id, "site": "youtube"})),
+        "description": entry["synopsis"],
+        "tags": [],
+    }))
+}
+
+// The official API numbers songs like "#1: ...", Jikan sometimes leaves the # out
+fn song_text(theme: &str) -> String {
+    let numbered = theme
+        .split_once(':')
+        .is_some_and(|(number, _)| number.parse::<u32>().is_ok());
+
+    match numbered {
+        true => format!("#{}", theme),
+        false => theme.to_string(),
+    }
+}
+
+fn songs(mal_id: u32, themes: &Value) -> Vec<Value> {
+    themes
+        .as_array()
+        .map(|themes| {
+            themes
+                .iter()
+                .filter_map(Value::as_str)
+                .enumerate()
+                .map(|(id, theme)| json!({"id": id, "anime_id": mal_id, "text": song_text(theme)}))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// A Jikan anime and its themes in the official MAL API's shape, only what the songs need
+pub fn to_mal_songs(entry: &Value, themes: &Value) -> Option<Value> {
+    let mal_id = entry["mal_id"].as_u64()? as u32;
+
+    Some(json!({
+        "id": mal_id,
+        "title": entry["title"],
+        "main_picture": {
+            "medium": entry.pointer("/images/jpg/image_url"),
+            "large": entry.pointer("/images/jpg/large_image_url"),
+        },
+        "opening_themes": songs(mal_id, &themes["openings"]),
+        "ending_themes": songs(mal_id, &themes["endings"]),
+    }))
+}
+
+impl MetadataProvider for Jikan {
+    fn name(&self) -> &'static str {
+        "Jikan"
+    }
+
+    fn search(
+        &self,
+        media_type: Type,
+        search: &str,
+        options: &FetchOptions,
+    ) -> Result<Vec<Value>, ProviderError> {
+        let mut params = vec![("q", search), ("limit", SEARCH_LIMIT)];
+        if !options.allow_adult {
+            params.push(("sfw", "true"));
+        }
+        let url =
+            Url::parse_with_params(&format!("{}/{}", self.base_url, path(media_type)), params)
+                .map_err(|_| ProviderError::Unsupported)?;
+
+        let response = get_json(url.as_str())?;
+        let results = response["data"]
+            .as_array()
+            .ok_or(ProviderError::Malformed)?;
+        Ok(results
+            .iter()
+            .filter_map(|entry| to_anilist(media_type, entry))
+            .collect())
+    }
+
+    fn get_by_id(
+        &self,
+        media_type: Type,
+        target: &QueryTarget,
+    ) -> Result<Option<Value>, ProviderError> {
+        let id = mal_id(media_type, target)?;
+        let response = get_json(&format!(
+            "{}/{}/{}/full",
+            self.base_url,
+            path(media_type),
+            id
+        ))?;
+
+        Ok(to_anilist(media_type, &response["data"]))
+    }
+
+    fn songs(&self, mal_id: u32) -> Result<MalResponse, ProviderError> {
+        // The songs have their own endpoint, the title and cover come from the anime
+        let anime = get_json(&format!("{}/anime/{}", self.base_url, mal_id))?;
+        let themes = get_json(&format!("{}/anime/{}/themes", self.base_url, mal_id))?;
+        let songs =
+            to_mal_songs(&anime["data"], &themes["data"]).ok_or(ProviderError::Malformed)?;
+
+        serde_json::from_value(songs).map_err(|_| ProviderError::Malformed)
+    }
+}
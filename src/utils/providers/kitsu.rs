@@ -0,0 +1,198 @@
+use super::{fuzzy_date, get_json, MetadataProvider, ProviderError};
+use crate::{
+    models::{
+        fetcher::FetchOptions, mal_response::MalResponse, media_query::QueryTarget,
+        media_type::MediaType as Type,
+    },
+    utils::id_mapper::id_mapper,
+};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::env;
+
+const KITSU_URL: &str = "https://kitsu.app/api/edge";
+const KITSU_SITE: &str = "https://kitsu.app";
+const SEARCH_LIMIT: &str = "10";
+
+pub struct Kitsu {
+    base_url: String,
+}
+
+fn path(media_type: Type) -> &'static str {
+    match media_type {
+        Type::Anime => "anime",
+        Type::Manga => "manga",
+    }
+}
+
+// Kitsu ids or slugs, other sites go through the id mapper which only knows anime
+fn kitsu_id(media_type: Type, target: &QueryTarget) -> Result<String, ProviderError> {
+    match (media_type, target) {
+        (_, QueryTarget::Kitsu(id)) => Ok(id.to_string()),
+        (Type::Anime, target) => id_mapper()
+            .ids(target)
+            .and_then(|ids| ids.kitsu.clone())
+            .ok_or(ProviderError::Unsupported),
+        (Type::Manga, _) => Err(ProviderError::Unsupported),
+    }
+}
+
+fn format(subtype: Option<&str>) -> Option<&'static str> {
+    match subtype?.to_lowercase().as_str() {
+        "tv" => Some("TV"),
+        "movie" => Some("MOVIE"),
+        "ova" => Some("OVA"),
+        "ona" => Some("ONA"),
+        "special" => Some("SPECIAL"),
+        "music" => Some("MUSIC"),
+        "manga" | "manhwa" | "manhua" | "oel" | "doujin" => Some("MANGA"),
+        "novel" => Some("NOVEL"),
+        "oneshot" => Some("ONE_SHOT"),
+        _ => None,
+    }
+}
+
+fn status(kitsu_status: Option<&str>) -> Option<&'static str> {
+    match kitsu_status? {
+        "finished" => Some("FINISHED"),
+        "current" => Some("RELEASING"),
+        "tba" | "unreleased" | "upcoming" => Some("NOT_YET_RELEASED"),
+        _ => None,
+    }
+}
+
+// A Kitsu anime or manga in AniList's shape
+pub fn to_anilist(media_type: Type, entry: &Value) -> Option<Value> {
+    let kitsu_id = entry["id"].as_str()?;
+    let attributes = &entry["attributes"];
+    let ids = match media_type {
+        Type::Anime => id_mapper()
+            .ids(&QueryTarget::Kitsu(kitsu_id.to_string()))
+            .cloned(),
+        Type::Manga => None,
+    }
+    .unwrap_or_default();
+    let slug = attributes["slug"].as_str().unwrap_or(kitsu_id);
+
+    Some(json!({
+        "type": path(media_type).to_uppercase(),
+        "id": ids.anilist,
+        "idMal": ids.mal,
+        "title": {
+            "romaji": attributes.pointer("/titles/en_jp").or(attributes.get("canonicalTitle")),
+            "english": attributes.pointer("/titles/en"),
+            "native": attributes.pointer("/titles/ja_jp"),
+        },
+        "synonyms": attributes["abbreviatedTitles"],
+        "startDate": fuzzy_date(attributes["startDate"].as_str()),
+        "endDate": fuzzy_date(attributes["endDate"].as_str()),
+        "format": format(attributes["subtype"].as_str()),
+        "status": status(attributes["status"].as_str()),
+        "episodes": attributes["episodeCount"],
+        "duration": attributes["episodeLength"],
+        "chapters": attributes["chapterCount"],
+        "volumes": attributes["volumeCount"],
+        // Genres are a separate request on Kitsu, not worth it for a fallback
+        "genres": [],
+        "isAdult": attributes["nsfw"].as_bool().unwrap_or(false),
+        "coverImage": {
+            "extraLarge": attributes.pointer("/posterImage/original"),
+            "large": attributes.pointer("/posterImage/large"),
+            "medium": attributes.pointer("/posterImage/medium"),
+        },
+        // "82.35" out of 100
+        "averageScore": attributes["averageRating"]
+            .as_str()
+            .and_then(|rating| rating.parse::<f64>().ok())
+            .map(|rating| rating.round() as u32),
+        "popularity": attributes["userCount"],
+        "siteUrl": format!("{}/{}/{}", KITSU_SITE, path(media_type), slug),
+        "trailer": attributes["youtubeVideoId"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(|id| json!({"id": id, "site": "youtube"})),
+        "description": attributes["synopsis"],
+        "tags": [],
+    }))
+}
+
+impl Kitsu {
+    pub fn new(base_url: &str) -> Kitsu {
+        Kitsu {
+            base_url: base_url.to_string(),
+        }
+    }
+
+    // The entry behind a slug, Kitsu's own links use slugs rather than ids
+    fn find_by_slug(&self, media_type: Type, slug: &str) -> Result<Value, ProviderError> {
+        let url = Url::parse_with_params(
+            &format!("{}/{}", self.base_url, path(media_type)),
+            [("filter[slug]", slug)],
+        )
+        .map_err(|_| ProviderError::Unsupported)?;
+
+        Ok(get_json(url.as_str())?["data"][0].clone())
+    }
+}
+
+// KITSU_URL points every Kitsu request somewhere else, like a local mock
+impl Default for Kitsu {
+    fn default() -> Kitsu {
+        Kitsu::new(&env::var("KITSU_URL").unwrap_or_else(|_| KITSU_URL.to_string()))
+    }
+}
+
+impl MetadataProvider for Kitsu {
+    fn name(&self) -> &'static str {
+        "Kitsu"
+    }
+
+    fn search(
+        &self,
+        media_type: Type,
+        search: &str,
+        _options: &FetchOptions,
+    ) -> Result<Vec<Value>, ProviderError> {
+        // Kitsu leaves adult entries out of anonymous requests on its own
+        let url = Url::parse_with_params(
+            &format!("{}/{}", self.base_url, path(media_type)),
+            [("filter[text]", search), ("page[limit]", SEARCH_LIMIT)],
+        )
+        .map_err(|_| ProviderError::Unsupported)?;
+
+        let response = get_json(url.as_str())?;
+        let results = response["data"]
+            .as_array()
+            .ok_or(ProviderError::Malformed)?;
+        Ok(results
+            .iter()
+            .filter_map(|entry| to_anilist(media_type, entry))
+            .collect())
+    }
+
+    fn get_by_id(
+        &self,
+        media_type: Type,
+        target: &QueryTarget,
+    ) -> Result<Option<Value>, ProviderError> {
+        let id = kitsu_id(media_type, target)?;
+        let entry = match id.parse::<u32>() {
+            Ok(id) => {
+                get_json(&format!("{}/{}/{}", self.base_url, path(media_type), id))?["data"].clone()
+            }
+            Err(_) => self.find_by_slug(media_type, &id)?,
+        };
+
+        Ok(to_anilist(media_type, &entry))
+    }
+
+    fn songs(&self, _mal_id: u32) -> Result<MalResponse, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    fn resolve_slug(&self, media_type: Type, slug: &str) -> Result<Option<String>, ProviderError> {
+        Ok(self.find_by_slug(media_type, slug)?["id"]
+            .as_str()
+            .map(str::to_string))
+    }
+}
@@ -0,0 +1,158 @@
+pub mod anilist;
+pub mod jikan;
+pub mod kitsu;
+
+use crate::models::{
+    fetcher::FetchOptions, mal_response::MalResponse, media_query::QueryTarget,
+    media_type::MediaType as Type,
+};
+use reqwest::{blocking::Response, StatusCode};
+use serde_json::Value;
+use std::env;
+use tracing::{error, info};
+
+const DEFAULT_PROVIDERS: [&str; 3] = ["anilist", "jikan", "kitsu"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderError {
+    // Down, timing out or answering with a server error
+    Unavailable,
+    RateLimited,
+    // The provider can't answer this, like a Kitsu id asked of Jikan
+    Unsupported,
+    // Answered with something we couldn't read
+    Malformed,
+    // Understood the request and refused it, like AniList's errors for bad variables
+    Rejected,
+}
+
+// Every provider hands back media in AniList's shape, so they all deserialize into the same
+// Anime and Manga types. Fields a provider doesn't have are left out
+pub trait MetadataProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn search(
+        &self,
+        media_type: Type,
+        search: &str,
+        options: &FetchOptions,
+    ) -> Result<Vec<Value>, ProviderError>;
+
+    fn get_by_id(
+        &self,
+        media_type: Type,
+        target: &QueryTarget,
+    ) -> Result<Option<Value>, ProviderError>;
+
+    fn songs(&self, mal_id: u32) -> Result<MalResponse, ProviderError>;
+
+    // The numeric id behind a slug, which is all the id mapper knows. None when there is no such
+    // slug, Unsupported for providers without slugs
+    fn resolve_slug(
+        &self,
+        _media_type: Type,
+        _slug: &str,
+    ) -> Result<Option<String>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+fn provider(name: &str) -> Option<Box<dyn MetadataProvider>> {
+    match name.trim().to_lowercase().as_str() {
+        "anilist" => Some(Box::new(anilist::AniList::default())),
+        "jikan" => Some(Box::new(jikan::Jikan::default())),
+        "kitsu" => Some(Box::new(kitsu::Kitsu::default())),
+        _ => None,
+    }
+}
+
+// Tried in order, METADATA_PROVIDERS=anilist,kitsu changes which ones and in what order
+pub fn providers() -> Vec<Box<dyn MetadataProvider>> {
+    let names = env::var("METADATA_PROVIDERS")
+        .map(|names| {
+            names
+                .split(',')
+                .map(str::to_string)
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_else(|_| DEFAULT_PROVIDERS.map(str::to_string).to_vec());
+
+    let providers: Vec<Box<dyn MetadataProvider>> =
+        names.iter().filter_map(|name| provider(name)).collect();
+    match providers.is_empty() {
+        true => vec![Box::new(anilist::AniList::default())],
+        false => providers,
+    }
+}
+
+// Moves on to the next provider whenever one fails, the first answer wins even if it is empty.
+// Unsupported only comes back when nobody could answer at all
+pub fn with_failover<R>(
+    providers: &[Box<dyn MetadataProvider>],
+    call: impl Fn(&dyn MetadataProvider) -> Result<R, ProviderError>,
+) -> Result<R, ProviderError> {
+    let mut last_error = ProviderError::Unsupported;
+
+    for provider in providers {
+        match call(provider.as_ref()) {
+            Ok(result) => return Ok(result),
+            Err(why) => {
+                if why != ProviderError::Unsupported {
+                    error!(
+                        "{} failed with {:?}, trying the next provider",
+                        provider.name(),
+                        why
+                    );
+                    last_error = why;
+                } else {
+                    info!(
+                        "{} can't answer this, trying the next provider",
+                        provider.name()
+                    );
+                }
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+pub fn check_response(response: reqwest::Result<Response>) -> Result<String, ProviderError> {
+    let response = response.map_err(|why| {
+        error!("Request failed: {:?}", why);
+        ProviderError::Unavailable
+    })?;
+
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => Err(ProviderError::RateLimited),
+        status if status.is_server_error() => Err(ProviderError::Unavailable),
+        // AniList answers bad ids with a 404 and an errors payload, that's still an answer
+        _ => response.text().map_err(|_| ProviderError::Unavailable),
+    }
+}
+
+pub fn get_json(url: &str) -> Result<Value, ProviderError> {
+    info!("Provider request: {}", url);
+    let response = reqwest::blocking::Client::new()
+        .get(url)
+        .header("Accept", "application/json")
+        .send();
+    let body = check_response(response)?;
+
+    serde_json::from_str(&body).map_err(|why| {
+        error!("Could not parse response from {}: {:?}", url, why);
+        ProviderError::Malformed
+    })
+}
+
+// "2019-04-06" or "2019-04-06T00:00:00+00:00" into an AniList FuzzyDate
+pub fn fuzzy_date(date: Option<&str>) -> Value {
+    let date: String = date.unwrap_or_default().chars().take(10).collect();
+    let mut parts = date.split('-').map(|part| part.parse::<u32>().ok());
+
+    serde_json::json!({
+        "year": parts.next().flatten(),
+        "month": parts.next().flatten(),
+        "day": parts.next().flatten(),
+    })
+}
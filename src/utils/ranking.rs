@@ -0,0 +1,369 @@
+use crate::models::{
+    anilist_common::Title,
+    installment::{lone_ordinal, ordinal, short_season, MAX_TRAILING_SEASON},
+    transformers::Transformers,
+};
+use crate::utils::japanese::{has_japanese, normalize_width, split_scripts};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use wana_kana::ConvertJapanese;
+
+// How much each signal counts towards a title's score, exact matches always score 1
+const TRIGRAM_WEIGHT: f32 = 0.5;
+const TOKEN_WEIGHT: f32 = 0.3;
+const PREFIX_WEIGHT: f32 = 0.2;
+// "aot" for Attack on Titan
+const ACRONYM_SCORE: f32 = 0.9;
+// Asking for season 3 or R2 and getting a title without it
+const SEQUEL_MISMATCH: f32 = 0.8;
+// Synonyms are full of other languages and nicknames, the main titles win ties
+const SYNONYM_DISCOUNT: f32 = 0.95;
+// Tie-breakers, small enough to only matter between titles that score about the same
+const RANK_WEIGHT: f32 = 0.02;
+const POPULARITY_WEIGHT: f32 = 0.05;
+const FORMAT_WEIGHT: f32 = 0.01;
+// What people usually mean when they don't say
+const MAIN_FORMATS: [&str; 4] = ["TV", "MOVIE", "MANGA", "NOVEL"];
+// Words in the query that ask for a format, "cowboy bebop movie"
+const FORMAT_WORDS: [(&str, &str); 6] = [
+    ("movie", "MOVIE"),
+    ("film", "MOVIE"),
+    ("ova", "OVA"),
+    ("ona", "ONA"),
+    ("special", "SPECIAL"),
+    ("novel", "NOVEL"),
+];
+
+// A title broken down for comparing, see normalize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalized {
+    pub tokens: Vec<String>,
+    // The tokens without spaces, so "K-On!" and "kon" still meet
+    pub compact: String,
+}
+
+fn season(number: u32) -> Option<String> {
+    (number > 1).then(|| format!("s{}", number))
+}
+
+// "season 2", "2nd season", "second season", "s2", a lone "ii" and a number at the end all
+// become "s2". The first season is dropped since titles never mention it
+fn season_tokens(tokens: Vec<String>) -> Vec<String> {
+    let mut normalized = vec![];
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index].as_str();
+        let next = tokens.get(index + 1).map(String::as_str);
+        // A lone "ii", "s2" as people type it, or "Shingeki no Kyojin 3"
+        let roman = token
+            .chars()
+            .all(|c| matches!(c, 'i' | 'v' | 'x'))
+            .then(|| lone_ordinal(token))
+            .flatten();
+        let trailing = (index > 0 && next.is_none())
+            .then(|| lone_ordinal(token))
+            .flatten()
+            .filter(|number| (2..=MAX_TRAILING_SEASON).contains(number));
+        let numbered = roman.or_else(|| short_season(token)).or(trailing);
+
+        match (token, next) {
+            ("season", Some(next)) if ordinal(next).is_some() => {
+                normalized.extend(ordinal(next).and_then(season));
+                index += 2;
+            }
+            (token, Some("season")) if ordinal(token).is_some() => {
+                normalized.extend(ordinal(token).and_then(season));
+                index += 2;
+            }
+            _ => {
+                match numbered {
+                    Some(number) => normalized.extend(season(number)),
+                    None => normalized.push(token.to_string()),
+                }
+                index += 1;
+            }
+        }
+    }
+
+    normalized
+}
+
+// Romanizations disagree on long vowels, Shingeki no Kyojin vs Kyoujin, Ōkami vs Ookami
+fn fold_long_vowels(token: &str) -> String {
+    token
+        .replace("ou", "o")
+        .replace("oo", "o")
+        .replace("uu", "u")
+}
+
+// AniList tells remakes apart with "(2011)" or "(TV)", nobody types those
+fn without_qualifiers(title: &str) -> String {
+    let mut depth = 0;
+    title
+        .chars()
+        .filter(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    return false;
+                }
+                _ => {}
+            }
+            depth == 0
+        })
+        .collect()
+}
+
+fn normalize_with(title: &str, romanize: bool) -> Normalized {
+    let title = split_scripts(&without_qualifiers(&normalize_width(title))).replace('×', " x ");
+    // Dakuten are combining marks too, kana only lose them once they are romaji
+    let title: String = match romanize {
+        true => title
+            .to_romaji()
+            .nfd()
+            .filter(|c| !is_combining_mark(*c))
+            .collect(),
+        false => title,
+    };
+    let words: String = title
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’'))
+        .map(|c| match c {
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect::<String>()
+        .to_lowercase();
+
+    let tokens: Vec<String> = season_tokens(words.split_whitespace().map(str::to_string).collect())
+        .iter()
+        .map(|token| fold_long_vowels(token))
+        .collect();
+    let compact = tokens.concat();
+
+    Normalized { tokens, compact }
+}
+
+// Lowercase words with kana turned into romaji, accents, macrons, qualifiers and punctuation gone
+// and season numbers spelled one way
+pub fn normalize(title: &str) -> Normalized {
+    normalize_with(title, true)
+}
+
+// The same but left in the script it was written in, kanji have no romaji to meet in
+pub fn normalize_native(title: &str) -> Normalized {
+    normalize_with(title, false)
+}
+
+// Character trigrams with how often they come up, padded so the start of a title counts more
+pub fn trigrams(text: &str) -> HashMap<[char; 3], usize> {
+    let padded: Vec<char> = format!("  {} ", text).chars().collect();
+    let mut counts = HashMap::new();
+    for window in padded.windows(3) {
+        *counts.entry([window[0], window[1], window[2]]).or_insert(0) += 1;
+    }
+    counts
+}
+
+// Dice coefficient over character trigrams, 1 for the same text
+fn trigram_similarity(query: &str, title: &str) -> f32 {
+    let query = trigrams(query);
+    let title = trigrams(title);
+    let shared: usize = query
+        .iter()
+        .map(|(trigram, count)| (*count).min(*title.get(trigram).unwrap_or(&0)))
+        .sum();
+    let total: usize = query.values().sum::<usize>() + title.values().sum::<usize>();
+
+    match total {
+        0 => 0.0,
+        total => 2.0 * shared as f32 / total as f32,
+    }
+}
+
+// How many query words the title has, the last one may still be half typed
+fn token_similarity(query: &[String], title: &[String]) -> f32 {
+    if query.is_empty() || title.is_empty() {
+        return 0.0;
+    }
+
+    let last = query.len() - 1;
+    let matched = query
+        .iter()
+        .enumerate()
+        .filter(|(index, token)| {
+            title.contains(token)
+                || (*index == last && title.iter().any(|word| word.starts_with(token.as_str())))
+        })
+        .count();
+
+    matched as f32 / query.len() as f32
+}
+
+fn is_acronym(query: &Normalized, title: &Normalized) -> bool {
+    let initials: String = title
+        .tokens
+        .iter()
+        .filter_map(|token| token.chars().next())
+        .collect();
+
+    query.tokens.len() == 1 && title.tokens.len() > 1 && query.compact == initials
+}
+
+// How close one title is to the query, from 0 to 1
+pub fn score_title(query: &Normalized, title: &Normalized) -> f32 {
+    if query.compact.is_empty() || title.compact.is_empty() {
+        return 0.0;
+    }
+    if query.compact == title.compact {
+        return 1.0;
+    }
+
+    let prefix = match title.compact.starts_with(&query.compact) {
+        true => 1.0,
+        false => 0.0,
+    };
+    let blended = TRIGRAM_WEIGHT * trigram_similarity(&query.compact, &title.compact)
+        + TOKEN_WEIGHT * token_similarity(&query.tokens, &title.tokens)
+        + PREFIX_WEIGHT * prefix;
+
+    let blended = match is_acronym(query, title) {
+        true => blended.max(ACRONYM_SCORE),
+        false => blended,
+    };
+    let missing_sequel = query
+        .tokens
+        .iter()
+        .filter(|token| token.chars().any(|c| c.is_ascii_digit()))
+        .any(|token| !title.tokens.contains(token));
+
+    match missing_sequel {
+        true => blended * SEQUEL_MISMATCH,
+        false => blended,
+    }
+}
+
+// Everything the ranking looks at, in AniList's shape
+#[derive(Deserialize, Debug, Clone)]
+pub struct Candidate {
+    // None for fallback entries without an AniList id
+    pub id: Option<u32>,
+    pub title: Title,
+    pub synonyms: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub popularity: Option<u32>,
+}
+
+impl Candidate {
+    pub fn from_media<T: Transformers>(media: &T) -> Candidate {
+        Candidate {
+            id: media.get_id(),
+            title: Title {
+                romaji: media.get_romaji_title(),
+                english: media.get_english_title(),
+                native: media.get_native_title(),
+            },
+            synonyms: media.get_synonyms(),
+            format: media.get_format(),
+            popularity: media.get_popularity(),
+        }
+    }
+
+    // Every title with how much it counts
+    fn titles(&self) -> Vec<(&str, f32)> {
+        let main = [&self.title.english, &self.title.romaji, &self.title.native]
+            .into_iter()
+            .flatten()
+            .map(|title| (title.as_str(), 1.0));
+        let synonyms = self
+            .synonyms
+            .iter()
+            .flatten()
+            .map(|synonym| (synonym.as_str(), SYNONYM_DISCOUNT));
+
+        main.chain(synonyms).collect()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Ranking {
+    // Where it was in the ranked list, which is AniList's own search order
+    pub index: usize,
+    pub id: Option<u32>,
+    // The title that matched best
+    pub matched: String,
+    pub title_score: f32,
+    // The title score with the tie-breakers on top
+    pub score: f32,
+}
+
+// Best match first. Naming a format puts the results in that format ahead of everything else
+pub fn rank(query: &str, candidates: &[Candidate]) -> Vec<Ranking> {
+    // Japanese searches are also compared in their own script, against native titles and synonyms
+    let native_query = has_japanese(query).then(|| normalize_native(query));
+    let query = normalize(query);
+    let requested_format = FORMAT_WORDS
+        .iter()
+        .find(|(word, _)| query.tokens.iter().any(|token| token == word))
+        .map(|(_, format)| *format);
+    let in_format = |ranking: &Ranking| {
+        requested_format
+            .is_some_and(|format| candidates[ranking.index].format.as_deref() == Some(format))
+    };
+    let most_popular = candidates
+        .iter()
+        .filter_map(|candidate| candidate.popularity)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+
+    let mut rankings: Vec<Ranking> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let (matched, title_score) = candidate
+                .titles()
+                .into_iter()
+                .map(|(title, weight)| {
+                    let romanized = score_title(&query, &normalize(title));
+                    let native = native_query.as_ref().map_or(0.0, |native_query| {
+                        score_title(native_query, &normalize_native(title))
+                    });
+                    (title, weight * romanized.max(native))
+                })
+                .fold(("", 0.0), |best, current| match current.1 > best.1 {
+                    true => current,
+                    false => best,
+                });
+
+            let rank = 1.0 - index as f32 / candidates.len() as f32;
+            let popularity = candidate.popularity.unwrap_or_default() as f32 / most_popular as f32;
+            let main_format = candidate
+                .format
+                .as_deref()
+                .is_some_and(|format| MAIN_FORMATS.contains(&format));
+            let tie_breakers = RANK_WEIGHT * rank
+                + POPULARITY_WEIGHT * popularity
+                + if main_format { FORMAT_WEIGHT } else { 0.0 };
+
+            Ranking {
+                index,
+                id: candidate.id,
+                matched: matched.to_string(),
+                title_score,
+                score: title_score + tie_breakers,
+            }
+        })
+        .collect();
+
+    rankings.sort_by(|a, b| {
+        in_format(b)
+            .cmp(&in_format(a))
+            .then(b.score.total_cmp(&a.score))
+            .then(a.index.cmp(&b.index))
+    });
+    rankings
+}
@@ -1,39 +1,479 @@
 use crate::models::{
-    fetcher::{AnimeConfig, Argument, MangaConfig, Response},
+    anilist_common::RelationNode,
+    fetcher::{check_match, format_allowed, FetchError, FetchOptions},
+    installment::Installment,
+    media_list_response::{CandidateScore, FetchResponse as MediaListResponse},
+    media_query::{MediaQuery, QueryTarget},
     media_type::MediaType as Type,
     transformers::Transformers,
 };
+use crate::utils::{
+    japanese::normalize_width,
+    providers::{kitsu::Kitsu, providers, with_failover, MetadataProvider, ProviderError},
+    ranking::{normalize, Ranking},
+    title_index::{title_index, Shortlist},
+};
+use serde::Serialize;
+use serde_json::Value;
 use tracing::info;
 
-fn return_argument(arg: &str) -> Argument {
-    match arg.parse::<u32>() {
-        Ok(id) => Argument::Id(id),
-        Err(_e) => Argument::Search(arg.to_string()),
+// How well the local index has to match before AniList isn't asked to search at all. Anything
+// less, or two entries matching about as well, goes to AniList which knows what is popular
+const LOCAL_CONFIDENCE: f32 = 0.95;
+const LOCAL_MARGIN: f32 = 0.05;
+// How well an index entry has to match to be fetched when the index isn't sure. Below that the
+// entry only shared a few trigrams with the search and AniList searches instead
+const SHORTLIST_FLOOR: f32 = 0.5;
+// How many of those close calls are offered besides the match
+const DID_YOU_MEAN: usize = 3;
+
+// Which way a lookup got to its match
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchPath {
+    // Asked for by id or link, nothing was ranked
+    Id,
+    // The local title index was sure, only the match itself was fetched
+    LocalIndex,
+    // The local title index wasn't sure, its closest entries were fetched by id and ranked
+    Shortlist,
+    // Ranked from the provider's search results
+    Search,
+    // Followed the sequels of the base title to the season asked for
+    Sequel,
+}
+
+// How a lookup found its match, with the scores of the candidates it actually ranked. A
+// sequel walk keeps the candidates of its base title
+#[derive(Serialize, Debug, Clone)]
+pub struct LookupTrace {
+    pub path: MatchPath,
+    pub provider: &'static str,
+    pub candidates: Vec<CandidateScore>,
+    // Local index entries that matched about as well as each other, so AniList had to pick
+    pub close_calls: Vec<CandidateScore>,
+}
+
+impl LookupTrace {
+    fn new(path: MatchPath, provider: &dyn MetadataProvider) -> LookupTrace {
+        LookupTrace {
+            path,
+            provider: provider.name(),
+            candidates: vec![],
+            close_calls: vec![],
+        }
+    }
+
+    // The close calls other than the match, for a "did you mean" next to it
+    pub fn did_you_mean(&self, matched: Option<u32>) -> Vec<&CandidateScore> {
+        self.close_calls
+            .iter()
+            .filter(|close_call| close_call.id != matched)
+            .take(DID_YOU_MEAN)
+            .collect()
     }
 }
 
-pub fn fetcher<
+// Turns raw user input into what to look up, flags and pasted links included
+pub fn parse_lookup(
+    media_type: Type,
+    input: &str,
+    mut options: FetchOptions,
+) -> Result<(QueryTarget, FetchOptions), FetchError> {
+    info!("Found Args: {:#?}", input);
+
+    let input = normalize_width(input);
+    let query = MediaQuery::parse(&input, media_type).map_err(FetchError::InvalidQuery)?;
+    info!("Parsed Query: {:#?}", query);
+
+    // Formats forced by the command itself win over the flag
+    if options.format_in.is_none() && !query.formats.is_empty() {
+        options.format_in = Some(query.formats);
+    }
+    options.year = query.year;
+
+    Ok((query.target, options))
+}
+
+impl From<ProviderError> for FetchError {
+    fn from(why: ProviderError) -> FetchError {
+        match why {
+            // Nobody knew what to do with the id
+            ProviderError::Unsupported => FetchError::Unmapped,
+            _ => FetchError::Unavailable,
+        }
+    }
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(media: Value) -> Result<T, ProviderError> {
+    serde_json::from_value(media).map_err(|why| {
+        info!("Could not read provider media: {:?}", why);
+        ProviderError::Malformed
+    })
+}
+
+// AniList applies these itself, the fallbacks only get them applied here
+fn matches_options<T: Transformers>(media: &T, options: &FetchOptions) -> bool {
+    let year_allowed = match options.year {
+        Some(year) => media
+            .get_start_date()
+            .and_then(|date| date.year)
+            .is_some_and(|start| start as i32 == year),
+        None => true,
+    };
+
+    format_allowed(media, options) && year_allowed
+}
+
+pub fn search<T: serde::de::DeserializeOwned + Transformers>(
+    provider: &dyn MetadataProvider,
+    media_type: Type,
+    search: &str,
+    options: &FetchOptions,
+) -> Result<MediaListResponse<T>, ProviderError> {
+    let media_list = provider
+        .search(media_type, search, options)?
+        .into_iter()
+        .map(deserialize::<T>)
+        .collect::<Result<Vec<T>, ProviderError>>()?
+        .into_iter()
+        .filter(|media| matches_options(media, options))
+        .collect();
+
+    Ok(MediaListResponse::from_media(media_list))
+}
+
+// The offline dataset's best matches for the search, none for manga which it doesn't have
+fn local_shortlist(media_type: Type, search: &str, options: &FetchOptions) -> Shortlist {
+    match media_type {
+        Type::Anime => title_index().search(search, options, DID_YOU_MEAN + 1),
+        Type::Manga => Shortlist::default(),
+    }
+}
+
+// Every entry the index was confident about, when there are several and it couldn't pick
+fn close_calls(shortlist: &Shortlist) -> Vec<CandidateScore> {
+    let confident: Vec<Ranking> = shortlist
+        .rankings
+        .iter()
+        .filter(|ranking| ranking.title_score >= LOCAL_CONFIDENCE)
+        .cloned()
+        .collect();
+
+    match confident.len() {
+        0 | 1 => vec![],
+        _ => shortlist.candidate_scores(&confident),
+    }
+}
+
+// The offline dataset's best match, fetched by id so the search itself costs no requests. None
+// when the index has nothing certain, or the entry doesn't pass the options once fetched
+fn local_match<T: serde::de::DeserializeOwned + Transformers>(
+    provider: &dyn MetadataProvider,
+    media_type: Type,
+    search: &str,
+    shortlist: &Shortlist,
+    options: &FetchOptions,
+) -> Result<Option<(T, LookupTrace)>, ProviderError> {
+    let rankings = &shortlist.rankings;
+    let Some((id, best)) = rankings
+        .first()
+        .filter(|best| best.title_score >= LOCAL_CONFIDENCE)
+        .filter(|best| {
+            rankings
+                .get(1)
+                .is_none_or(|next| next.title_score < best.title_score - LOCAL_MARGIN)
+        })
+        .and_then(|best| Some((best.id?, best)))
+    else {
+        return Ok(None);
+    };
+
+    info!(
+        "Local index matched {:?} to {} through {:?}",
+        search, id, best.matched
+    );
+    let media = match provider.get_by_id(media_type, &QueryTarget::AniList(id)) {
+        Ok(Some(media)) => deserialize::<T>(media)?,
+        Ok(None) | Err(ProviderError::Unsupported) => return Ok(None),
+        Err(why) => return Err(why),
+    };
+
+    let trace = LookupTrace {
+        candidates: shortlist.candidate_scores(rankings),
+        ..LookupTrace::new(MatchPath::LocalIndex, provider)
+    };
+    Ok(Some((media, trace)).filter(|(media, _)| {
+        matches_options(media, options) && (options.allow_adult || !media.get_is_adult())
+    }))
+}
+
+// The index's closest entries fetched by id and ranked like search results, for when it had
+// candidates but couldn't pick one. What AniList sends back has the popularity to break the tie.
+// None when nothing matched well enough to fetch, or none of it passed the options once fetched
+fn shortlist_match<
     T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
 >(
+    provider: &dyn MetadataProvider,
     media_type: Type,
-    mut args: serenity::framework::standard::Args,
-) -> Option<T> {
-    // Skips over the first arg because this is the command name
-    args.single::<String>().unwrap();
+    search: &str,
+    shortlist: &Shortlist,
+    options: &FetchOptions,
+) -> Result<Option<(T, LookupTrace)>, ProviderError> {
+    let ids: Vec<u32> = shortlist
+        .rankings
+        .iter()
+        .filter(|ranking| ranking.title_score >= SHORTLIST_FLOOR)
+        .filter_map(|ranking| ranking.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(None);
+    }
 
-    let args = args.remains().unwrap();
-    info!("Found Args: {:#?}", args);
+    info!("Local index shortlisted {:?} for {:?}", ids, search);
+    let mut media_list = vec![];
+    for id in ids {
+        match provider.get_by_id(media_type, &QueryTarget::AniList(id)) {
+            Ok(Some(media)) => media_list.push(deserialize::<T>(media)?),
+            Ok(None) => {}
+            Err(ProviderError::Unsupported) => return Ok(None),
+            Err(why) => return Err(why),
+        }
+    }
+    media_list.retain(|media| matches_options(media, options));
 
-    let argument = return_argument(args);
+    let response = MediaListResponse::from_media(media_list);
+    let trace = LookupTrace {
+        candidates: response.candidate_scores(search, media_type, options.allow_adult),
+        close_calls: close_calls(shortlist),
+        ..LookupTrace::new(MatchPath::Shortlist, provider)
+    };
+    Ok(response
+        .fuzzy_match(search, media_type, options.allow_adult)
+        .map(|media| (media, trace)))
+}
 
-    match media_type {
-        Type::Anime => {
-            let anime_response: AnimeConfig = Response::new(argument);
-            anime_response.fetch::<T>(media_type)
+// Local index first, the provider's search only when the index has nothing to offer
+fn best_match<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    provider: &dyn MetadataProvider,
+    media_type: Type,
+    value: &str,
+    options: &FetchOptions,
+) -> Result<Option<(T, LookupTrace)>, ProviderError> {
+    let shortlist = local_shortlist(media_type, value, options);
+    if let Some(matched) = local_match(provider, media_type, value, &shortlist, options)? {
+        return Ok(Some(matched));
+    }
+    if let Some(matched) = shortlist_match(provider, media_type, value, &shortlist, options)? {
+        return Ok(Some(matched));
+    }
+
+    let response: MediaListResponse<T> = search(provider, media_type, value, options)?;
+    let trace = LookupTrace {
+        candidates: response.candidate_scores(value, media_type, options.allow_adult),
+        close_calls: close_calls(&shortlist),
+        ..LookupTrace::new(MatchPath::Search, provider)
+    };
+    let result = response.fuzzy_match(value, media_type, options.allow_adult);
+    info!("Fuzzy Response from {}: {:#?}", provider.name(), result);
+    Ok(result.map(|media| (media, trace)))
+}
+
+// The entry that continues this one in the same format, a TV season's sequel is the next TV
+// season and not the movie that came out in between. Adult sequels count only where allowed
+fn next_sequel<T: Transformers>(
+    media: &T,
+    media_type: Type,
+    allow_adult: bool,
+) -> Option<RelationNode> {
+    let media_type = match media_type {
+        Type::Anime => "ANIME",
+        Type::Manga => "MANGA",
+    };
+
+    media
+        .get_relations()?
+        .of_type("SEQUEL", media_type)
+        .into_iter()
+        .filter(|node| node.format.is_none() || node.format == media.get_format())
+        .filter(|node| allow_adult || !node.is_adult())
+        .min_by_key(|node| node.start_date.unwrap_or_default())
+        .cloned()
+}
+
+// Finds the base title and follows its sequels to the season and part asked for. None when the
+// franchise runs out before that, or the provider doesn't know about relations
+fn find_installment<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    provider: &dyn MetadataProvider,
+    media_type: Type,
+    installment: &Installment,
+    options: &FetchOptions,
+) -> Result<Option<(T, LookupTrace)>, ProviderError> {
+    let Some((mut media, trace)) =
+        best_match::<T>(provider, media_type, &installment.title, options)?
+    else {
+        return Ok(None);
+    };
+    let (mut season, mut part) = (1, 1);
+
+    while (season, part) < (installment.season, installment.part) {
+        let Some(sequel) = next_sequel(&media, media_type, options.allow_adult) else {
+            return Ok(None);
+        };
+        // Split seasons come as separate entries, "Shingeki no Kyojin 3 Part 2" is still the
+        // third season
+        let continues_season = [&sequel.title.romaji, &sequel.title.english]
+            .into_iter()
+            .flatten()
+            .filter_map(|title| Installment::parse(title))
+            .any(|title| title.part > 1);
+        match continues_season {
+            true => part += 1,
+            false => (season, part) = (season + 1, 1),
         }
-        Type::Manga => {
-            let manga_response: MangaConfig = Response::new(argument);
-            manga_response.fetch::<T>(media_type)
+        if season > installment.season {
+            return Ok(None);
+        }
+
+        info!(
+            "Following sequel {} to season {} part {}",
+            sequel.id, season, part
+        );
+        media = match provider.get_by_id(media_type, &QueryTarget::AniList(sequel.id))? {
+            Some(sequel) => deserialize::<T>(sequel)?,
+            None => return Ok(None),
+        };
+    }
+
+    let trace = LookupTrace {
+        path: MatchPath::Sequel,
+        ..trace
+    };
+    Ok(Some((media, trace)))
+}
+
+pub fn lookup<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    media_type: Type,
+    input: &str,
+    options: FetchOptions,
+) -> Result<T, FetchError> {
+    traced_lookup(media_type, input, options).map(|(media, _)| media)
+}
+
+// Kitsu links and ids are mostly slugs, but the id mapper only knows Kitsu's numeric ids. Asks
+// Kitsu itself when none of the providers knows slugs. Left as it is when Kitsu can't be asked,
+// its own fallback can still look the slug up
+fn resolve_kitsu_slug(
+    providers: &[Box<dyn MetadataProvider>],
+    media_type: Type,
+    target: QueryTarget,
+) -> QueryTarget {
+    match target {
+        QueryTarget::Kitsu(slug) if slug.parse::<u32>().is_err() => {
+            let resolved = match with_failover(providers, |provider| {
+                provider.resolve_slug(media_type, &slug)
+            }) {
+                Err(ProviderError::Unsupported) => Kitsu::default().resolve_slug(media_type, &slug),
+                resolved => resolved,
+            };
+            match resolved {
+                Ok(Some(id)) => QueryTarget::Kitsu(id),
+                _ => QueryTarget::Kitsu(slug),
+            }
+        }
+        target => target,
+    }
+}
+
+// True when the best candidate is the whole search, "Monogatari Series: Second Season" is a
+// title of its own and not the second season of something
+fn matches_exactly(trace: &LookupTrace, search: &str) -> bool {
+    let search = normalize(search).compact;
+    trace
+        .candidates
+        .first()
+        .is_some_and(|best| normalize(&best.matched).compact == search)
+}
+
+// The lookup along with how it found its match, for the CLI
+pub fn traced_lookup<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    media_type: Type,
+    input: &str,
+    options: FetchOptions,
+) -> Result<(T, LookupTrace), FetchError> {
+    traced_lookup_with(&providers(), media_type, input, options)
+}
+
+pub fn traced_lookup_with<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    providers: &[Box<dyn MetadataProvider>],
+    media_type: Type,
+    input: &str,
+    options: FetchOptions,
+) -> Result<(T, LookupTrace), FetchError> {
+    let (target, options) = parse_lookup(media_type, input, options)?;
+    let target = resolve_kitsu_slug(providers, media_type, target);
+
+    let matched = with_failover(providers, |provider| match &target {
+        QueryTarget::Search(value) => {
+            let plain = best_match(provider, media_type, value, &options)?;
+            // "season 3 part 2" is found through the first season's sequels, unless a title
+            // is the whole search. The plain match is still there for when the walk goes nowhere
+            let installment = Installment::parse(value).filter(|_| {
+                !plain
+                    .as_ref()
+                    .is_some_and(|(_, trace)| matches_exactly(trace, value))
+            });
+            let Some(installment) = installment else {
+                return Ok(plain);
+            };
+
+            match find_installment(provider, media_type, &installment, &options)? {
+                Some(matched) => Ok(Some(matched)),
+                None => Ok(plain),
+            }
         }
+        target => Ok(provider
+            .get_by_id(media_type, target)?
+            .map(deserialize::<T>)
+            .transpose()?
+            .map(|media| (media, LookupTrace::new(MatchPath::Id, provider)))),
+    })?;
+
+    match matched {
+        None => Err(FetchError::NotFound),
+        Some((media, trace)) => Ok((check_match(Some(media), &options)?, trace)),
     }
 }
+
+pub fn fetcher<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    media_type: Type,
+    args: serenity::framework::standard::Args,
+    options: FetchOptions,
+) -> Result<T, FetchError> {
+    traced_fetcher(media_type, args, options).map(|(media, _)| media)
+}
+
+pub fn traced_fetcher<
+    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    media_type: Type,
+    mut args: serenity::framework::standard::Args,
+    options: FetchOptions,
+) -> Result<(T, LookupTrace), FetchError> {
+    // Skips over the first arg because this is the command name
+    args.advance();
+
+    traced_lookup(media_type, args.remains().unwrap_or_default(), options)
+}
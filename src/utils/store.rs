@@ -0,0 +1,69 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+use tracing::{error, info};
+
+const DEFAULT_DATA_DIR: &str = "./data";
+
+pub fn data_dir() -> PathBuf {
+    PathBuf::from(env::var("ANNIE_DATA_DIR").unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string()))
+}
+
+// Small JSON file backed store for anything the bot needs to remember across restarts
+#[derive(Debug)]
+pub struct JsonStore<T> {
+    path: PathBuf,
+    pub data: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    pub fn open(name: &str) -> JsonStore<T> {
+        let path = data_dir().join(format!("{}.json", name));
+
+        let data = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|why| {
+                error!("Could not parse store {:#?}: {:?}", path, why);
+                T::default()
+            }),
+            Err(_) => {
+                info!("No store found at {:#?}, starting fresh", path);
+                T::default()
+            }
+        };
+
+        JsonStore { path, data }
+    }
+
+    pub fn save(&self) {
+        write(
+            &self.path,
+            &serde_json::to_string_pretty(&self.data).unwrap(),
+        );
+    }
+
+    // Writes on the blocking pool instead of the async runtime. Keep the store locked until this
+    // is done, so the saves land in the order they were made
+    pub async fn save_async(&self) {
+        let path = self.path.clone();
+        let contents = serde_json::to_string_pretty(&self.data).unwrap();
+
+        if let Err(why) = tokio::task::spawn_blocking(move || write(&path, &contents)).await {
+            error!("Saving store {:#?} failed: {:?}", self.path, why);
+        }
+    }
+}
+
+fn write(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        if let Err(why) = fs::create_dir_all(parent) {
+            error!("Could not create data directory {:#?}: {:?}", parent, why);
+            return;
+        }
+    }
+
+    if let Err(why) = fs::write(path, contents) {
+        error!("Could not write store {:#?}: {:?}", path, why);
+    }
+}
@@ -0,0 +1,235 @@
+// Every anime title and synonym from the anime-offline-database, so searches can be answered
+// without AniList and only the by-id fetch of the match goes out. The dataset has no manga
+
+use crate::{
+    models::{anilist_common::Title, fetcher::FetchOptions, media_list_response::CandidateScore},
+    utils::{
+        id_mapper::{dataset_path, Dataset, MappedIds},
+        ranking::{normalize, rank, trigrams, Candidate, Ranking},
+    },
+};
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Arc, OnceLock, RwLock},
+};
+use tracing::{error, info};
+
+// How many of the entries sharing the most trigrams with the search get ranked properly
+const SHORTLIST: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    // With the AniList id, AniList's format name and the dataset's main title as romaji
+    pub candidate: Candidate,
+    pub year: Option<i32>,
+}
+
+impl IndexEntry {
+    // AniList applies these to its searches, the index has to do it itself
+    fn matches(&self, options: &FetchOptions) -> bool {
+        let format_allowed = match (&options.format_in, &self.candidate.format) {
+            (Some(formats), Some(format)) => formats
+                .iter()
+                .any(|allowed| allowed.anilist_name() == format),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let year_allowed = options.year.is_none() || options.year == self.year;
+
+        format_allowed && year_allowed
+    }
+}
+
+// What a search ranked, best first, with the entries it ranked them from
+#[derive(Debug, Default)]
+pub struct Shortlist {
+    pub rankings: Vec<Ranking>,
+    candidates: Vec<Candidate>,
+}
+
+impl Shortlist {
+    // Scores of some of these rankings, with the dataset's main title of each entry
+    pub fn candidate_scores(&self, rankings: &[Ranking]) -> Vec<CandidateScore> {
+        rankings
+            .iter()
+            .map(|ranking| {
+                let title = self
+                    .candidates
+                    .get(ranking.index)
+                    .and_then(|candidate| candidate.title.romaji.to_owned())
+                    .unwrap_or_else(|| ranking.matched.to_string());
+
+                CandidateScore {
+                    id: ranking.id,
+                    title,
+                    matched: ranking.matched.to_string(),
+                    title_score: ranking.title_score,
+                    score: ranking.score,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TitleIndex {
+    entries: Vec<IndexEntry>,
+    // Trigrams of every normalized title to the entries that have them
+    trigrams: HashMap<[char; 3], Vec<u32>>,
+    // Every normalized title with its entry, sorted for prefix lookups
+    titles: Vec<(String, u32)>,
+}
+
+impl TitleIndex {
+    pub fn from_json(json: &str) -> Result<TitleIndex, serde_json::Error> {
+        Ok(TitleIndex::from_dataset(&serde_json::from_str(json)?))
+    }
+
+    pub(crate) fn from_dataset(dataset: &Dataset) -> TitleIndex {
+        let mut index = TitleIndex::default();
+
+        for entry in &dataset.data {
+            // Only entries that can be fetched from AniList afterwards
+            let Some(id) = MappedIds::from_sources(&entry.sources).anilist else {
+                continue;
+            };
+            let position = index.entries.len() as u32;
+            let format = entry
+                .format
+                .as_deref()
+                .filter(|format| *format != "UNKNOWN")
+                .map(str::to_string);
+
+            let mut entry_trigrams: Vec<[char; 3]> = vec![];
+            for title in std::iter::once(&entry.title).chain(&entry.synonyms) {
+                let normalized = normalize(title);
+                if normalized.compact.is_empty() {
+                    continue;
+                }
+                entry_trigrams.extend(trigrams(&normalized.compact).into_keys());
+                index.titles.push((normalized.compact, position));
+            }
+            entry_trigrams.sort_unstable();
+            entry_trigrams.dedup();
+            for trigram in entry_trigrams {
+                index.trigrams.entry(trigram).or_default().push(position);
+            }
+
+            index.entries.push(IndexEntry {
+                candidate: Candidate {
+                    id: Some(id),
+                    title: Title {
+                        romaji: Some(entry.title.to_string()),
+                        english: None,
+                        native: None,
+                    },
+                    synonyms: Some(entry.synonyms.clone()),
+                    format,
+                    popularity: None,
+                },
+                year: entry.anime_season.as_ref().and_then(|season| season.year),
+            });
+        }
+
+        index.titles.sort_unstable();
+        index.titles.dedup();
+        index
+    }
+
+    pub fn load() -> TitleIndex {
+        let path = dataset_path();
+        let index = fs::read_to_string(&path)
+            .map_err(|why| info!("No title dataset found at {:#?}: {:?}", path, why))
+            .and_then(|json| {
+                TitleIndex::from_json(&json)
+                    .map_err(|why| error!("Could not parse title dataset {:#?}: {:?}", path, why))
+            })
+            .unwrap_or_default();
+
+        info!("Indexed {} titles", index.len());
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Best match first, like a search on AniList ranked by fuzzy_match
+    pub fn search(&self, query: &str, options: &FetchOptions, limit: usize) -> Shortlist {
+        let normalized = normalize(query);
+        let mut shared: HashMap<u32, usize> = HashMap::new();
+        for trigram in trigrams(&normalized.compact).keys() {
+            for position in self.trigrams.get(trigram).into_iter().flatten() {
+                *shared.entry(*position).or_default() += 1;
+            }
+        }
+
+        let mut shortlist: Vec<(u32, usize)> = shared
+            .into_iter()
+            .filter(|(position, _)| self.entries[*position as usize].matches(options))
+            .collect();
+        shortlist.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        shortlist.truncate(SHORTLIST);
+
+        let candidates: Vec<Candidate> = shortlist
+            .iter()
+            .map(|(position, _)| self.entries[*position as usize].candidate.clone())
+            .collect();
+        let mut rankings = rank(query, &candidates);
+        rankings.truncate(limit);
+        Shortlist {
+            rankings,
+            candidates,
+        }
+    }
+
+    // Entries with a title starting with what was typed so far, shortest title first
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<&IndexEntry> {
+        let prefix = normalize(prefix).compact;
+        if prefix.is_empty() {
+            return vec![];
+        }
+
+        let start = self.titles.partition_point(|(title, _)| *title < prefix);
+        let mut matches: Vec<&(String, u32)> = self.titles[start..]
+            .iter()
+            .take_while(|(title, _)| title.starts_with(&prefix))
+            .collect();
+        matches.sort_by_key(|(title, _)| title.len());
+
+        let mut completions: Vec<&IndexEntry> = vec![];
+        for (_, position) in matches {
+            let entry = &self.entries[*position as usize];
+            if !completions
+                .iter()
+                .any(|added| added.candidate.id == entry.candidate.id)
+            {
+                completions.push(entry);
+            }
+            if completions.len() == limit {
+                break;
+            }
+        }
+        completions
+    }
+}
+
+fn index_lock() -> &'static RwLock<Arc<TitleIndex>> {
+    static INDEX: OnceLock<RwLock<Arc<TitleIndex>>> = OnceLock::new();
+    INDEX.get_or_init(|| RwLock::new(Arc::new(TitleIndex::load())))
+}
+
+// Loaded from disk the first time it is needed
+pub fn title_index() -> Arc<TitleIndex> {
+    index_lock().read().unwrap().clone()
+}
+
+// Swaps in an index built from a freshly downloaded dataset
+pub(crate) fn replace(index: TitleIndex) {
+    *index_lock().write().unwrap() = Arc::new(index);
+}
@@ -0,0 +1,83 @@
+// Runs commands end to end against recorded AniList and MAL responses and a fake Discord
+
+mod common;
+
+use annie_mai::{
+    commands::{
+        anime::command::ANIME_COMMAND, manga::command::MANGA_COMMAND, songs::command::SONGS_COMMAND,
+    },
+    models::{
+        anilist_anime::Anime, fetcher::FetchOptions, media_type::MediaType as Type,
+        transformers::Transformers,
+    },
+    utils::response_fetcher::{traced_lookup, MatchPath},
+};
+use common::{discord::Discord, golden::check_embed, replay};
+use serde_json::Value;
+
+async fn first_embed(
+    command: &'static serenity::framework::standard::Command,
+    content: &str,
+) -> Value {
+    replay::start();
+    let discord = Discord::start();
+    discord.run(command, content).await;
+
+    let sent = discord.sent();
+    assert_eq!(sent.len(), 1, "Expected a single message, got {:#?}", sent);
+    sent[0]["embeds"][0].clone()
+}
+
+#[tokio::test]
+async fn anime() {
+    let embed = first_embed(&ANIME_COMMAND, "!anime cowboy bebop").await;
+    check_embed("anime_cowboy_bebop", &embed);
+}
+
+// Searched as typed and as romaji, the native title picks the same entry as the English search
+#[tokio::test]
+async fn anime_katakana() {
+    let embed = first_embed(&ANIME_COMMAND, "!anime カウボーイビバップ").await;
+    check_embed("anime_cowboy_bebop", &embed);
+}
+
+// "Attack on Titan Season 3" is the English title, so the search finds it without walking
+// the first season's sequels
+#[tokio::test]
+async fn anime_sequel() {
+    let embed = first_embed(&ANIME_COMMAND, "!anime attack on titan season 3").await;
+    check_embed("anime_attack_on_titan_season_3", &embed);
+}
+
+#[tokio::test]
+async fn manga() {
+    let embed = first_embed(&MANGA_COMMAND, "!manga berserk").await;
+    check_embed("manga_berserk", &embed);
+}
+
+#[tokio::test]
+async fn songs() {
+    let embed = first_embed(&SONGS_COMMAND, "!songs cowboy bebop").await;
+    check_embed("songs_cowboy_bebop", &embed);
+}
+
+// What annie-cli prints, the scores have to come from the path that found the match
+#[test]
+fn trace_belongs_to_the_match() {
+    replay::start();
+
+    let (anime, trace) =
+        traced_lookup::<Anime>(Type::Anime, "cowboy bebop", FetchOptions::default()).unwrap();
+    assert_eq!(trace.path, MatchPath::Search);
+    assert_eq!(trace.provider, "AniList");
+    assert_eq!(trace.candidates[0].id, anime.get_id());
+
+    let (anime, trace) = traced_lookup::<Anime>(
+        Type::Anime,
+        "attack on titan season 3",
+        FetchOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(trace.path, MatchPath::Search);
+    assert_eq!(trace.candidates[0].id, anime.get_id());
+}
@@ -0,0 +1,133 @@
+// A fake Discord HTTP API, so command handlers run against a Context without a gateway and
+// everything they send can be looked at afterwards
+
+use super::http::{serve, Reply, Request};
+use annie_mai::commands::{config::settings::GuildConfig, prefs::settings::UserConfig};
+use futures::channel::mpsc;
+use serde_json::{json, Value};
+use serenity::{
+    cache::Cache,
+    client::{bridge::gateway::ShardMessenger, Context},
+    framework::standard::{Args, Command, Delimiter},
+    http::{Http, HttpBuilder},
+    model::channel::Message,
+    prelude::{RwLock, TypeMap},
+};
+use std::sync::{Arc, Mutex};
+
+const CHANNEL_ID: u64 = 1000;
+const USER_ID: u64 = 2000;
+
+// Discord echoes the message back with the ids and timestamps filled in
+fn sent_message(request: &Request) -> Value {
+    let body: Value = serde_json::from_str(&request.body).unwrap_or_default();
+
+    json!({
+        "id": "3000",
+        "channel_id": CHANNEL_ID.to_string(),
+        "author": {"id": "4000", "username": "Annie Mai", "discriminator": "0000", "avatar": null, "bot": true},
+        "content": body["content"].as_str().unwrap_or_default(),
+        "embeds": [],
+        "attachments": [],
+        "mentions": [],
+        "mention_roles": [],
+        "mention_everyone": false,
+        "pinned": false,
+        "tts": false,
+        "type": 0,
+        "timestamp": "2022-08-01T00:00:00.000000+00:00",
+        "edited_timestamp": null,
+    })
+}
+
+pub struct Discord {
+    http: Arc<Http>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Discord {
+    pub fn start() -> Discord {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        let base = serve(move |request| {
+            let body = match request.method.as_str() {
+                "POST" | "PATCH" if request.path.ends_with("/messages") => sent_message(&request),
+                _ => json!({}),
+            };
+            recorded.lock().unwrap().push(request);
+            Reply {
+                status: 200,
+                body: body.to_string(),
+            }
+        });
+
+        let http = HttpBuilder::new("Bot fake-token")
+            .proxy(base)
+            .expect("Valid proxy url")
+            .ratelimiter_disabled(true)
+            .build();
+
+        Discord {
+            http: Arc::new(http),
+            requests,
+        }
+    }
+
+    // What the handler would get from a running client, with fresh stores
+    pub fn context(&self) -> Context {
+        let mut data = TypeMap::new();
+        data.insert::<GuildConfig>(GuildConfig::open());
+        data.insert::<UserConfig>(UserConfig::open());
+        // Nobody reads from the gateway, collectors just never see anything
+        let (sender, _) = mpsc::unbounded();
+
+        Context {
+            data: Arc::new(RwLock::new(data)),
+            shard: ShardMessenger::new(sender),
+            shard_id: 0,
+            http: self.http.clone(),
+            cache: Arc::new(Cache::new()),
+        }
+    }
+
+    // Bodies of every message the bot sent
+    pub fn sent(&self) -> Vec<Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.method == "POST" && request.path.ends_with("/messages"))
+            .filter_map(|request| serde_json::from_str(&request.body).ok())
+            .collect()
+    }
+
+    // Runs a command the way the framework would for a DM with this content
+    pub async fn run(&self, command: &'static Command, content: &str) {
+        let message = message(content);
+        let args = Args::new(content, &[Delimiter::Single(' ')]);
+
+        (command.fun)(&self.context(), &message, args)
+            .await
+            .expect("Command ran");
+    }
+}
+
+pub fn message(content: &str) -> Message {
+    serde_json::from_value(json!({
+        "id": "5000",
+        "channel_id": CHANNEL_ID.to_string(),
+        "author": {"id": USER_ID.to_string(), "username": "tester", "discriminator": "0001", "avatar": null},
+        "content": content,
+        "embeds": [],
+        "attachments": [],
+        "mentions": [],
+        "mention_roles": [],
+        "mention_everyone": false,
+        "pinned": false,
+        "tts": false,
+        "type": 0,
+        "timestamp": "2022-08-01T00:00:00.000000+00:00",
+        "edited_timestamp": null,
+    }))
+    .expect("Valid message")
+}
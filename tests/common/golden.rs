@@ -0,0 +1,32 @@
+// Expected embeds live in tests/golden. UPDATE_GOLDEN=1 rewrites them from what the commands
+// sent, so does RECORD_FIXTURES=1 since new recordings mean new embeds
+
+use super::replay::recording;
+use serde_json::{Map, Value};
+use std::{env, fs};
+
+// The parts of an embed worth pinning down, the description is mostly AniList's own text
+const CHECKED: [&str; 4] = ["title", "fields", "color", "thumbnail"];
+
+pub fn check_embed(name: &str, embed: &Value) {
+    let path = format!("tests/golden/{}.json", name);
+    let actual: Map<String, Value> = CHECKED
+        .iter()
+        .map(|key| (key.to_string(), embed[key].clone()))
+        .collect();
+    let actual = Value::Object(actual);
+
+    if recording() || env::var("UPDATE_GOLDEN").is_ok_and(|update| update == "1") {
+        fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n")
+            .expect("Saved golden embed");
+        return;
+    }
+
+    let expected: Value = serde_json::from_str(
+        &fs::read_to_string(&path).unwrap_or_else(|_| panic!("Missing golden embed {}", path)),
+    )
+    .expect("Valid golden embed");
+    for key in CHECKED {
+        assert_eq!(actual[key], expected[key], "{} differs from {}", key, path);
+    }
+}
@@ -0,0 +1,7 @@
+// Shared by the integration tests, not every test binary uses all of it
+#![allow(dead_code)]
+
+pub mod discord;
+pub mod golden;
+pub mod http;
+pub mod replay;
@@ -0,0 +1,211 @@
+// Serves recorded AniList, MAL, Jikan and Kitsu responses from fixtures/recorded, keyed by the
+// request. RECORD_FIXTURES=1 sends every request to the real service instead and saves what it
+// answered, MAL needs a real MAL_CLIENT_ID for that.
+// fixtures/handmade holds answers written or edited by hand, a recording of the same request wins
+
+use super::http::{serve, Reply, Request};
+use annie_mai::utils::id_mapper;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const FIXTURES: &str = "fixtures/recorded";
+const HANDMADE: &str = "fixtures/handmade";
+
+// Path prefix on the mock, environment variable the client reads and the real service
+const SERVICES: [(&str, &str, &str); 4] = [
+    ("anilist", "ANILIST_URL", "https://graphql.anilist.co"),
+    ("mal", "MAL_URL", "https://api.myanimelist.net/v2"),
+    ("jikan", "JIKAN_URL", "https://api.jikan.moe/v4"),
+    ("kitsu", "KITSU_URL", "https://kitsu.app/api/edge"),
+];
+
+pub fn recording() -> bool {
+    env::var("RECORD_FIXTURES").is_ok_and(|record| record == "1")
+}
+
+// Same request, same key. JSON bodies are compared by value so key order doesn't matter
+fn key(method: &str, path: &str, body: &str) -> String {
+    let body = serde_json::from_str::<Value>(body)
+        .map(|body| body.to_string())
+        .unwrap_or_else(|_| body.to_string());
+
+    format!("{} {}\n{}", method, path, body)
+}
+
+// FNV-1a, stable across Rust versions unlike the std hasher
+fn file_name(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}.json", hash)
+}
+
+struct Recording {
+    status: u16,
+    body: String,
+}
+
+struct Replay {
+    recordings: HashMap<String, Recording>,
+}
+
+impl Replay {
+    fn load() -> Replay {
+        let mut recordings = HashMap::new();
+        for (directory, service) in [FIXTURES, HANDMADE]
+            .into_iter()
+            .flat_map(|directory| SERVICES.map(|(service, _, _)| (directory, service)))
+        {
+            let Ok(files) = fs::read_dir(Path::new(directory).join(service)) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let recording: Value = serde_json::from_str(
+                    &fs::read_to_string(file.path()).expect("Readable recording"),
+                )
+                .unwrap_or_else(|why| panic!("Broken recording {:?}: {:?}", file.path(), why));
+                let request = &recording["request"];
+                let body = match &request["body"] {
+                    Value::Null => String::new(),
+                    body => body.to_string(),
+                };
+                let key = key(
+                    request["method"].as_str().unwrap_or_default(),
+                    &format!(
+                        "/{}{}",
+                        service,
+                        request["path"].as_str().unwrap_or_default()
+                    ),
+                    &body,
+                );
+                let body = match &recording["response"] {
+                    Value::String(text) => text.to_string(),
+                    response => response.to_string(),
+                };
+                let status = recording["status"].as_u64().unwrap_or(200) as u16;
+                recordings.entry(key).or_insert(Recording { status, body });
+            }
+        }
+
+        Replay { recordings }
+    }
+
+    fn answer(&self, request: Request) -> Reply {
+        if recording() {
+            return record(&request);
+        }
+
+        let key = key(&request.method, &request.path, &request.body);
+        match self.recordings.get(&key) {
+            Some(recording) => Reply {
+                status: recording.status,
+                body: recording.body.to_string(),
+            },
+            None => {
+                eprintln!(
+                    "No recording for {} {}, record it with RECORD_FIXTURES=1. Body: {}",
+                    request.method, request.path, request.body
+                );
+                // Anything in the 500s makes the clients give up on the provider
+                Reply {
+                    status: 501,
+                    body: json!({"error": "No recording"}).to_string(),
+                }
+            }
+        }
+    }
+}
+
+// Every request and answer from one service the mock can replay, recorded and handmade alike
+pub fn recordings(service: &str) -> Vec<Value> {
+    [FIXTURES, HANDMADE]
+        .iter()
+        .filter_map(|directory| fs::read_dir(Path::new(directory).join(service)).ok())
+        .flat_map(|files| files.flatten())
+        .map(|file| {
+            serde_json::from_str(&fs::read_to_string(file.path()).expect("Readable recording"))
+                .unwrap_or_else(|why| panic!("Broken recording {:?}: {:?}", file.path(), why))
+        })
+        .collect()
+}
+
+// Forwards to the real service and saves its answer
+fn record(request: &Request) -> Reply {
+    let (service, path) = request.path[1..]
+        .split_once('/')
+        .map(|(service, path)| (service, format!("/{}", path)))
+        .unwrap_or((&request.path[1..], String::new()));
+    let (_, _, upstream) = SERVICES
+        .iter()
+        .find(|(name, _, _)| *name == service)
+        .unwrap_or_else(|| panic!("Unknown service in {}", request.path));
+
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}{}", upstream, path);
+    let mut upstream_request = match request.method.as_str() {
+        "POST" => client.post(&url).body(request.body.to_string()),
+        _ => client.get(&url),
+    };
+    for header in ["Content-Type", "Accept", "X-MAL-CLIENT-ID"] {
+        if let Some(value) = request.header(header) {
+            upstream_request = upstream_request.header(header, value);
+        }
+    }
+    let response = upstream_request.send().expect("Upstream answered");
+    let status = response.status().as_u16();
+    let body = response.text().unwrap_or_default();
+
+    let request_body = match request.body.is_empty() {
+        true => Value::Null,
+        false => serde_json::from_str(&request.body)
+            .unwrap_or_else(|_| Value::String(request.body.to_string())),
+    };
+    let recording = json!({
+        "request": {"method": request.method, "path": path, "body": request_body},
+        "status": status,
+        "response": serde_json::from_str::<Value>(&body).unwrap_or(Value::String(body.to_string())),
+    });
+    let directory = PathBuf::from(FIXTURES).join(service);
+    fs::create_dir_all(&directory).expect("Fixture directory");
+    fs::write(
+        directory.join(file_name(&key(
+            &request.method,
+            &request.path,
+            &request.body,
+        ))),
+        serde_json::to_string_pretty(&recording).unwrap() + "\n",
+    )
+    .expect("Saved recording");
+
+    Reply { status, body }
+}
+
+// Starts the mock once per test binary and points every client at it. The commands build their
+// own clients, so this is still done through the environment, before any command runs
+pub fn start() {
+    static STARTED: OnceLock<()> = OnceLock::new();
+
+    STARTED.get_or_init(|| {
+        let replay = Replay::load();
+        let base = serve(move |request| replay.answer(request));
+
+        for (service, variable, _) in SERVICES {
+            // AniList posts to the root of its url
+            let suffix = if service == "anilist" { "/" } else { "" };
+            env::set_var(variable, format!("{}/{}{}", base, service, suffix));
+        }
+        if !recording() {
+            env::set_var("MAL_CLIENT_ID", "replay");
+        }
+        // Nothing from the machine running the tests leaks in
+        let data_dir = env::temp_dir().join(format!("annie-mai-tests-{}", std::process::id()));
+        env::set_var("ANNIE_DATA_DIR", &data_dir);
+        id_mapper::use_dataset(r#"{"data": []}"#).unwrap();
+    });
+}
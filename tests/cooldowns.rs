@@ -0,0 +1,117 @@
+// Server cooldowns set with !config cooldown, see commands/config/cooldowns.rs
+
+use annie_mai::{
+    commands::config::cooldowns::{CooldownTracker, Limited},
+    models::guild_settings::{Cooldown, CooldownScope},
+};
+use std::time::{Duration, Instant};
+
+const GUILD: u64 = 1;
+const USER: u64 = 10;
+
+#[test]
+fn parses_cooldowns() {
+    let cooldown = |uses, seconds, scope| Cooldown {
+        uses,
+        seconds,
+        scope,
+    };
+
+    assert_eq!(
+        Cooldown::from_args("3/60", None),
+        Some(cooldown(3, 60, CooldownScope::User))
+    );
+    assert_eq!(
+        Cooldown::from_args("1/10", Some("channel")),
+        Some(cooldown(1, 10, CooldownScope::Channel))
+    );
+    assert_eq!(
+        Cooldown::from_args("5/300", Some("Server")),
+        Some(cooldown(5, 300, CooldownScope::Guild))
+    );
+    for (limit, scope) in [
+        ("3", None),
+        ("0/60", None),
+        ("3/0", None),
+        ("three/60", None),
+        ("3/60", Some("everyone")),
+    ] {
+        assert_eq!(Cooldown::from_args(limit, scope), None, "{:?}", limit);
+    }
+}
+
+#[test]
+fn limits_uses_within_the_cooldown() {
+    let mut tracker = CooldownTracker::default();
+    let cooldown = Cooldown::from_args("2/60", None).unwrap();
+    let start = Instant::now();
+    let at = |seconds| start + Duration::from_secs(seconds);
+
+    assert_eq!(tracker.take(GUILD, "anime", USER, &cooldown, at(0)), Ok(()));
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER, &cooldown, at(10)),
+        Ok(())
+    );
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER, &cooldown, at(20)),
+        Err(Limited {
+            wait: Duration::from_secs(40),
+            is_first_try: true,
+        })
+    );
+    // Only told once
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER, &cooldown, at(30)),
+        Err(Limited {
+            wait: Duration::from_secs(30),
+            is_first_try: false,
+        })
+    );
+
+    // Other users, commands and guilds have their own uses
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER + 1, &cooldown, at(30)),
+        Ok(())
+    );
+    assert_eq!(
+        tracker.take(GUILD, "manga", USER, &cooldown, at(30)),
+        Ok(())
+    );
+    assert_eq!(
+        tracker.take(GUILD + 1, "anime", USER, &cooldown, at(30)),
+        Ok(())
+    );
+
+    // The first use ran out
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER, &cooldown, at(60)),
+        Ok(())
+    );
+    assert!(tracker
+        .take(GUILD, "anime", USER, &cooldown, at(61))
+        .is_err());
+}
+
+#[test]
+fn follows_changed_cooldowns() {
+    let mut tracker = CooldownTracker::default();
+    let loose = Cooldown::from_args("3/60", None).unwrap();
+    let strict = Cooldown::from_args("1/60", None).unwrap();
+    let start = Instant::now();
+    let at = |seconds| start + Duration::from_secs(seconds);
+
+    for seconds in 0..3 {
+        assert_eq!(
+            tracker.take(GUILD, "anime", USER, &loose, at(seconds)),
+            Ok(())
+        );
+    }
+    // Every use above the new limit has to run out first
+    assert_eq!(
+        tracker.take(GUILD, "anime", USER, &strict, at(10)),
+        Err(Limited {
+            wait: Duration::from_secs(52),
+            is_first_try: true,
+        })
+    );
+}
@@ -0,0 +1,136 @@
+// Every partial date AniList can send, see models/fuzzy_date.rs
+
+use annie_mai::models::{anilist_anime::Anime, fuzzy_date::FuzzyDate, transformers::Transformers};
+use serde_json::json;
+use std::cmp::Ordering;
+
+// Missing, out of range and real values for every part, 2020 is a leap year
+const YEARS: [Option<u32>; 5] = [None, Some(0), Some(1999), Some(2020), Some(2021)];
+const MONTHS: [Option<u32>; 6] = [None, Some(0), Some(1), Some(2), Some(12), Some(13)];
+const DAYS: [Option<u32>; 8] = [
+    None,
+    Some(0),
+    Some(1),
+    Some(28),
+    Some(29),
+    Some(30),
+    Some(31),
+    Some(32),
+];
+
+const STATUSES: [Option<&str>; 6] = [
+    None,
+    Some("FINISHED"),
+    Some("RELEASING"),
+    Some("NOT_YET_RELEASED"),
+    Some("CANCELLED"),
+    Some("HIATUS"),
+];
+
+fn all_dates() -> Vec<FuzzyDate> {
+    let mut dates = Vec::new();
+    for year in YEARS {
+        for month in MONTHS {
+            for day in DAYS {
+                dates.push(FuzzyDate { year, month, day });
+            }
+        }
+    }
+    dates
+}
+
+#[test]
+fn formats_every_combination() {
+    for date in all_dates() {
+        let formatted = date.format();
+
+        match date.year {
+            Some(year) if year > 0 => {
+                let formatted = formatted.unwrap_or_else(|| panic!("{:?}", date));
+                assert!(formatted.ends_with(&year.to_string()), "{:?}", date);
+            }
+            _ => assert_eq!(formatted, None, "{:?}", date),
+        }
+    }
+}
+
+#[test]
+fn ord_agrees_with_sort_key() {
+    let dates = all_dates();
+
+    for a in dates.iter() {
+        for b in dates.iter() {
+            let ordering = a.cmp(b);
+            assert_eq!(ordering, a.sort_key().cmp(&b.sort_key()), "{:?} {:?}", a, b);
+            assert_eq!(ordering, b.cmp(a).reverse(), "{:?} {:?}", a, b);
+            assert_eq!(a == b, ordering == Ordering::Equal, "{:?} {:?}", a, b);
+            assert_eq!(a.partial_cmp(b), Some(ordering), "{:?} {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn sorts_into_a_total_order() {
+    let mut dates = all_dates();
+    dates.sort();
+
+    // Sorted means every earlier date is at most every later one, which fails if cmp isn't
+    // transitive
+    for (index, earlier) in dates.iter().enumerate() {
+        for later in dates[index..].iter() {
+            assert_ne!(
+                earlier.cmp(later),
+                Ordering::Greater,
+                "{:?} {:?}",
+                earlier,
+                later
+            );
+        }
+    }
+    // Missing parts sort last
+    assert_eq!(dates.last().unwrap().format(), None);
+}
+
+#[test]
+fn only_releasing_is_ongoing() {
+    let dates = all_dates();
+
+    for start in dates.iter() {
+        for end in dates.iter() {
+            let range = FuzzyDate::format_range(Some(start), Some(end), false);
+            assert!(!range.contains("Ongoing"), "{:?} {:?}", start, end);
+        }
+        let range = FuzzyDate::format_range(Some(start), None, true);
+        assert!(range.ends_with(" - Ongoing"), "{:?}", start);
+    }
+
+    // The status decides, not whether there is an end date
+    for status in STATUSES {
+        for (start, end) in [
+            (json!({"year": 2020, "month": 4}), json!({})),
+            (json!({"year": 2020}), json!({"year": 2021, "month": 3})),
+            (json!({}), json!({})),
+        ] {
+            let anime: Anime = serde_json::from_value(json!({
+                "title": {"romaji": "Cowboy Bebop"},
+                "status": status,
+                "startDate": start,
+                "endDate": end,
+                "genres": [],
+                "coverImage": {},
+                "siteUrl": "https://anilist.co/anime/1",
+                "tags": [],
+            }))
+            .unwrap();
+
+            assert_eq!(
+                anime.transform_date().contains("Ongoing"),
+                status == Some("RELEASING"),
+                "{:?} {} {}",
+                status,
+                start,
+                end
+            );
+        }
+    }
+}
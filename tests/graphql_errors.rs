@@ -0,0 +1,96 @@
+// AniList's errors payloads, recorded in fixtures/anilist/errors
+
+use annie_mai::{
+    commands::anime::queries::{FETCH_ANIME, FETCH_ANIME_BY_ID},
+    models::{
+        anilist_anime::Anime,
+        fetcher::{check_match, FetchError, FetchOptions},
+        graphql_error::{operation_name, ErrorLocation},
+        id_response::FetchResponse as IdResponse,
+        media_list_response::FetchResponse as MediaListResponse,
+        media_type::MediaType as Type,
+    },
+    utils::{message::NOT_FOUND_ANIME, providers::ProviderError},
+};
+use serde_json::Value;
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("fixtures/anilist/errors/{}.json", name))
+        .expect("Recorded errors payload")
+}
+
+#[test]
+fn errors_are_modelled() {
+    let response: IdResponse<Value> = serde_json::from_str(&fixture("invalid_variables")).unwrap();
+    let why = &response.errors[0];
+
+    assert!(why
+        .message
+        .starts_with("Variable \"$id\" got invalid value"));
+    assert_eq!(why.status, Some(400));
+    assert_eq!(
+        why.locations,
+        vec![ErrorLocation {
+            line: 1,
+            column: 17
+        }]
+    );
+    assert!(response.data.is_none());
+}
+
+#[test]
+fn not_found_is_the_not_found_reply() {
+    let response: IdResponse<Anime> = serde_json::from_str(&fixture("not_found")).unwrap();
+    let media = response.into_media(&FETCH_ANIME_BY_ID).unwrap();
+    let why = check_match(media, &FetchOptions::default()).unwrap_err();
+
+    assert_eq!(why, FetchError::NotFound);
+    assert_eq!(why.message(Type::Anime), NOT_FOUND_ANIME);
+}
+
+#[test]
+fn refused_queries_are_errors() {
+    for name in ["invalid_variables", "unknown_field"] {
+        let response: IdResponse<Anime> = serde_json::from_str(&fixture(name)).unwrap();
+        assert_eq!(
+            response.into_media(&FETCH_ANIME_BY_ID).unwrap_err(),
+            ProviderError::Rejected,
+            "{}",
+            name
+        );
+    }
+
+    let response: IdResponse<Anime> = serde_json::from_str(&fixture("rate_limited")).unwrap();
+    assert_eq!(
+        response.into_media(&FETCH_ANIME_BY_ID).unwrap_err(),
+        ProviderError::RateLimited
+    );
+}
+
+#[test]
+fn partial_data_is_kept() {
+    let response: IdResponse<Value> = serde_json::from_str(&fixture("partial_data")).unwrap();
+    let media = response.into_media(&FETCH_ANIME_BY_ID).unwrap().unwrap();
+
+    assert_eq!(media["id"], 5114);
+}
+
+#[test]
+fn searches_without_data_do_not_panic() {
+    let response: MediaListResponse<Anime> =
+        serde_json::from_str(&fixture("unknown_field")).unwrap();
+
+    assert!(response.no_results());
+    assert!(response.fuzzy_match("bebop", Type::Anime, false).is_none());
+    assert_eq!(
+        response.checked(&FETCH_ANIME).unwrap_err(),
+        ProviderError::Rejected
+    );
+}
+
+#[test]
+fn queries_are_named_for_the_logs() {
+    assert_eq!(operation_name(&FETCH_ANIME_BY_ID), "AnimeById");
+    assert_eq!(operation_name(&FETCH_ANIME), "AnimeSearch");
+    assert_eq!(operation_name("query { Media { id } }"), "anonymous query");
+}
@@ -0,0 +1,368 @@
+// Checks every AniList query against schema/anilist.json, so a typo in a field name fails here
+// instead of in production. UPDATE_SCHEMA=1 replaces the schema with whatever AniList's
+// introspection query answers, the file is never edited by hand
+
+use annie_mai::{
+    commands::{
+        anime::queries::{FETCH_ANIME, FETCH_ANIME_BY_ID},
+        manga::queries::{FETCH_MANGA, FETCH_MANGA_BY_ID},
+        quiz::queries::FETCH_QUIZ_ANIME,
+        watchorder::queries::FETCH_RELATIONS_BY_ID,
+    },
+    utils::graphql::Query,
+};
+use graphql_parser::query::{
+    parse_query, Definition, Document, OperationDefinition, Selection, SelectionSet, Type,
+    TypeCondition, Value as Argument,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+};
+
+const SCHEMA: &str = "schema/anilist.json";
+const ANILIST: &str = "https://graphql.anilist.co";
+
+// Saves AniList's answer to the standard introspection query as is
+fn update_schema() {
+    let query = fs::read_to_string("schema/introspection.graphql").expect("Introspection query");
+    let response = reqwest::blocking::Client::new()
+        .post(ANILIST)
+        .json(&json!({ "query": query }))
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json::<Value>())
+        .expect("AniList answered the introspection query");
+
+    fs::write(
+        SCHEMA,
+        serde_json::to_string_pretty(&response).unwrap() + "\n",
+    )
+    .expect("Saved schema");
+}
+
+struct Schema {
+    types: HashMap<String, Value>,
+}
+
+impl Schema {
+    fn load() -> Schema {
+        if env::var("UPDATE_SCHEMA").is_ok_and(|update| update == "1") {
+            update_schema();
+        }
+
+        let schema = fs::read_to_string(SCHEMA).expect("Vendored schema");
+        let schema: Value = serde_json::from_str(&schema).expect("Valid introspection JSON");
+        let types = schema
+            .pointer("/data/__schema/types")
+            .and_then(Value::as_array)
+            .expect("Introspection types")
+            .iter()
+            .map(|schema_type| {
+                (
+                    schema_type["name"].as_str().unwrap().to_string(),
+                    schema_type.clone(),
+                )
+            })
+            .collect();
+
+        Schema { types }
+    }
+
+    fn field<'a>(&'a self, type_name: &str, field: &str) -> Option<&'a Value> {
+        self.types[type_name]["fields"]
+            .as_array()?
+            .iter()
+            .find(|schema_field| schema_field["name"] == field)
+    }
+
+    fn kind(&self, type_name: &str) -> &str {
+        self.types[type_name]["kind"].as_str().unwrap_or_default()
+    }
+
+    fn has_enum_value(&self, type_name: &str, value: &str) -> bool {
+        self.types[type_name]["enumValues"]
+            .as_array()
+            .is_some_and(|values| values.iter().any(|enum_value| enum_value["name"] == value))
+    }
+}
+
+// The innermost type name of a type reference
+fn named_type(type_ref: &Value) -> &str {
+    match type_ref["name"].as_str() {
+        Some(name) => name,
+        None => named_type(&type_ref["ofType"]),
+    }
+}
+
+// Written the way a variable declares it, non-null marks left out
+fn written_type(type_ref: &Value) -> String {
+    match type_ref["kind"].as_str() {
+        Some("NON_NULL") => written_type(&type_ref["ofType"]),
+        Some("LIST") => format!("[{}]", written_type(&type_ref["ofType"])),
+        _ => named_type(type_ref).to_string(),
+    }
+}
+
+// The declared type of a variable written the same way, non-null marks left out
+fn declared_type(variable_type: &Type<String>) -> String {
+    match variable_type {
+        Type::NamedType(name) => name.to_string(),
+        Type::ListType(item) => format!("[{}]", declared_type(item)),
+        Type::NonNullType(inner) => declared_type(inner),
+    }
+}
+
+// The innermost type name a variable declares
+fn type_name<'a>(variable_type: &'a Type<String>) -> &'a str {
+    match variable_type {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => type_name(inner),
+    }
+}
+
+struct Validator<'a> {
+    schema: &'a Schema,
+    // Declared type of every variable
+    variables: HashMap<String, String>,
+    // Name to type condition and selection
+    fragments: HashMap<String, (String, &'a SelectionSet<'a, String>)>,
+    used_variables: HashSet<String>,
+    used_fragments: HashSet<String>,
+    errors: Vec<String>,
+}
+
+impl Validator<'_> {
+    fn argument(&mut self, path: &str, type_ref: &Value, argument: &Argument<String>) {
+        match argument {
+            Argument::Variable(name) => {
+                self.used_variables.insert(name.to_string());
+                match self.variables.get(name) {
+                    None => self
+                        .errors
+                        .push(format!("{}: ${} is not declared", path, name)),
+                    Some(declared) if *declared != written_type(type_ref) => {
+                        self.errors.push(format!(
+                            "{}: ${} is {} but the argument takes {}",
+                            path,
+                            name,
+                            declared,
+                            written_type(type_ref)
+                        ))
+                    }
+                    Some(_) => (),
+                }
+            }
+            Argument::List(items) => match type_ref["kind"].as_str() {
+                Some("NON_NULL") => self.argument(path, &type_ref["ofType"], argument),
+                Some("LIST") => items
+                    .iter()
+                    .for_each(|item| self.argument(path, &type_ref["ofType"], item)),
+                _ => self.errors.push(format!("{}: takes a single value", path)),
+            },
+            literal => {
+                let type_name = named_type(type_ref);
+                let valid = match (self.schema.kind(type_name), literal) {
+                    ("ENUM", Argument::Enum(value)) => self.schema.has_enum_value(type_name, value),
+                    (_, Argument::Boolean(_)) => type_name == "Boolean",
+                    (_, Argument::Int(_)) => type_name == "Int" || type_name == "Float",
+                    (_, Argument::Float(_)) => type_name == "Float",
+                    (_, Argument::String(_)) => type_name == "String",
+                    _ => false,
+                };
+                if !valid {
+                    self.errors.push(format!(
+                        "{}: {} is not a valid {}",
+                        path, literal, type_name
+                    ));
+                }
+            }
+        }
+    }
+
+    fn selection(&mut self, path: &str, type_name: &str, selection: &SelectionSet<String>) {
+        for item in selection.items.iter() {
+            match item {
+                Selection::FragmentSpread(spread) => {
+                    let name = &spread.fragment_name;
+                    let Some((on, fragment)) = self.fragments.get(name).cloned() else {
+                        self.errors
+                            .push(format!("{}: fragment {} is not defined", path, name));
+                        continue;
+                    };
+                    if on != type_name {
+                        self.errors.push(format!(
+                            "{}: fragment {} is on {}, not {}",
+                            path, name, on, type_name
+                        ));
+                        continue;
+                    }
+                    self.used_fragments.insert(name.to_string());
+                    self.selection(&format!("{}...{}", path, name), type_name, fragment);
+                }
+                Selection::InlineFragment(_) => self
+                    .errors
+                    .push(format!("{}: inline fragments are not checked", path)),
+                Selection::Field(field) => {
+                    let name = &field.name;
+                    let field_path = format!("{}.{}", path, name);
+                    let Some(schema_field) = self.schema.field(type_name, name) else {
+                        self.errors.push(format!(
+                            "{}: {} has no field {}",
+                            field_path, type_name, name
+                        ));
+                        continue;
+                    };
+                    for (argument, value) in field.arguments.iter() {
+                        let schema_argument =
+                            schema_field["args"].as_array().and_then(|schema_args| {
+                                schema_args
+                                    .iter()
+                                    .find(|schema_arg| schema_arg["name"] == *argument)
+                            });
+                        match schema_argument {
+                            Some(schema_argument) => self.argument(
+                                &format!("{}({})", field_path, argument),
+                                &schema_argument["type"],
+                                value,
+                            ),
+                            None => self
+                                .errors
+                                .push(format!("{}: unknown argument {}", field_path, argument)),
+                        }
+                    }
+
+                    let field_type = named_type(&schema_field["type"]);
+                    let has_selection = !field.selection_set.items.is_empty();
+                    match (self.schema.kind(field_type), has_selection) {
+                        ("OBJECT", false) => self
+                            .errors
+                            .push(format!("{}: {} needs a selection", field_path, field_type)),
+                        ("OBJECT", true) => {
+                            self.selection(&field_path, field_type, &field.selection_set)
+                        }
+                        (_, true) => self
+                            .errors
+                            .push(format!("{}: {} has no fields", field_path, field_type)),
+                        (_, false) => (),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn validate(schema: &Schema, query: &str) -> Vec<String> {
+    let document: Document<String> = match parse_query(query) {
+        Ok(document) => document,
+        Err(why) => return vec![format!("does not parse: {}", why)],
+    };
+    let mut validator = Validator {
+        schema,
+        variables: HashMap::new(),
+        fragments: HashMap::new(),
+        used_variables: HashSet::new(),
+        used_fragments: HashSet::new(),
+        errors: vec![],
+    };
+
+    let mut operations = vec![];
+    for definition in document.definitions.iter() {
+        match definition {
+            Definition::Operation(OperationDefinition::Query(query)) => operations.push(query),
+            Definition::Operation(_) => validator
+                .errors
+                .push("only queries are sent to AniList".to_string()),
+            Definition::Fragment(fragment) => {
+                let TypeCondition::On(on) = &fragment.type_condition;
+                validator.fragments.insert(
+                    fragment.name.to_string(),
+                    (on.to_string(), &fragment.selection_set),
+                );
+            }
+        }
+    }
+    let [operation] = operations[..] else {
+        return vec![format!("has {} queries instead of one", operations.len())];
+    };
+
+    for variable in operation.variable_definitions.iter() {
+        let name = type_name(&variable.var_type);
+        if !schema.types.contains_key(name) {
+            validator.errors.push(format!(
+                "${} has unknown type {}",
+                variable.name, variable.var_type
+            ));
+        }
+        validator
+            .variables
+            .insert(variable.name.to_string(), declared_type(&variable.var_type));
+    }
+    validator.selection("Query", "Query", &operation.selection_set);
+
+    // AniList rejects documents with fragments nothing spreads
+    for name in validator.fragments.keys() {
+        if !validator.used_fragments.contains(name) {
+            validator
+                .errors
+                .push(format!("fragment {} is never used", name));
+        }
+    }
+    for name in validator.variables.keys() {
+        if !validator.used_variables.contains(name) {
+            validator.errors.push(format!("${} is never used", name));
+        }
+    }
+
+    validator.errors
+}
+
+#[test]
+fn queries_match_the_schema() {
+    let schema = Schema::load();
+    let queries: [(&str, &str); 6] = [
+        ("FETCH_ANIME_BY_ID", &FETCH_ANIME_BY_ID),
+        ("FETCH_ANIME", &FETCH_ANIME),
+        ("FETCH_MANGA_BY_ID", &FETCH_MANGA_BY_ID),
+        ("FETCH_MANGA", &FETCH_MANGA),
+        ("FETCH_RELATIONS_BY_ID", &FETCH_RELATIONS_BY_ID),
+        ("FETCH_QUIZ_ANIME", &FETCH_QUIZ_ANIME),
+    ];
+
+    for (name, query) in queries {
+        let errors = validate(&schema, query);
+        assert!(
+            errors.is_empty(),
+            "{} does not match the schema: {:#?}",
+            name,
+            errors
+        );
+    }
+}
+
+#[test]
+fn typos_are_caught() {
+    let schema = Schema::load();
+    let query = Query::new("Typos", "Media(id: $id, type: ANIMU)")
+        .variable("id", "String")
+        .field("episodez")
+        .field("title")
+        .field("characters(sort: [ROLE], perPage: 5) { ...Characters }")
+        .build();
+
+    let errors = validate(&schema, &query);
+    assert!(errors
+        .iter()
+        .any(|why| why.contains("ANIMU is not a valid MediaType")));
+    assert!(errors.iter().any(|why| why.contains("$id is String")));
+    assert!(errors
+        .iter()
+        .any(|why| why.contains("Media has no field episodez")));
+    assert!(errors
+        .iter()
+        .any(|why| why.contains("MediaTitle needs a selection")));
+    assert!(errors
+        .iter()
+        .any(|why| why.contains("fragment Characters is not defined")));
+}
@@ -0,0 +1,232 @@
+// Season and part hints in searches, see models/installment.rs, and how lookups follow them
+// through a franchise served from memory
+
+use annie_mai::{
+    models::{
+        anilist_anime::Anime,
+        fetcher::FetchOptions,
+        installment::{japanese_season, ordinal, Installment},
+        mal_response::MalResponse,
+        media_query::QueryTarget,
+        media_type::MediaType as Type,
+        transformers::Transformers,
+    },
+    utils::{
+        id_mapper,
+        providers::{MetadataProvider, ProviderError},
+        response_fetcher::{traced_lookup_with, MatchPath},
+    },
+};
+use serde_json::{json, Value};
+use std::sync::Once;
+
+#[test]
+fn reads_ordinals() {
+    let ordinals = [
+        ("2", 2),
+        ("２", 2),
+        ("2nd", 2),
+        ("3rd", 3),
+        ("4th", 4),
+        ("second", 2),
+        ("Third", 3),
+        ("II", 2),
+        ("iv", 4),
+        ("二", 2),
+        ("ニ", 2),
+        ("十", 10),
+    ];
+
+    for (token, number) in ordinals {
+        assert_eq!(ordinal(token), Some(number), "{:?}", token);
+    }
+    for token in ["", "nd", "2x", "titan", "二十"] {
+        assert_eq!(ordinal(token), None, "{:?}", token);
+    }
+}
+
+#[test]
+fn reads_japanese_seasons() {
+    assert_eq!(japanese_season("2期"), Some(("", 2)));
+    assert_eq!(japanese_season("第2期"), Some(("", 2)));
+    assert_eq!(japanese_season("二期"), Some(("", 2)));
+    assert_eq!(japanese_season("ニ期"), Some(("", 2)));
+    assert_eq!(japanese_season("進撃の巨人3期"), Some(("進撃の巨人", 3)));
+    assert_eq!(japanese_season("進撃の巨人第3期"), Some(("進撃の巨人", 3)));
+    assert_eq!(japanese_season("期"), None);
+    assert_eq!(japanese_season("進撃の巨人"), None);
+}
+
+fn installment(title: &str, season: u32, part: u32) -> Option<Installment> {
+    Some(Installment {
+        title: title.to_string(),
+        season,
+        part,
+    })
+}
+
+#[test]
+fn parses_installments() {
+    let searches = [
+        (
+            "attack on titan season 3 part 2",
+            installment("attack on titan", 3, 2),
+        ),
+        (
+            "attack on titan 3rd season",
+            installment("attack on titan", 3, 1),
+        ),
+        ("Attack on Titan S2", installment("Attack on Titan", 2, 1)),
+        (
+            "shingeki no kyojin 3 part 2",
+            installment("shingeki no kyojin", 3, 2),
+        ),
+        ("mob psycho 3", installment("mob psycho", 3, 1)),
+        ("mob psycho III", installment("mob psycho", 3, 1)),
+        ("re zero second season", installment("re zero", 2, 1)),
+        ("re zero 2nd cour", installment("re zero", 1, 2)),
+        ("spice and wolf ii", installment("spice and wolf", 2, 1)),
+        ("進撃の巨人 2期", installment("進撃の巨人", 2, 1)),
+        ("進撃の巨人ニ期", installment("進撃の巨人", 2, 1)),
+        ("ワンパンマン2", installment("ワンパンマン", 2, 1)),
+        (
+            "attack on titan season 1",
+            installment("attack on titan", 1, 1),
+        ),
+        ("mob psycho 100", None),
+        ("steins gate 0", None),
+        ("spy x family", None),
+        ("86", None),
+        ("Steins;Gate 0", None),
+        ("ワンパンマン", None),
+        ("season 2", None),
+        ("cowboy bebop", None),
+    ];
+
+    for (search, expected) in searches {
+        assert_eq!(Installment::parse(search), expected, "{:?}", search);
+    }
+}
+
+// (id, romaji title, format, year, sequels)
+type Entry = (u32, &'static str, &'static str, u32, &'static [u32]);
+
+// Searches match every title containing the search, like a very forgiving AniList
+struct Franchise(&'static [Entry]);
+
+impl Franchise {
+    fn media(&self, entry: &Entry) -> Value {
+        let (id, title, format, year, sequels) = *entry;
+        let sequels: Vec<&Entry> = self
+            .0
+            .iter()
+            .filter(|other| sequels.contains(&other.0))
+            .collect();
+
+        json!({
+            "type": "ANIME",
+            "id": id,
+            "title": {"romaji": title},
+            "format": format,
+            "startDate": {"year": year},
+            "genres": [],
+            "coverImage": {},
+            "siteUrl": format!("https://anilist.co/anime/{}", id),
+            "tags": [],
+            "relations": {
+                "edges": sequels.iter().map(|_| json!({"relationType": "SEQUEL"})).collect::<Vec<Value>>(),
+                "nodes": sequels.iter().map(|(id, title, format, year, _)| json!({
+                    "id": id,
+                    "type": "ANIME",
+                    "format": format,
+                    "title": {"romaji": title},
+                    "startDate": {"year": year},
+                })).collect::<Vec<Value>>(),
+            },
+        })
+    }
+}
+
+impl MetadataProvider for Franchise {
+    fn name(&self) -> &'static str {
+        "Franchise"
+    }
+
+    fn search(&self, _: Type, search: &str, _: &FetchOptions) -> Result<Vec<Value>, ProviderError> {
+        let search = search.to_lowercase();
+        Ok(self
+            .0
+            .iter()
+            .filter(|(_, title, _, _, _)| title.to_lowercase().contains(&search))
+            .map(|entry| self.media(entry))
+            .collect())
+    }
+
+    fn get_by_id(&self, _: Type, target: &QueryTarget) -> Result<Option<Value>, ProviderError> {
+        let QueryTarget::AniList(id) = target else {
+            return Err(ProviderError::Unsupported);
+        };
+        Ok(self
+            .0
+            .iter()
+            .find(|entry| entry.0 == *id)
+            .map(|entry| self.media(entry)))
+    }
+
+    fn songs(&self, _: u32) -> Result<MalResponse, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+const MONOGATARI: &[Entry] = &[
+    (5081, "Bakemonogatari", "TV", 2009, &[17074]),
+    (
+        17074,
+        "Monogatari Series: Second Season",
+        "TV",
+        2013,
+        &[20918],
+    ),
+    (20918, "Owarimonogatari", "TV", 2015, &[]),
+];
+
+const SHINGEKI: &[Entry] = &[
+    (16498, "Shingeki no Kyojin", "TV", 2013, &[18397, 20958]),
+    (18397, "Shingeki no Kyojin OVA", "OVA", 2013, &[]),
+    (20958, "Shingeki no Kyojin 2", "TV", 2017, &[]),
+];
+
+fn lookup(franchise: &'static [Entry], search: &str) -> (Option<u32>, MatchPath) {
+    // Only the franchise answers, not whatever title dataset the machine has
+    static NO_DATASET: Once = Once::new();
+    NO_DATASET.call_once(|| {
+        id_mapper::use_dataset(r#"{"data": []}"#).unwrap();
+    });
+
+    let providers: Vec<Box<dyn MetadataProvider>> = vec![Box::new(Franchise(franchise))];
+    let (anime, trace) =
+        traced_lookup_with::<Anime>(&providers, Type::Anime, search, FetchOptions::default())
+            .unwrap();
+    (anime.get_id(), trace.path)
+}
+
+#[test]
+fn exact_titles_are_not_walked() {
+    // "Second Season" is part of the title, not the second season of "Monogatari Series:"
+    assert_eq!(
+        lookup(MONOGATARI, "Monogatari Series: Second Season"),
+        (Some(17074), MatchPath::Search)
+    );
+}
+
+#[test]
+fn seasons_follow_sequels() {
+    assert_eq!(
+        lookup(SHINGEKI, "shingeki no kyojin second season"),
+        (Some(20958), MatchPath::Sequel)
+    );
+    assert_eq!(
+        lookup(SHINGEKI, "shingeki no kyojin"),
+        (Some(16498), MatchPath::Search)
+    );
+}
@@ -0,0 +1,25 @@
+// Japanese input handling, see utils/japanese.rs
+
+use annie_mai::utils::japanese::{normalize_width, search_forms, split_scripts};
+
+#[test]
+fn normalizes_character_width() {
+    assert_eq!(normalize_width("ＳＰＹ×ＦＡＭＩＬＹ"), "SPY×FAMILY");
+    assert_eq!(normalize_width("ﾄﾗﾄﾞﾗ!"), "トラドラ!");
+    assert_eq!(normalize_width("進撃の巨人　２期"), "進撃の巨人 2期");
+}
+
+#[test]
+fn splits_japanese_from_latin() {
+    assert_eq!(split_scripts("ワンパンマン2"), "ワンパンマン 2");
+    assert_eq!(split_scripts("進撃の巨人Season2"), "進撃の巨人 Season2");
+    assert_eq!(split_scripts("SPY×FAMILY"), "SPY×FAMILY");
+}
+
+#[test]
+fn searches_kana_as_romaji_too() {
+    assert_eq!(search_forms("cowboy bebop"), ["cowboy bebop"]);
+    assert_eq!(search_forms("とらドラ"), ["とらドラ", "toradora"]);
+    // Kanji have no romaji, half converted titles only find the wrong things
+    assert_eq!(search_forms("進撃の巨人"), ["進撃の巨人"]);
+}
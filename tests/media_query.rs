@@ -0,0 +1,253 @@
+// Flags, links and ids in the command arguments, see models/media_query.rs
+
+use annie_mai::models::{
+    media_format::MediaFormat,
+    media_query::{parse_url, MediaQuery, QueryError, QueryTarget},
+    media_type::MediaType::{self, Anime, Manga},
+};
+
+fn target(input: &str, media_type: MediaType) -> Result<QueryTarget, QueryError> {
+    MediaQuery::parse(input, media_type).map(|query| query.target)
+}
+
+fn search(text: &str) -> QueryTarget {
+    QueryTarget::Search(text.to_string())
+}
+
+#[test]
+fn reads_flags() {
+    let query = MediaQuery::parse("kimi no na wa --year 2016 --format movie", Anime).unwrap();
+    assert_eq!(query.target, search("kimi no na wa"));
+    assert_eq!(query.year, Some(2016));
+    assert_eq!(query.formats, vec![MediaFormat::Movie]);
+
+    let query = MediaQuery::parse("--YEAR=2016 --format=tv,ova bebop", Anime).unwrap();
+    assert_eq!(query.target, search("bebop"));
+    assert_eq!(query.year, Some(2016));
+    assert_eq!(query.formats, vec![MediaFormat::Tv, MediaFormat::Ova]);
+
+    let query = MediaQuery::parse("berserk --format novel,one-shot", Manga).unwrap();
+    assert_eq!(
+        query.formats,
+        vec![MediaFormat::Novel, MediaFormat::OneShot]
+    );
+
+    assert_eq!(target("--mal 5114", Anime), Ok(QueryTarget::Mal(5114)));
+    assert_eq!(target("--mal=5114", Anime), Ok(QueryTarget::Mal(5114)));
+}
+
+#[test]
+fn rejects_missing_and_invalid_flag_values() {
+    let errors = [
+        ("bebop --year", QueryError::MissingValue("--year")),
+        ("bebop --year=", QueryError::MissingValue("--year")),
+        ("bebop --format", QueryError::MissingValue("--format")),
+        ("--mal", QueryError::MissingValue("--mal")),
+        ("--url", QueryError::MissingValue("--url")),
+        ("bebop --year 98", QueryError::InvalidYear),
+        ("bebop --year 1800", QueryError::InvalidYear),
+        ("bebop --year=next", QueryError::InvalidYear),
+        ("--mal abc", QueryError::InvalidMalId),
+        ("--mal=-1", QueryError::InvalidMalId),
+        ("--url https://example.com/anime/1", QueryError::UnknownUrl),
+        ("bebop --format nonsense", QueryError::InvalidFormat),
+        ("bebop --format=tv,nonsense", QueryError::InvalidFormat),
+        ("bebop --format manga", QueryError::WrongMediaType),
+        ("bebop --format=tv,ova,manga", QueryError::WrongMediaType),
+        ("", QueryError::Empty),
+        ("--year 2019", QueryError::Empty),
+    ];
+
+    for (input, error) in errors {
+        assert_eq!(target(input, Anime), Err(error), "{:?}", input);
+    }
+}
+
+#[test]
+fn reads_links() {
+    let links = [
+        (
+            "https://anilist.co/anime/1/cowboy-bebop/",
+            QueryTarget::AniList(1),
+        ),
+        ("<https://anilist.co/anime/1>", QueryTarget::AniList(1)),
+        ("anilist.co/anime/21", QueryTarget::AniList(21)),
+        (
+            "https://myanimelist.net/anime/5114/Fullmetal_Alchemist",
+            QueryTarget::Mal(5114),
+        ),
+        (
+            "https://www.myanimelist.net/anime.php?id=5114",
+            QueryTarget::Mal(5114),
+        ),
+        (
+            "http://myanimelist.net/anime/5114?q=x#top",
+            QueryTarget::Mal(5114),
+        ),
+        (
+            "https://kitsu.app/anime/cowboy-bebop",
+            QueryTarget::Kitsu("cowboy-bebop".to_string()),
+        ),
+        (
+            "https://kitsu.io/anime/1",
+            QueryTarget::Kitsu("1".to_string()),
+        ),
+        ("https://anidb.net/anime/69", QueryTarget::AniDb(69)),
+        ("https://anidb.net/a69", QueryTarget::AniDb(69)),
+        (
+            "https://anidb.net/perl-bin/animedb.pl?show=anime&aid=69",
+            QueryTarget::AniDb(69),
+        ),
+    ];
+
+    for (link, expected) in links {
+        assert_eq!(parse_url(link, Anime), Ok(expected.clone()), "{:?}", link);
+        assert_eq!(target(link, Anime), Ok(expected.clone()), "{:?}", link);
+        assert_eq!(
+            target(&format!("--url {}", link), Anime),
+            Ok(expected),
+            "{:?}",
+            link
+        );
+    }
+
+    assert_eq!(
+        parse_url("https://myanimelist.net/manga.php?id=2", Manga),
+        Ok(QueryTarget::Mal(2))
+    );
+}
+
+#[test]
+fn rejects_unusable_links() {
+    let errors = [
+        (
+            "https://anilist.co/anime/1",
+            Manga,
+            QueryError::WrongMediaType,
+        ),
+        (
+            "https://myanimelist.net/manga/2",
+            Anime,
+            QueryError::WrongMediaType,
+        ),
+        (
+            "https://myanimelist.net/manga.php?id=2",
+            Anime,
+            QueryError::WrongMediaType,
+        ),
+        (
+            "https://anidb.net/anime/69",
+            Manga,
+            QueryError::WrongMediaType,
+        ),
+        (
+            "https://anilist.co/user/someone",
+            Anime,
+            QueryError::UnknownUrl,
+        ),
+        (
+            "https://anilist.co/anime/abc",
+            Anime,
+            QueryError::UnknownUrl,
+        ),
+        (
+            "https://myanimelist.net/anime.php",
+            Anime,
+            QueryError::UnknownUrl,
+        ),
+        (
+            "https://anidb.net/perl-bin/animedb.pl?show=main",
+            Anime,
+            QueryError::UnknownUrl,
+        ),
+        ("https://example.com/anime/1", Anime, QueryError::UnknownUrl),
+    ];
+
+    for (link, media_type, error) in errors {
+        assert_eq!(target(link, media_type), Err(error), "{:?}", link);
+    }
+}
+
+#[test]
+fn reads_ids() {
+    let ids = [
+        ("1", QueryTarget::AniList(1)),
+        (" 21 ", QueryTarget::AniList(21)),
+        ("anilist:21", QueryTarget::AniList(21)),
+        ("mal:5114", QueryTarget::Mal(5114)),
+        ("idMal:5114", QueryTarget::Mal(5114)),
+        ("anidb:69", QueryTarget::AniDb(69)),
+        (
+            "kitsu:Cowboy-Bebop",
+            QueryTarget::Kitsu("cowboy-bebop".to_string()),
+        ),
+    ];
+    for (input, expected) in ids {
+        assert_eq!(target(input, Anime), Ok(expected), "{:?}", input);
+    }
+
+    for (input, error) in [
+        ("mal:abc", QueryError::InvalidMalId),
+        ("anidb:", QueryError::InvalidId),
+        ("kitsu:", QueryError::InvalidId),
+        ("anilist:one", QueryError::InvalidId),
+    ] {
+        assert_eq!(target(input, Anime), Err(error), "{:?}", input);
+    }
+}
+
+#[test]
+fn rejects_more_than_one_target() {
+    for input in [
+        "mal:5114 --mal 5114",
+        "https://anilist.co/anime/1 https://anilist.co/anime/5",
+        "--url https://anilist.co/anime/1 anidb:69",
+    ] {
+        assert_eq!(
+            target(input, Anime),
+            Err(QueryError::ConflictingTargets),
+            "{:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn rejects_words_next_to_a_target() {
+    for input in [
+        "https://anilist.co/anime/1 cowboy bebop",
+        "bebop mal:1",
+        "--mal 1 the movie",
+    ] {
+        assert_eq!(
+            target(input, Anime),
+            Err(QueryError::ExtraText),
+            "{:?}",
+            input
+        );
+    }
+
+    // Flags still go with a link
+    let query = MediaQuery::parse("mal:1 --year 1998", Anime).unwrap();
+    assert_eq!(query.target, QueryTarget::Mal(1));
+    assert_eq!(query.year, Some(1998));
+}
+
+#[test]
+fn keeps_titles_with_colons_and_slashes() {
+    let titles = [
+        "Re:Zero kara Hajimeru Isekai Seikatsu",
+        "Steins;Gate 0",
+        "Code Geass: Hangyaku no Lelouch",
+        "Fate/Zero",
+        "Fate/stay night: Unlimited Blade Works",
+        "86: Eighty Six",
+        "Kaguya-sama wa Kokurasetai? Tensai-tachi no Renai Zunousen",
+    ];
+
+    for title in titles {
+        assert_eq!(target(title, Anime), Ok(search(title)), "{:?}", title);
+    }
+    // Only a bare number is an id
+    assert_eq!(target("86", Anime), Ok(QueryTarget::AniList(86)));
+}
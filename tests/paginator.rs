@@ -0,0 +1,38 @@
+// Page numbers in the footer of paged embeds, see utils/paginator.rs
+
+use annie_mai::utils::paginator::numbered;
+use serenity::builder::CreateEmbed;
+
+fn footer(embed: &CreateEmbed) -> Option<&str> {
+    embed.0.get("footer")?["text"].as_str()
+}
+
+#[test]
+fn keeps_the_footer_before_the_page_number() {
+    let mut embed = CreateEmbed::default();
+    embed.footer(|f| {
+        f.text("Cowboy Bebop")
+            .icon_url("https://anilist.co/favicon.ico")
+    });
+
+    let page = numbered(embed, 1, 3);
+    assert_eq!(footer(&page), Some("Cowboy Bebop • Page 2/3"));
+    assert_eq!(
+        page.0["footer"]["icon_url"].as_str(),
+        Some("https://anilist.co/favicon.ico")
+    );
+}
+
+#[test]
+fn numbers_pages_without_a_footer() {
+    assert_eq!(
+        footer(&numbered(CreateEmbed::default(), 0, 2)),
+        Some("Page 1/2")
+    );
+
+    // A single page has nothing to number
+    let mut embed = CreateEmbed::default();
+    embed.footer(|f| f.text("Cowboy Bebop"));
+    assert_eq!(footer(&numbered(embed, 0, 1)), Some("Cowboy Bebop"));
+    assert_eq!(footer(&numbered(CreateEmbed::default(), 0, 1)), None);
+}
@@ -0,0 +1,600 @@
+// Recorded Jikan and Kitsu payloads through the mappers into AniList's shape, the providers
+// against local mocks, and the failover between providers
+
+mod common;
+
+use annie_mai::{
+    models::{
+        anilist_anime::Anime,
+        anilist_manga::Manga,
+        fetcher::{FetchError, FetchOptions},
+        fuzzy_date::FuzzyDate,
+        mal_response::MalResponse,
+        media_format::MediaFormat,
+        media_query::QueryError,
+        media_query::QueryTarget,
+        media_type::MediaType as Type,
+        transformers::Transformers,
+    },
+    utils::{
+        id_mapper::{self, id_mapper},
+        providers::{
+            anilist::AniList,
+            jikan::{self, Jikan},
+            kitsu::{self, Kitsu},
+            with_failover, MetadataProvider, ProviderError,
+        },
+        response_fetcher::{traced_lookup_with, MatchPath},
+    },
+};
+use common::{
+    http::{serve, Reply, Request},
+    replay,
+};
+use serde_json::{json, Value};
+use std::sync::{Once, OnceLock};
+
+// The id mapper is swapped for one built from the small copy of the dataset, once
+fn use_offline_dataset() {
+    static DATASET: Once = Once::new();
+    DATASET.call_once(|| {
+        let dataset = std::fs::read_to_string("fixtures/offline/anime-offline-database.json")
+            .expect("Offline dataset fixture");
+        id_mapper::use_dataset(&dataset).expect("Valid offline dataset");
+    });
+}
+
+fn fixture(path: &str) -> Value {
+    let json =
+        std::fs::read_to_string(format!("fixtures/providers/{}", path)).expect("Provider fixture");
+    serde_json::from_str(&json).expect("Valid provider fixture")
+}
+
+fn date(date: Option<FuzzyDate>) -> (Option<u32>, Option<u32>, Option<u32>) {
+    let date = date.unwrap_or_default();
+    (date.year, date.month, date.day)
+}
+
+#[test]
+fn maps_jikan_anime() {
+    use_offline_dataset();
+    let mapped = jikan::to_anilist(Type::Anime, &fixture("jikan/anime_5114.json")["data"])
+        .expect("Mapped anime");
+    let anime: Anime = serde_json::from_value(mapped).expect("Anime in AniList's shape");
+
+    assert_eq!(anime.get_id(), Some(5114));
+    assert_eq!(anime.get_mal_id(), Some(5114));
+    assert_eq!(
+        anime.get_romaji_title().as_deref(),
+        Some("Fullmetal Alchemist: Brotherhood")
+    );
+    assert_eq!(anime.get_format().as_deref(), Some("TV"));
+    assert_eq!(anime.get_status().as_deref(), Some("FINISHED"));
+    assert_eq!(date(anime.get_start_date()), (Some(2009), Some(4), Some(5)));
+    assert_eq!(date(anime.get_end_date()), (Some(2010), Some(7), Some(4)));
+    assert_eq!(anime.get_average_score(), Some(91));
+    assert_eq!(anime.get_source().as_deref(), Some("MANGA"));
+    assert!(!anime.get_is_adult());
+}
+
+#[test]
+fn maps_jikan_manga() {
+    use_offline_dataset();
+    let mapped = jikan::to_anilist(Type::Manga, &fixture("jikan/manga_2.json")["data"])
+        .expect("Mapped manga");
+    let manga: Manga = serde_json::from_value(mapped).expect("Manga in AniList's shape");
+
+    // The dataset has no manga, so there is no AniList id to give it
+    assert_eq!(manga.get_id(), None);
+    assert_eq!(manga.get_mal_id(), Some(2));
+    assert_eq!(manga.get_romaji_title().as_deref(), Some("Berserk"));
+    assert_eq!(manga.get_format().as_deref(), Some("MANGA"));
+    assert_eq!(manga.get_status().as_deref(), Some("RELEASING"));
+    assert_eq!(
+        date(manga.get_start_date()),
+        (Some(1989), Some(8), Some(25))
+    );
+    assert_eq!(date(manga.get_end_date()), (None, None, None));
+    assert_eq!(manga.get_average_score(), Some(95));
+}
+
+#[test]
+fn maps_kitsu_anime() {
+    use_offline_dataset();
+    let mapped = kitsu::to_anilist(
+        Type::Anime,
+        &fixture("kitsu/anime_cowboy-bebop.json")["data"][0],
+    )
+    .expect("Mapped anime");
+    let anime: Anime = serde_json::from_value(mapped).expect("Anime in AniList's shape");
+
+    assert_eq!(anime.get_id(), Some(1));
+    assert_eq!(anime.get_mal_id(), Some(1));
+    assert_eq!(anime.get_romaji_title().as_deref(), Some("Cowboy Bebop"));
+    assert_eq!(
+        anime.get_native_title().as_deref(),
+        Some("カウボーイビバップ")
+    );
+    assert_eq!(anime.get_format().as_deref(), Some("TV"));
+    assert_eq!(anime.get_status().as_deref(), Some("FINISHED"));
+    assert_eq!(date(anime.get_start_date()), (Some(1998), Some(4), Some(3)));
+    assert_eq!(date(anime.get_end_date()), (Some(1999), Some(4), Some(24)));
+    assert_eq!(anime.get_average_score(), Some(82));
+    assert_eq!(anime.get_site_url(), "https://kitsu.app/anime/cowboy-bebop");
+}
+
+#[test]
+fn skips_entries_without_an_id() {
+    assert!(jikan::to_anilist(Type::Anime, &Value::Null).is_none());
+    assert!(kitsu::to_anilist(Type::Anime, &Value::Null).is_none());
+}
+
+// Answers every call with the same result
+struct Fake {
+    result: Result<Option<Value>, ProviderError>,
+}
+
+impl Fake {
+    fn boxed(result: Result<Option<Value>, ProviderError>) -> Box<dyn MetadataProvider> {
+        Box::new(Fake { result })
+    }
+}
+
+impl MetadataProvider for Fake {
+    fn name(&self) -> &'static str {
+        "Fake"
+    }
+
+    fn search(&self, _: Type, _: &str, _: &FetchOptions) -> Result<Vec<Value>, ProviderError> {
+        self.result.clone().map(|media| media.into_iter().collect())
+    }
+
+    fn get_by_id(&self, _: Type, _: &QueryTarget) -> Result<Option<Value>, ProviderError> {
+        self.result.clone()
+    }
+
+    fn songs(&self, _: u32) -> Result<MalResponse, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+fn get_by_id(providers: &[Box<dyn MetadataProvider>]) -> Result<Option<Value>, ProviderError> {
+    with_failover(providers, |provider| {
+        provider.get_by_id(Type::Anime, &QueryTarget::AniList(1))
+    })
+}
+
+#[test]
+fn fails_over_to_the_next_provider() {
+    let answer = Some(json!({"id": 1}));
+    let providers = [
+        Fake::boxed(Err(ProviderError::RateLimited)),
+        Fake::boxed(Err(ProviderError::Unsupported)),
+        Fake::boxed(Ok(answer.clone())),
+        Fake::boxed(Ok(None)),
+    ];
+
+    assert_eq!(get_by_id(&providers), Ok(answer));
+}
+
+#[test]
+fn first_answer_wins_even_when_empty() {
+    let providers = [
+        Fake::boxed(Ok(None)),
+        Fake::boxed(Ok(Some(json!({"id": 1})))),
+    ];
+
+    assert_eq!(get_by_id(&providers), Ok(None));
+}
+
+#[test]
+fn reports_the_last_real_failure() {
+    let providers = [
+        Fake::boxed(Err(ProviderError::Unavailable)),
+        Fake::boxed(Err(ProviderError::Unsupported)),
+    ];
+    assert_eq!(get_by_id(&providers), Err(ProviderError::Unavailable));
+
+    // Nobody could answer at all
+    let providers = [
+        Fake::boxed(Err(ProviderError::Unsupported)),
+        Fake::boxed(Err(ProviderError::Unsupported)),
+    ];
+    assert_eq!(get_by_id(&providers), Err(ProviderError::Unsupported));
+    assert_eq!(get_by_id(&[]), Err(ProviderError::Unsupported));
+}
+
+// Answers by id from the Hunter x Hunter entries, searching fails the lookup
+struct HunterById;
+
+impl MetadataProvider for HunterById {
+    fn name(&self) -> &'static str {
+        "HunterById"
+    }
+
+    fn search(&self, _: Type, _: &str, _: &FetchOptions) -> Result<Vec<Value>, ProviderError> {
+        Err(ProviderError::Unavailable)
+    }
+
+    fn get_by_id(&self, _: Type, target: &QueryTarget) -> Result<Option<Value>, ProviderError> {
+        let media = |id: u32, title: &str, popularity: u32| {
+            json!({
+                "type": "ANIME",
+                "id": id,
+                "title": {"romaji": title},
+                "genres": [],
+                "coverImage": {},
+                "siteUrl": format!("https://anilist.co/anime/{}", id),
+                "tags": [],
+                "popularity": popularity,
+            })
+        };
+        Ok(match target {
+            QueryTarget::AniList(136) => Some(media(136, "HUNTER×HUNTER", 180000)),
+            QueryTarget::AniList(11061) => Some(media(11061, "HUNTER×HUNTER (2011)", 800000)),
+            _ => None,
+        })
+    }
+
+    fn songs(&self, _: u32) -> Result<MalResponse, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+#[test]
+fn picks_from_the_shortlist_the_index_could_not_pick_from() {
+    use_offline_dataset();
+    // Both Hunter x Hunter entries match the search as well as each other
+    let providers: Vec<Box<dyn MetadataProvider>> = vec![Box::new(HunterById)];
+
+    let (anime, trace) = traced_lookup_with::<Anime>(
+        &providers,
+        Type::Anime,
+        "hunter x hunter",
+        FetchOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(trace.path, MatchPath::Shortlist);
+    // The more popular one
+    assert_eq!(anime.get_id(), Some(11061));
+
+    let did_you_mean: Vec<Option<u32>> = trace
+        .did_you_mean(anime.get_id())
+        .into_iter()
+        .map(|close_call| close_call.id)
+        .collect();
+    assert_eq!(did_you_mean, vec![Some(136)]);
+}
+
+#[test]
+fn searches_when_the_index_has_nothing_close() {
+    use_offline_dataset();
+    let answer = json!({
+        "type": "ANIME",
+        "id": 21,
+        "title": {"romaji": "ONE PIECE"},
+        "genres": [],
+        "coverImage": {},
+        "siteUrl": "https://anilist.co/anime/21",
+        "tags": [],
+    });
+    let providers = [Fake::boxed(Ok(Some(answer)))];
+
+    let (_, trace) =
+        traced_lookup_with::<Anime>(&providers, Type::Anime, "mushishi", FetchOptions::default())
+            .unwrap();
+    assert_eq!(trace.path, MatchPath::Search);
+    assert!(trace.close_calls.is_empty());
+
+    // Nothing to offer when the index was sure
+    let (_, trace) = traced_lookup_with::<Anime>(
+        &providers,
+        Type::Anime,
+        "cowboy bebop",
+        FetchOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(trace.path, MatchPath::LocalIndex);
+    assert!(trace.close_calls.is_empty());
+}
+
+// Kitsu only knows the slug in the recorded payload
+fn kitsu_mock() -> Kitsu {
+    static BASE: OnceLock<String> = OnceLock::new();
+    let base = BASE.get_or_init(|| {
+        serve(|request| {
+            let body = match request.path.contains("filter%5Bslug%5D=cowboy-bebop") {
+                true => fixture("kitsu/anime_cowboy-bebop.json"),
+                false => json!({"data": []}),
+            };
+            Reply {
+                status: 200,
+                body: body.to_string(),
+            }
+        })
+    });
+
+    Kitsu::new(base)
+}
+
+// Answers ids it can map to AniList, like AniList itself
+struct Mapped;
+
+impl MetadataProvider for Mapped {
+    fn name(&self) -> &'static str {
+        "Mapped"
+    }
+
+    fn search(&self, _: Type, _: &str, _: &FetchOptions) -> Result<Vec<Value>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    fn get_by_id(&self, _: Type, target: &QueryTarget) -> Result<Option<Value>, ProviderError> {
+        let id = id_mapper()
+            .to_anilist(target)
+            .ok_or(ProviderError::Unsupported)?;
+        Ok(Some(json!({
+            "type": "ANIME",
+            "id": id,
+            "title": {"romaji": "Cowboy Bebop"},
+            "genres": [],
+            "coverImage": {},
+            "siteUrl": format!("https://anilist.co/anime/{}", id),
+            "tags": [],
+        })))
+    }
+
+    fn songs(&self, _: u32) -> Result<MalResponse, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+#[test]
+fn resolves_kitsu_slugs_to_ids() {
+    use_offline_dataset();
+    let kitsu = kitsu_mock();
+
+    assert_eq!(
+        kitsu.resolve_slug(Type::Anime, "cowboy-bebop"),
+        Ok(Some("1".to_string()))
+    );
+    assert_eq!(kitsu.resolve_slug(Type::Anime, "not-on-kitsu"), Ok(None));
+    assert_eq!(
+        Mapped.resolve_slug(Type::Anime, "cowboy-bebop"),
+        Err(ProviderError::Unsupported)
+    );
+
+    // The id mapper only knows the numeric id, so the slug has to be resolved to reach AniList
+    let providers: [Box<dyn MetadataProvider>; 2] = [Box::new(Mapped), Box::new(kitsu)];
+    for input in ["kitsu:cowboy-bebop", "https://kitsu.app/anime/cowboy-bebop"] {
+        let (anime, trace) =
+            traced_lookup_with::<Anime>(&providers, Type::Anime, input, FetchOptions::default())
+                .unwrap();
+        assert_eq!(anime.get_id(), Some(1), "{}", input);
+        assert_eq!(trace.path, MatchPath::Id, "{}", input);
+    }
+}
+
+#[test]
+fn ids_outside_the_formats_asked_for_are_refused() {
+    let berserk = json!({
+        "type": "MANGA",
+        "id": 30002,
+        "title": {"romaji": "Berserk"},
+        "format": "MANGA",
+        "genres": [],
+        "coverImage": {},
+        "siteUrl": "https://anilist.co/manga/30002",
+        "tags": [],
+    });
+    let providers = [Fake::boxed(Ok(Some(berserk)))];
+    let lookup = |format_in: Option<Vec<MediaFormat>>| {
+        let options = FetchOptions {
+            format_in,
+            ..Default::default()
+        };
+        traced_lookup_with::<Manga>(&providers, Type::Manga, "anilist:30002", options)
+            .map(|(manga, _)| manga.get_id())
+    };
+
+    // Like `ln` with the id of a manga
+    assert_eq!(
+        lookup(Some(vec![MediaFormat::Novel])),
+        Err(FetchError::InvalidQuery(QueryError::WrongMediaType))
+    );
+    assert_eq!(lookup(Some(vec![MediaFormat::Manga])), Ok(Some(30002)));
+    assert_eq!(lookup(None), Ok(Some(30002)));
+}
+
+// AniList answering from its replay fixtures, anything else is AniList's own miss
+fn anilist_mock() -> AniList {
+    static BASE: OnceLock<String> = OnceLock::new();
+    let base = BASE.get_or_init(|| {
+        let answers: Vec<(Value, Value)> = replay::recordings("anilist")
+            .into_iter()
+            .map(|recording| {
+                (
+                    recording["request"]["body"].clone(),
+                    recording["response"].clone(),
+                )
+            })
+            .collect();
+        serve(move |request| {
+            let body: Value = serde_json::from_str(&request.body).unwrap_or_default();
+            match answers.iter().find(|(asked, _)| *asked == body) {
+                Some((_, answer)) => Reply {
+                    status: 200,
+                    body: answer.to_string(),
+                },
+                None => not_found(),
+            }
+        })
+    });
+
+    AniList::new(base, base)
+}
+
+fn not_found() -> Reply {
+    Reply {
+        status: 404,
+        body: json!({
+            "data": {"Media": null},
+            "errors": [{"message": "Not Found.", "status": 404}],
+        })
+        .to_string(),
+    }
+}
+
+// AniList that answers every request the same way
+fn anilist_answering(reply: impl Fn(&Request) -> Reply + Send + Sync + 'static) -> AniList {
+    let base = serve(move |request| reply(&request));
+    AniList::new(&base, &base)
+}
+
+fn ids(media: Vec<Value>) -> Vec<u64> {
+    media
+        .iter()
+        .filter_map(|media| media["id"].as_u64())
+        .collect()
+}
+
+#[test]
+fn anilist_searches_every_form_of_the_title() {
+    let anilist = anilist_mock();
+    let options = FetchOptions::default();
+
+    let search = |media_type, search| anilist.search(media_type, search, &options).map(ids);
+    assert_eq!(search(Type::Anime, "cowboy bebop"), Ok(vec![1, 5]));
+    assert_eq!(search(Type::Manga, "berserk"), Ok(vec![30002]));
+    // Kana is searched as it is and romanized, the movie only turns up the second time
+    assert_eq!(search(Type::Anime, "カウボーイビバップ"), Ok(vec![1, 5]));
+    assert_eq!(search(Type::Anime, "nothing like this"), Ok(vec![]));
+}
+
+#[test]
+fn anilist_looks_up_its_own_mal_and_mapped_ids() {
+    use_offline_dataset();
+    // Sends back the variables it was asked with
+    let anilist = anilist_answering(|request| {
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        Reply {
+            status: 200,
+            body: json!({"data": {"Media": body["variables"]}}).to_string(),
+        }
+    });
+    let variables = |target| anilist.get_by_id(Type::Anime, &target);
+
+    assert_eq!(
+        variables(QueryTarget::AniList(99147)),
+        Ok(Some(json!({"id": 99147})))
+    );
+    assert_eq!(
+        variables(QueryTarget::Mal(25777)),
+        Ok(Some(json!({"idMal": 25777})))
+    );
+    assert_eq!(
+        variables(QueryTarget::Kitsu("1".to_string())),
+        Ok(Some(json!({"id": 1})))
+    );
+    // Nothing in the dataset to map it with, so AniList isn't asked at all
+    assert_eq!(
+        variables(QueryTarget::AniDb(99999)),
+        Err(ProviderError::Unsupported)
+    );
+
+    assert_eq!(
+        anilist_mock().get_by_id(Type::Anime, &QueryTarget::AniList(7)),
+        Ok(None)
+    );
+    let shingeki = anilist_mock()
+        .get_by_id(Type::Anime, &QueryTarget::AniList(99147))
+        .unwrap()
+        .unwrap();
+    assert_eq!(shingeki["title"]["romaji"], "Shingeki no Kyojin 3");
+}
+
+#[test]
+fn anilist_failures_become_provider_errors() {
+    let answering = |status: u16, body: Value| {
+        anilist_answering(move |_| Reply {
+            status,
+            body: body.to_string(),
+        })
+    };
+    let get_by_id = |anilist: AniList| anilist.get_by_id(Type::Anime, &QueryTarget::AniList(1));
+
+    assert_eq!(
+        get_by_id(answering(429, json!({}))),
+        Err(ProviderError::RateLimited)
+    );
+    assert_eq!(
+        get_by_id(answering(500, json!({}))),
+        Err(ProviderError::Unavailable)
+    );
+    assert_eq!(
+        get_by_id(answering(200, json!("not a response"))),
+        Err(ProviderError::Malformed)
+    );
+    assert_eq!(
+        get_by_id(answering(
+            400,
+            json!({"data": null, "errors": [{"message": "Variable \"$id\" got invalid value", "status": 400}]})
+        )),
+        Err(ProviderError::Rejected)
+    );
+    // Partial data is still an answer
+    assert_eq!(
+        get_by_id(answering(
+            200,
+            json!({"data": {"Media": {"id": 1}}, "errors": [{"message": "Rate limit", "status": 500}]})
+        )),
+        Ok(Some(json!({"id": 1})))
+    );
+}
+
+// Jikan answering from its replay fixtures by path
+fn jikan_mock() -> Jikan {
+    static BASE: OnceLock<String> = OnceLock::new();
+    let base = BASE.get_or_init(|| {
+        let answers: Vec<(String, Value)> = replay::recordings("jikan")
+            .into_iter()
+            .map(|recording| {
+                (
+                    recording["request"]["path"].as_str().unwrap().to_string(),
+                    recording["response"].clone(),
+                )
+            })
+            .collect();
+        serve(
+            move |request| match answers.iter().find(|(path, _)| *path == request.path) {
+                Some((_, answer)) => Reply {
+                    status: 200,
+                    body: answer.to_string(),
+                },
+                None => Reply {
+                    status: 404,
+                    body: json!({"status": 404, "message": "Resource does not exist"}).to_string(),
+                },
+            },
+        )
+    });
+
+    Jikan::new(base)
+}
+
+#[test]
+fn jikan_songs_read_like_the_official_api() {
+    let official: MalResponse = replay::recordings("mal")
+        .into_iter()
+        .find(|recording| recording["response"]["id"] == 1)
+        .map(|recording| serde_json::from_value(recording["response"].clone()).unwrap())
+        .expect("Recorded MAL songs for Cowboy Bebop");
+    let jikan = jikan_mock().songs(1).expect("Songs from Jikan");
+
+    assert_eq!(jikan.transform_title(), official.transform_title());
+    assert_eq!(jikan.transform_thumbnail(), official.transform_thumbnail());
+    assert_eq!(jikan.transform_mal_link(), official.transform_mal_link());
+    // Jikan leaves the # out of the numbered songs, the official API has it
+    assert_eq!(jikan.transform_openings(), official.transform_openings());
+    assert_eq!(jikan.transform_endings(), official.transform_endings());
+}
@@ -0,0 +1,135 @@
+// The quiz round state machine driven by a fake clock, see commands/quiz/game.rs
+
+use annie_mai::{
+    commands::quiz::game::{Clock, Difficulty, Guess, Round, RoundState},
+    models::anilist_quiz::QuizMedia,
+};
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+const PLAYER: u64 = 7;
+
+// Only moves when told to
+struct FakeClock(Cell<Instant>);
+
+impl FakeClock {
+    fn new() -> FakeClock {
+        FakeClock(Cell::new(Instant::now()))
+    }
+
+    fn advance(&self, seconds: u64) {
+        self.0.set(self.0.get() + Duration::from_secs(seconds));
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+fn round(clock: &FakeClock) -> Round {
+    let answers = vec!["Cowboy Bebop".to_string(), "カウボーイビバップ".to_string()];
+    // 45 seconds to answer
+    Round::start(answers, Difficulty::Easy, clock)
+}
+
+#[test]
+fn correct_guess_wins_the_round() {
+    let clock = FakeClock::new();
+    let mut round = round(&clock);
+
+    clock.advance(10);
+    assert_eq!(
+        round.guess(PLAYER, "  cowboy bebop ", &clock),
+        Guess::Correct
+    );
+    assert_eq!(round.state(), RoundState::Won { user_id: PLAYER });
+    assert_eq!(round.remaining(&clock), None);
+
+    // Later guesses don't take the win away
+    assert_eq!(
+        round.guess(PLAYER + 1, "Cowboy Bebop", &clock),
+        Guess::Ignored
+    );
+    clock.advance(60);
+    assert_eq!(round.tick(&clock), RoundState::Won { user_id: PLAYER });
+}
+
+#[test]
+fn wrong_guess_keeps_the_round_open() {
+    let clock = FakeClock::new();
+    let mut round = round(&clock);
+
+    assert_eq!(round.guess(PLAYER, "Trigun", &clock), Guess::Wrong);
+    assert_eq!(round.guess(PLAYER, "   ", &clock), Guess::Wrong);
+    assert_eq!(round.state(), RoundState::Running);
+
+    assert_eq!(
+        round.guess(PLAYER + 1, "カウボーイビバップ", &clock),
+        Guess::Correct
+    );
+    assert_eq!(
+        round.state(),
+        RoundState::Won {
+            user_id: PLAYER + 1
+        }
+    );
+}
+
+#[test]
+fn times_out_at_the_deadline() {
+    let clock = FakeClock::new();
+    let mut round = round(&clock);
+
+    clock.advance(44);
+    assert_eq!(round.tick(&clock), RoundState::Running);
+    assert_eq!(round.remaining(&clock), Some(Duration::from_secs(1)));
+
+    clock.advance(1);
+    assert_eq!(round.tick(&clock), RoundState::TimedOut);
+    assert_eq!(round.state(), RoundState::TimedOut);
+}
+
+#[test]
+fn ignores_guesses_after_the_deadline() {
+    let clock = FakeClock::new();
+    let mut round = round(&clock);
+
+    // Nothing ticked the round over, the guess itself notices the time
+    clock.advance(46);
+    assert_eq!(round.guess(PLAYER, "Cowboy Bebop", &clock), Guess::Ignored);
+    assert_eq!(round.state(), RoundState::TimedOut);
+}
+
+#[test]
+fn remaining_time_runs_out() {
+    let clock = FakeClock::new();
+    let mut round = round(&clock);
+
+    assert_eq!(round.remaining(&clock), Some(Duration::from_secs(45)));
+    clock.advance(30);
+    assert_eq!(round.remaining(&clock), Some(Duration::from_secs(15)));
+    clock.advance(15);
+    assert_eq!(round.remaining(&clock), None);
+    clock.advance(600);
+    assert_eq!(round.remaining(&clock), None);
+}
+
+#[test]
+fn answers_have_no_duplicates() {
+    let media: QuizMedia = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "title": {"romaji": "Cowboy Bebop", "english": "Cowboy Bebop", "native": "カウボーイビバップ"},
+        "synonyms": ["COWBOY BEBOP", "Cowboy Bebop", " ", "カウボーイビバップ"],
+        "coverImage": {},
+    }))
+    .unwrap();
+
+    assert_eq!(
+        media.answers(),
+        vec!["Cowboy Bebop", "カウボーイビバップ", "COWBOY BEBOP"]
+    );
+}
@@ -0,0 +1,209 @@
+// Real searches with the entry people meant, ranked against what AniList sent back for them, in
+// fixtures/ranking/corpus.json. Run with --nocapture to see every miss, and with RECORD_CORPUS=1
+// to search AniList again for every query and save the whole first page it answers with
+
+use annie_mai::{
+    models::{fetcher::FetchOptions, media_type::MediaType as Type},
+    utils::{
+        fuzzy::fuzzy_matcher,
+        providers::{anilist::AniList, MetadataProvider},
+        ranking::{normalize, rank, Candidate},
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{env, fs};
+
+const CORPUS: &str = "fixtures/ranking/corpus.json";
+
+// Anything lower is a regression, raise it when the ranking gets better
+const MIN_ACCURACY: f32 = 0.95;
+
+#[derive(Deserialize, Serialize)]
+struct Case {
+    query: String,
+    #[serde(rename = "type")]
+    media_type: String,
+    expected: u32,
+    // In AniList's order
+    results: Vec<Value>,
+}
+
+impl Case {
+    fn candidates(&self) -> Vec<Candidate> {
+        self.results
+            .iter()
+            .map(|result| serde_json::from_value(result.clone()).expect("Valid candidate"))
+            .collect()
+    }
+}
+
+// The fields Candidate reads, out of AniList's answer
+fn record(case: &mut Case) {
+    let media_type = match case.media_type.as_str() {
+        "manga" => Type::Manga,
+        _ => Type::Anime,
+    };
+    let results = AniList::default()
+        .search(media_type, &case.query, &FetchOptions::default())
+        .expect("AniList answered the search");
+
+    case.results = results
+        .into_iter()
+        .map(|media| {
+            json!({
+                "id": media["id"],
+                "title": media["title"],
+                "synonyms": media["synonyms"],
+                "format": media["format"],
+                "popularity": media["popularity"],
+            })
+        })
+        .collect();
+}
+
+fn corpus() -> Vec<Case> {
+    let corpus = fs::read_to_string(CORPUS).expect("Ranking corpus");
+    let mut corpus: Vec<Case> = serde_json::from_str(&corpus).expect("Valid ranking corpus");
+
+    if env::var("RECORD_CORPUS").is_ok_and(|record_corpus| record_corpus == "1") {
+        corpus.iter_mut().for_each(record);
+        fs::write(
+            CORPUS,
+            serde_json::to_string_pretty(&corpus).unwrap() + "\n",
+        )
+        .expect("Saved ranking corpus");
+    }
+    corpus
+}
+
+// What picked the result before the ranking: the closest English or romaji title when either was
+// close enough, otherwise the first exact synonym, otherwise whatever came first
+fn old_fuzzy_match(query: &str, candidates: &[Candidate]) -> Option<u32> {
+    let query = query.to_lowercase();
+    let titles = |title: fn(&Candidate) -> Option<String>| -> Vec<String> {
+        candidates
+            .iter()
+            .map(|candidate| title(candidate).unwrap_or_default())
+            .collect()
+    };
+
+    let english = fuzzy_matcher(&query, titles(|c| c.title.english.clone()), 0.5);
+    let romaji = fuzzy_matcher(&query, titles(|c| c.title.romaji.clone()), 0.5);
+    let best = [english, romaji]
+        .into_iter()
+        .flatten()
+        .reduce(|english, romaji| {
+            if english.result.similarity < romaji.result.similarity {
+                romaji
+            } else {
+                english
+            }
+        });
+    if let Some(best) = best.as_ref().filter(|best| best.result.similarity >= 0.85) {
+        return candidates[best.index].id;
+    }
+
+    let synonym = candidates.iter().position(|candidate| {
+        let synonyms = candidate.synonyms.clone().unwrap_or_default();
+        fuzzy_matcher(&query, synonyms, 1.0).is_some()
+    });
+    synonym
+        .or(best.map(|best| best.index))
+        .or((!candidates.is_empty()).then_some(0))
+        .and_then(|index| candidates[index].id)
+}
+
+fn accuracy(corpus: &[Case], pick: impl Fn(&Case) -> Option<u32>) -> f32 {
+    let hits = corpus
+        .iter()
+        .filter(|case| pick(case) == Some(case.expected))
+        .count();
+    hits as f32 / corpus.len() as f32
+}
+
+#[test]
+fn ranks_the_expected_entry_first() {
+    let corpus = corpus();
+    let mut misses = vec![];
+
+    for case in &corpus {
+        let rankings = rank(&case.query, &case.candidates());
+        let best = rankings.first().expect("Ranked results");
+        if best.id != Some(case.expected) {
+            misses.push(format!(
+                "{} {:?}: expected {}, got {:?} through {:?} ({:.3})",
+                case.media_type, case.query, case.expected, best.id, best.matched, best.score
+            ));
+        }
+    }
+
+    let accuracy = 1.0 - misses.len() as f32 / corpus.len() as f32;
+    let results: usize = corpus.iter().map(|case| case.results.len()).sum();
+    println!(
+        "Ranking accuracy {:.1}% over {} queries with {} results",
+        accuracy * 100.0,
+        corpus.len(),
+        results
+    );
+    for miss in &misses {
+        println!("  {}", miss);
+    }
+
+    assert!(
+        accuracy >= MIN_ACCURACY,
+        "Accuracy {:.3} below {}:\n{}",
+        accuracy,
+        MIN_ACCURACY,
+        misses.join("\n")
+    );
+}
+
+#[test]
+fn beats_the_baselines() {
+    let corpus = corpus();
+
+    let ranking = accuracy(&corpus, |case| rank(&case.query, &case.candidates())[0].id);
+    let anilist = accuracy(&corpus, |case| case.candidates().first()?.id);
+    let old_fuzzy_match = accuracy(&corpus, |case| {
+        old_fuzzy_match(&case.query, &case.candidates())
+    });
+    println!(
+        "Ranking {:.1}%, AniList's order {:.1}%, old fuzzy_match {:.1}%",
+        ranking * 100.0,
+        anilist * 100.0,
+        old_fuzzy_match * 100.0
+    );
+
+    assert!(ranking > anilist, "{} <= {}", ranking, anilist);
+    assert!(
+        ranking > old_fuzzy_match,
+        "{} <= {}",
+        ranking,
+        old_fuzzy_match
+    );
+}
+
+#[test]
+fn normalizes_title_variants_the_same_way() {
+    let same = [
+        ("Attack on Titan Season 2", "attack on titan 2nd season"),
+        ("Attack on Titan Season 2", "Attack on Titan II"),
+        ("Attack on Titan Season 2", "attack on titan second season"),
+        ("Ōkami to Kōshinryō", "Ookami to Koushinryou"),
+        ("SPY×FAMILY", "spy x family"),
+        ("Attack on Titan Season 1", "Attack on Titan"),
+        ("Shingeki no Kyojin 3", "Shingeki no Kyojin Season 3"),
+        ("Hunter x Hunter (2011)", "Hunter x Hunter"),
+    ];
+
+    for (title, variant) in same {
+        assert_eq!(normalize(title), normalize(variant), "{:?}", variant);
+    }
+
+    // Kana has no spaces to split words on
+    assert_eq!(
+        normalize("Shingeki no Kyojin").compact,
+        normalize("しんげきのきょじん").compact
+    );
+}
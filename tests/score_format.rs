@@ -0,0 +1,41 @@
+// AniList's 0-100 average score in every format a server or user can pick
+
+use annie_mai::models::guild_settings::ScoreFormat;
+
+#[test]
+fn formats_the_average_score() {
+    let formats = [
+        (ScoreFormat::Point100, ["0/100", "9/100", "86/100"]),
+        (ScoreFormat::Point10Decimal, ["0.0/10", "0.9/10", "8.6/10"]),
+        (ScoreFormat::Point10, ["0/10", "1/10", "9/10"]),
+        (ScoreFormat::Point5, ["0/5", "0/5", "4/5"]),
+        (ScoreFormat::Point3, ["🙁", "🙁", "🙂"]),
+        (ScoreFormat::Stars, ["☆", "☆", "⭐⭐⭐⭐"]),
+    ];
+
+    for (format, expected) in formats {
+        let formatted = [0, 9, 86].map(|score| format.format(score));
+        assert_eq!(formatted, expected, "{}", format.name());
+    }
+    assert_eq!(ScoreFormat::Point3.format(50), "😐");
+}
+
+#[test]
+fn reads_every_format_back_from_its_name() {
+    for format in [
+        ScoreFormat::Point100,
+        ScoreFormat::Point10Decimal,
+        ScoreFormat::Point10,
+        ScoreFormat::Point5,
+        ScoreFormat::Point3,
+        ScoreFormat::Stars,
+    ] {
+        assert_eq!(ScoreFormat::from_arg(format.name()), Some(format));
+    }
+    assert_eq!(
+        ScoreFormat::from_arg("10"),
+        Some(ScoreFormat::Point10Decimal)
+    );
+    assert_eq!(ScoreFormat::from_arg("POINT_3"), Some(ScoreFormat::Point3));
+    assert_eq!(ScoreFormat::from_arg("point_2"), None);
+}
@@ -0,0 +1,144 @@
+// AniList spoilers in descriptions and tags, run through the same call the embeds use
+
+mod common;
+
+use annie_mai::{
+    models::{anilist_anime::Anime, transformers::Transformers},
+    utils::{embed_budget::EmbedBudget, EMPTY_STR},
+};
+use common::replay;
+use serde_json::{json, Value};
+
+// None of the replay fixtures has a ~! spoiler yet, these are written to AniList's markup by hand:
+// name, description, how it reads with spoilers hidden
+const MARKUP: [(&str, &str, &str); 4] = [
+    (
+        "inline spoiler",
+        "The crew takes a job.<br><br>\n~!The captain dies.!~ The rest split up.",
+        "The crew takes a job.  \n\n||The captain dies.|| The rest split up.",
+    ),
+    (
+        "spoiler over a line break",
+        "Intro.<br><br>\n~!First half.<br><br>\nSecond half.!~<br><br>\n<i>Note.</i>",
+        "Intro.  \n\n||First half.||  \n\n||Second half.||  \n\n*Note.*",
+    ),
+    (
+        "italics inside a spoiler",
+        "Intro.<br><br>\n~!<i>Ending.</i>!~",
+        "Intro.  \n\n||*Ending.*||",
+    ),
+    (
+        "unterminated marker",
+        "A review with ~! and nothing after it.",
+        "A review with \\~! and nothing after it.",
+    ),
+];
+
+fn anime_with(description: &str, tags: Value) -> Anime {
+    serde_json::from_value(json!({
+        "id": 1,
+        "title": {"romaji": "Cowboy Bebop"},
+        "genres": [],
+        "coverImage": {},
+        "siteUrl": "https://anilist.co/anime/1",
+        "description": description,
+        "tags": tags,
+    }))
+    .unwrap()
+}
+
+fn description(description: &str, show_spoilers: bool) -> String {
+    anime_with(description, json!([]))
+        .transform_description_and_mal_link(show_spoilers, &mut EmbedBudget::default())
+}
+
+// Every description in the AniList replay fixtures, searches and lookups alike
+fn recorded_descriptions() -> Vec<String> {
+    fn collect(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(description)) = object.get("description") {
+                    found.push(description.to_string());
+                }
+                object.values().for_each(|value| collect(value, found));
+            }
+            Value::Array(list) => list.iter().for_each(|value| collect(value, found)),
+            _ => {}
+        }
+    }
+
+    let mut found = vec![];
+    for recording in replay::recordings("anilist") {
+        collect(&recording["response"], &mut found);
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+#[test]
+fn recorded_descriptions_come_out_as_markdown() {
+    let descriptions = recorded_descriptions();
+    assert!(!descriptions.is_empty());
+
+    for sample in descriptions {
+        let hidden = description(&sample, false);
+
+        assert_eq!(hidden, description(&sample, true), "{}", sample);
+        assert!(!hidden.contains("<br>"), "{}", sample);
+        assert!(!hidden.contains("||"), "{}", sample);
+        assert!(!hidden.contains(['\u{E000}', '\u{E001}']), "{}", sample);
+    }
+}
+
+#[test]
+fn hides_spoilers_behind_bars() {
+    for (name, sample, expected) in MARKUP {
+        let hidden = description(sample, false);
+
+        assert_eq!(hidden, expected, "{}", name);
+        assert!(!hidden.contains(['\u{E000}', '\u{E001}']), "{}", name);
+    }
+}
+
+#[test]
+fn shows_spoilers_without_markers() {
+    for (name, sample, expected) in MARKUP {
+        let shown = description(sample, true);
+
+        assert!(!shown.contains(['\u{E000}', '\u{E001}']), "{}", name);
+        // Same text either way, only the bars differ
+        assert_eq!(shown, expected.replace("||", ""), "{}", name);
+    }
+}
+
+#[test]
+fn top_tag_skips_spoiler_tags() {
+    let anime = anime_with(
+        "",
+        json!([
+            {"name": "Tragedy", "rank": 95, "isMediaSpoiler": true},
+            {"name": "Space", "rank": 90},
+            {"name": "Noir", "rank": 90},
+        ]),
+    );
+
+    // Ties go to whichever AniList listed first
+    assert_eq!(anime.transform_tags(false), "*Space*");
+    assert_eq!(anime.transform_tags(true), "*Space*");
+}
+
+#[test]
+fn top_tag_falls_back_to_a_barred_spoiler_tag() {
+    let anime = anime_with(
+        "",
+        json!([
+            {"name": "Time Loop", "rank": 80, "isGeneralSpoiler": true},
+            {"name": "Tragedy", "rank": 95, "isMediaSpoiler": true},
+        ]),
+    );
+
+    assert_eq!(anime.transform_tags(false), "||*Tragedy*||");
+    assert_eq!(anime.transform_tags(true), "*Tragedy*");
+    assert_eq!(anime_with("", json!([])).transform_tags(false), EMPTY_STR);
+}
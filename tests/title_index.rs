@@ -0,0 +1,85 @@
+// The local index searches are answered from before anything goes to AniList, see
+// utils/title_index.rs. Built from a small copy of the anime-offline-database
+
+use annie_mai::{
+    models::{fetcher::FetchOptions, media_format::MediaFormat},
+    utils::title_index::TitleIndex,
+};
+
+fn index() -> TitleIndex {
+    let json = std::fs::read_to_string("fixtures/offline/anime-offline-database.json")
+        .expect("Offline dataset fixture");
+    TitleIndex::from_json(&json).expect("Valid offline dataset")
+}
+
+fn best(index: &TitleIndex, query: &str, options: &FetchOptions) -> Option<u32> {
+    index
+        .search(query, options, 1)
+        .rankings
+        .first()
+        .and_then(|ranking| ranking.id)
+}
+
+#[test]
+fn skips_entries_without_anilist() {
+    let index = index();
+
+    assert_eq!(index.len(), 9);
+    assert!(index.complete("only on", 10).is_empty());
+}
+
+#[test]
+fn searches_titles_and_synonyms() {
+    let index = index();
+    let options = FetchOptions::default();
+    let searches = [
+        ("cowboy bebop", 1),
+        ("attack on titan", 16498),
+        ("attack on titan season 2", 20958),
+        ("進撃の巨人", 16498),
+        ("neon genesis evangelion", 30),
+        ("knockin on heavens door", 5),
+        ("one pice", 21),
+    ];
+
+    for (query, id) in searches {
+        assert_eq!(best(&index, query, &options), Some(id), "{:?}", query);
+    }
+
+    let rankings = index.search("cowboy bebop", &options, 2).rankings;
+    let ids: Vec<u32> = rankings.iter().filter_map(|ranking| ranking.id).collect();
+    assert_eq!(ids, vec![1, 5]);
+    assert!(rankings[0].title_score > rankings[1].title_score);
+}
+
+#[test]
+fn applies_format_and_year() {
+    let index = index();
+    let movies = FetchOptions {
+        format_in: Some(vec![MediaFormat::Movie]),
+        ..Default::default()
+    };
+    let from_1999 = FetchOptions {
+        year: Some(1999),
+        ..Default::default()
+    };
+
+    assert_eq!(best(&index, "cowboy bebop", &movies), Some(5));
+    assert_eq!(best(&index, "evangelion", &movies), None);
+    assert_eq!(best(&index, "hunter x hunter", &from_1999), Some(136));
+}
+
+#[test]
+fn completes_prefixes() {
+    let index = index();
+    let completions: Vec<u32> = index
+        .complete("Cowboy Be", 10)
+        .iter()
+        .filter_map(|entry| entry.candidate.id)
+        .collect();
+
+    assert_eq!(completions, vec![1, 5]);
+    assert_eq!(index.complete("shingeki no", 1).len(), 1);
+    assert!(index.complete("zzz", 10).is_empty());
+    assert!(index.complete("", 10).is_empty());
+}
@@ -0,0 +1,169 @@
+// Walking and ordering small fixture franchises, see commands/watchorder/graph.rs
+
+use annie_mai::{
+    commands::watchorder::graph::{
+        walk, Franchise, MediaNode, OrderOptions, Relation, RelationType, WalkOptions,
+    },
+    models::fuzzy_date::FuzzyDate,
+};
+
+// Every entry is a TV show that came out in the year it is given
+fn node(id: u32, year: u32) -> MediaNode {
+    let date = FuzzyDate {
+        year: Some(year),
+        month: None,
+        day: None,
+    };
+    MediaNode::new(
+        id,
+        format!("Entry {}", id),
+        Some("TV".to_string()),
+        Some(&date),
+    )
+}
+
+// (from, relation, to, year `to` came out), AniList lists relations from both ends
+type Edge = (u32, &'static str, u32, u32);
+
+fn relations_of(edges: &[Edge], id: u32) -> Option<Vec<Relation>> {
+    let relations = edges
+        .iter()
+        .filter(|(from, _, _, _)| *from == id)
+        .map(|(_, relation_type, to, year)| Relation {
+            relation_type: RelationType::from_anilist(relation_type).unwrap(),
+            target: node(*to, *year),
+        })
+        .collect();
+
+    Some(relations)
+}
+
+// Walks from `root` and remembers which ids were looked up
+fn walk_edges(root: MediaNode, options: WalkOptions, edges: &[Edge]) -> (Franchise, Vec<u32>) {
+    let mut lookups = Vec::new();
+    let franchise = walk(root, options, |id| {
+        lookups.push(id);
+        relations_of(edges, id)
+    });
+
+    (franchise, lookups)
+}
+
+fn ids(nodes: Vec<&MediaNode>) -> Vec<u32> {
+    nodes.into_iter().map(|node| node.id).collect()
+}
+
+#[test]
+fn walks_a_cycle_once() {
+    // Bad data, both entries say the other one is their sequel
+    let edges = [(1, "SEQUEL", 2, 2003), (2, "SEQUEL", 1, 2001)];
+    let (franchise, lookups) = walk_edges(node(1, 2001), WalkOptions::default(), &edges);
+
+    assert_eq!(lookups, vec![1, 2]);
+    assert_eq!(
+        ids(franchise.release_order(OrderOptions::default())),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn stops_at_max_depth() {
+    let edges = [
+        (1, "SEQUEL", 2, 2002),
+        (2, "SEQUEL", 3, 2003),
+        (3, "SEQUEL", 4, 2004),
+        (4, "SEQUEL", 5, 2005),
+    ];
+    let options = WalkOptions {
+        max_depth: 2,
+        ..Default::default()
+    };
+    let (franchise, lookups) = walk_edges(node(1, 2001), options, &edges);
+
+    // Entries at the last level are kept but not looked up
+    assert_eq!(lookups, vec![1, 2]);
+    assert_eq!(
+        ids(franchise.watch_order(OrderOptions::default())),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn stops_at_max_nodes() {
+    let edges: Vec<Edge> = (2..=10)
+        .map(|id| (1, "SIDE_STORY", id, 2000 + id))
+        .collect();
+    let options = WalkOptions {
+        max_nodes: 4,
+        ..Default::default()
+    };
+    let (franchise, lookups) = walk_edges(node(1, 2001), options, &edges);
+
+    assert_eq!(lookups, vec![1, 2, 3, 4]);
+    assert_eq!(
+        ids(franchise.release_order(OrderOptions::default())),
+        vec![1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn reaches_a_node_twice_and_keeps_it_once() {
+    // 4 hangs off both 2 and 3, it is looked up once and slotted after 2 which found it first
+    let edges = [
+        (1, "SEQUEL", 2, 2002),
+        (1, "SIDE_STORY", 3, 2003),
+        (2, "PREQUEL", 1, 2001),
+        (2, "SIDE_STORY", 4, 2010),
+        (3, "SIDE_STORY", 4, 2010),
+        (2, "SEQUEL", 5, 2005),
+    ];
+    let (franchise, lookups) = walk_edges(node(1, 2001), WalkOptions::default(), &edges);
+
+    assert_eq!(lookups, vec![1, 2, 3, 4, 5]);
+    assert_eq!(
+        ids(franchise.watch_order(OrderOptions::default())),
+        vec![1, 3, 2, 4, 5]
+    );
+    assert_eq!(
+        ids(franchise.release_order(OrderOptions::default())),
+        vec![1, 2, 3, 5, 4]
+    );
+}
+
+#[test]
+fn orders_the_main_line_by_relations_not_dates() {
+    // A prequel made years after the original still comes first in the watch order
+    let edges = [
+        (1, "PREQUEL", 2, 2015),
+        (1, "SEQUEL", 3, 2012),
+        (2, "SEQUEL", 1, 2010),
+        (3, "PREQUEL", 1, 2010),
+    ];
+    let (franchise, _) = walk_edges(node(1, 2010), WalkOptions::default(), &edges);
+
+    assert_eq!(
+        ids(franchise.watch_order(OrderOptions::default())),
+        vec![2, 1, 3]
+    );
+    assert_eq!(
+        ids(franchise.release_order(OrderOptions::default())),
+        vec![1, 3, 2]
+    );
+}
+
+#[test]
+fn falls_back_to_release_dates_in_a_cycle() {
+    // 1 and 2 are each other's sequel, the earlier one goes first and 3 still follows 2
+    let edges = [
+        (1, "SEQUEL", 2, 2003),
+        (2, "SEQUEL", 1, 2001),
+        (2, "SEQUEL", 3, 2005),
+        (3, "PREQUEL", 2, 2003),
+    ];
+    let (franchise, _) = walk_edges(node(2, 2003), WalkOptions::default(), &edges);
+
+    assert_eq!(
+        ids(franchise.watch_order(OrderOptions::default())),
+        vec![1, 2, 3]
+    );
+}